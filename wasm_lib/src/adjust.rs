@@ -0,0 +1,542 @@
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+use crate::binary::luma;
+use crate::color::{
+    linear_to_oklab, linear_to_oklab_fast, linear_to_srgb, linear_to_srgb_lookup, linear_to_srgb_table, oklab_to_linear,
+    srgb_to_linear_table,
+};
+use crate::error::{check_finite, check_float_image, check_image, check_range, check_rgba};
+use crate::memory::check_memory;
+use crate::pixels::{pixels, pixels_mut};
+use crate::settings;
+
+// ========================================================================
+// COLOR ADJUSTMENTS
+// Per-pixel tone and colour controls. Most don't need the image size, so
+// like grayscale and invert they take just the RGBA bytes.
+// ========================================================================
+
+// OKLab chroma of the most saturated sRGB colours is a little over 0.3;
+// vibrance treats anything at or above this as fully saturated
+const VIBRANCE_FULL_CHROMA: f32 = 0.25;
+
+// --- Saturation ---
+// Scales chroma in OKLab, so hue and perceived lightness stay put: -1 is
+// grayscale, 0 leaves the image alone and 1 doubles the chroma. Colours
+// pushed outside sRGB are clipped per channel.
+#[wasm_bindgen]
+pub fn apply_saturation(mut image_data: Vec<u8>, amount: f32) -> Result<Vec<u8>, JsValue> {
+    check_rgba(&image_data)?;
+    check_finite("amount", amount)?;
+    console_log!("Rust (WASM): Saturation started...");
+    let factor = (1.0 + amount).max(0.0);
+    scale_chroma(&mut image_data, |_| factor);
+    console_log!("Rust (WASM): Saturation finished.");
+    Ok(image_data)
+}
+
+// --- Vibrance ---
+// Like saturation, but the boost fades out as a colour's chroma rises, so
+// muted colours (and skin tones) gain the most and already-vivid ones
+// barely move or clip. Negative amounts mute the vivid colours first.
+#[wasm_bindgen]
+pub fn apply_vibrance(mut image_data: Vec<u8>, amount: f32) -> Result<Vec<u8>, JsValue> {
+    check_rgba(&image_data)?;
+    check_finite("amount", amount)?;
+    console_log!("Rust (WASM): Vibrance started...");
+    scale_chroma(&mut image_data, |chroma| {
+        let muted = 1.0 - (chroma / VIBRANCE_FULL_CHROMA).min(1.0);
+        let weight = if amount >= 0.0 { muted } else { 1.0 - muted };
+        (1.0 + amount * weight).max(0.0)
+    });
+    console_log!("Rust (WASM): Vibrance finished.");
+    Ok(image_data)
+}
+
+// Multiplies each pixel's OKLab chroma by `factor(chroma)`. Fast math
+// swaps the cube roots and the sRGB encoding for approximations.
+pub(crate) fn scale_chroma(image_data: &mut [u8], factor: impl Fn(f32) -> f32) {
+    let to_linear = srgb_to_linear_table();
+    let fast = settings::fast_math();
+    let encoder = SrgbEncoder::new();
+    for pixel in pixels_mut(image_data) {
+        let linear = [
+            to_linear[pixel[0] as usize],
+            to_linear[pixel[1] as usize],
+            to_linear[pixel[2] as usize],
+        ];
+        let [l, a, b] = if fast { linear_to_oklab_fast(linear) } else { linear_to_oklab(linear) };
+        let scale = factor((a * a + b * b).sqrt());
+        let rgb = oklab_to_linear([l, a * scale, b * scale]);
+        for (out, value) in pixel.iter_mut().zip(rgb) {
+            *out = encoder.encode(value);
+        }
+    }
+}
+
+// Linear -> sRGB for filters working in linear light: the exact curve, or
+// a lookup table under fast math
+pub(crate) struct SrgbEncoder(Option<Vec<u8>>);
+
+impl SrgbEncoder {
+    pub(crate) fn new() -> SrgbEncoder {
+        SrgbEncoder(settings::fast_math().then(linear_to_srgb_table))
+    }
+
+    pub(crate) fn encode(&self, value: f32) -> u8 {
+        match &self.0 {
+            Some(table) => linear_to_srgb_lookup(table, value),
+            None => linear_to_srgb(value),
+        }
+    }
+}
+
+// --- Curves ---
+// `points_json` holds up to four curves of `[input, output]` control points
+// in 0-255, e.g.
+//
+//   { "rgb": [[0, 0], [64, 48], [192, 210], [255, 255]], "blue": [[0, 20], [255, 235]] }
+//
+// "red", "green" and "blue" apply to their own channel and "rgb" to all
+// three after them; missing curves leave the image alone. Each curve needs
+// at least two points with distinct inputs and is a monotone cubic through
+// them (so it never overshoots between points), flat beyond the first and
+// last. The curves are baked into one table per channel before the pass.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CurvesSpec {
+    rgb: Option<Vec<[f32; 2]>>,
+    red: Option<Vec<[f32; 2]>>,
+    green: Option<Vec<[f32; 2]>>,
+    blue: Option<Vec<[f32; 2]>>,
+}
+
+#[wasm_bindgen]
+pub fn apply_curves(mut image_data: Vec<u8>, points_json: &str) -> Result<Vec<u8>, JsValue> {
+    check_rgba(&image_data)?;
+    let spec: CurvesSpec = serde_json::from_str(points_json)
+        .map_err(|err| JsValue::from(JsError::new(&format!("invalid curves: {}", err))))?;
+    let master = curve_lut("rgb", spec.rgb)?;
+    let channels = [
+        curve_lut("red", spec.red)?,
+        curve_lut("green", spec.green)?,
+        curve_lut("blue", spec.blue)?,
+    ];
+    let tables: Vec<[u8; 256]> = channels
+        .iter()
+        .map(|channel| {
+            let mut table = [0u8; 256];
+            for (value, out) in table.iter_mut().enumerate() {
+                *out = master[channel[value] as usize];
+            }
+            table
+        })
+        .collect();
+
+    console_log!("Rust (WASM): Curves started...");
+    for pixel in pixels_mut(&mut image_data) {
+        for (c, table) in tables.iter().enumerate() {
+            pixel[c] = table[pixel[c] as usize];
+        }
+    }
+    console_log!("Rust (WASM): Curves finished.");
+    Ok(image_data)
+}
+
+// Bakes one curve into a table; no points means the identity
+fn curve_lut(name: &str, points: Option<Vec<[f32; 2]>>) -> Result<[u8; 256], JsValue> {
+    let mut table = [0u8; 256];
+    for (value, out) in table.iter_mut().enumerate() {
+        *out = value as u8;
+    }
+    let Some(mut points) = points else {
+        return Ok(table);
+    };
+
+    let invalid = |reason: &str| JsValue::from(JsError::new(&format!("invalid curves: \"{}\" {}", name, reason)));
+    if points.len() < 2 {
+        return Err(invalid("needs at least two points"));
+    }
+    if points.iter().flatten().any(|v| !(0.0..=255.0).contains(v)) {
+        return Err(invalid("has a point outside 0-255"));
+    }
+    points.sort_by(|a, b| a[0].total_cmp(&b[0]));
+    if points.windows(2).any(|pair| pair[0][0] == pair[1][0]) {
+        return Err(invalid("has two points with the same input"));
+    }
+
+    let tangents = monotone_tangents(&points);
+    let mut segment = 0;
+    for (value, out) in table.iter_mut().enumerate() {
+        let x = value as f32;
+        let y = if x <= points[0][0] {
+            points[0][1]
+        } else if x >= points[points.len() - 1][0] {
+            points[points.len() - 1][1]
+        } else {
+            while x > points[segment + 1][0] {
+                segment += 1;
+            }
+            let ([x0, y0], [x1, y1]) = (points[segment], points[segment + 1]);
+            let h = x1 - x0;
+            let t = (x - x0) / h;
+            let (t2, t3) = (t * t, t * t * t);
+            (2.0 * t3 - 3.0 * t2 + 1.0) * y0
+                + (t3 - 2.0 * t2 + t) * h * tangents[segment]
+                + (-2.0 * t3 + 3.0 * t2) * y1
+                + (t3 - t2) * h * tangents[segment + 1]
+        };
+        *out = (y + 0.5).clamp(0.0, 255.0) as u8;
+    }
+    Ok(table)
+}
+
+// Slopes at each point for a shape-preserving (PCHIP) Hermite spline: zero
+// at local extrema, otherwise a weighted harmonic mean of the neighbouring
+// secants, which keeps every segment monotone
+fn monotone_tangents(points: &[[f32; 2]]) -> Vec<f32> {
+    let n = points.len();
+    let widths: Vec<f32> = points.windows(2).map(|pair| pair[1][0] - pair[0][0]).collect();
+    let secants: Vec<f32> = points
+        .windows(2)
+        .zip(&widths)
+        .map(|(pair, h)| (pair[1][1] - pair[0][1]) / h)
+        .collect();
+
+    let mut tangents = vec![0.0f32; n];
+    tangents[0] = secants[0];
+    tangents[n - 1] = secants[n - 2];
+    for k in 1..n - 1 {
+        let (d0, d1) = (secants[k - 1], secants[k]);
+        if d0 * d1 <= 0.0 {
+            continue;
+        }
+        let (h0, h1) = (widths[k - 1], widths[k]);
+        tangents[k] = 3.0 * (h0 + h1) / ((2.0 * h1 + h0) / d0 + (h1 + 2.0 * h0) / d1);
+    }
+    tangents
+}
+
+// --- Sepia ---
+// The classic sepia matrix, blended with the original by `intensity`
+// (0 leaves the image alone, 1 is full sepia; values outside are clamped)
+#[wasm_bindgen]
+pub fn apply_sepia(mut image_data: Vec<u8>, intensity: f32) -> Result<Vec<u8>, JsValue> {
+    check_rgba(&image_data)?;
+    console_log!("Rust (WASM): Sepia started...");
+    let amount = if intensity.is_nan() { 0.0 } else { intensity.clamp(0.0, 1.0) };
+    let matrix = [
+        [0.393, 0.769, 0.189],
+        [0.349, 0.686, 0.168],
+        [0.272, 0.534, 0.131],
+    ];
+    for pixel in pixels_mut(&mut image_data) {
+        let (r, g, b) = (pixel[0] as f32, pixel[1] as f32, pixel[2] as f32);
+        for (out, row) in pixel.iter_mut().zip(&matrix) {
+            let sepia = (row[0] * r + row[1] * g + row[2] * b).min(255.0);
+            *out = (*out as f32 + (sepia - *out as f32) * amount + 0.5) as u8;
+        }
+    }
+    console_log!("Rust (WASM): Sepia finished.");
+    Ok(image_data)
+}
+
+// --- Exposure ---
+// Brightens (positive) or darkens (negative) by `stops` the way a longer
+// or shorter exposure would: each stop doubles or halves the light, which
+// means scaling linear values, not the gamma-encoded bytes. Scaling the
+// bytes instead would brighten shadows far less than highlights. Values
+// pushed past white clip.
+#[wasm_bindgen]
+pub fn apply_exposure(mut image_data: Vec<u8>, stops: f32) -> Result<Vec<u8>, JsValue> {
+    check_rgba(&image_data)?;
+    check_finite("stops", stops)?;
+    console_log!("Rust (WASM): Exposure started...");
+    let gain = 2f32.powf(stops);
+    let encoder = SrgbEncoder::new();
+    let mut lut = [0u8; 256];
+    for (out, linear) in lut.iter_mut().zip(srgb_to_linear_table()) {
+        *out = encoder.encode(linear * gain);
+    }
+    for pixel in pixels_mut(&mut image_data) {
+        for channel in &mut pixel[..3] {
+            *channel = lut[*channel as usize];
+        }
+    }
+    console_log!("Rust (WASM): Exposure finished.");
+    Ok(image_data)
+}
+
+// --- Tone Mapping ---
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToneMapOperator {
+    // L / (1 + L) on luminance, scaling R, G and B together so hues hold;
+    // soft, never quite reaches white
+    Reinhard,
+    // Narkowicz's fit of the ACES filmic curve per channel: more contrast,
+    // and very bright colours roll off towards white like film
+    Aces,
+}
+
+// Maps a Float32Array of linear RGBA (1.0 is diffuse white, highlights go
+// beyond, alpha 0..1), such as an HDR merge or a path-traced frame, to
+// 8-bit sRGB. `exposure_stops` scales the input by 2^stops first; raise it
+// for dark renders. NaNs and negative values count as 0.
+#[wasm_bindgen]
+pub fn tone_map(
+    hdr_data: &[f32],
+    width: u32,
+    height: u32,
+    operator: ToneMapOperator,
+    exposure_stops: f32,
+) -> Result<Vec<u8>, JsValue> {
+    check_float_image(hdr_data, width, height)?;
+    check_finite("exposure_stops", exposure_stops)?;
+    check_memory("tone_map", width, height)?;
+    console_log!("Rust (WASM): Tone mapping started...");
+    let gain = 2f32.powf(exposure_stops);
+    let encoder = SrgbEncoder::new();
+    let mut result = vec![0u8; hdr_data.len()];
+    for (out, pixel) in result.chunks_exact_mut(4).zip(hdr_data.chunks_exact(4)) {
+        // `max` also turns NaN into 0
+        let rgb = [pixel[0], pixel[1], pixel[2]].map(|v| (v * gain).max(0.0));
+        let mapped = match operator {
+            ToneMapOperator::Reinhard => {
+                let luminance = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+                let scale = if luminance > 0.0 { 1.0 / (1.0 + luminance) } else { 0.0 };
+                rgb.map(|v| v * scale)
+            }
+            ToneMapOperator::Aces => rgb.map(|v| (v * (2.51 * v + 0.03)) / (v * (2.43 * v + 0.59) + 0.14)),
+        };
+        for (channel, value) in out.iter_mut().zip(mapped) {
+            *channel = encoder.encode(value);
+        }
+        out[3] = (pixel[3].clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+    }
+    console_log!("Rust (WASM): Tone mapping finished.");
+    Ok(result)
+}
+
+// --- Temperature / Tint ---
+// White balance sliders. `temperature` runs from -1 (cooler, towards
+// blue) to 1 (warmer, towards amber) and `tint` from -1 (towards green)
+// to 1 (towards magenta); 0 leaves the image alone. Temperature scales
+// R, G and B in linear light by the colour of a blackbody at about 4600 K
+// (at 1) to 9200 K (at -1) relative to daylight at 6500 K, so a warm shift
+// looks like tungsten light rather than an orange wash; tint scales green
+// by up to half a stop. The gains are normalised to keep luminance, and
+// values outside -1..1 are clamped.
+#[wasm_bindgen]
+pub fn apply_temperature_tint(mut image_data: Vec<u8>, temperature: f32, tint: f32) -> Result<Vec<u8>, JsValue> {
+    check_rgba(&image_data)?;
+    check_finite("temperature", temperature)?;
+    check_finite("tint", tint)?;
+    console_log!("Rust (WASM): Temperature / tint started...");
+    let to_linear = srgb_to_linear_table();
+    let white = |kelvin: f32| blackbody_srgb(kelvin).map(|v| to_linear[(v * 255.0).round() as usize]);
+    let (target, daylight) = (white(DAYLIGHT_KELVIN / 2f32.powf(temperature.clamp(-1.0, 1.0) / 2.0)), white(DAYLIGHT_KELVIN));
+    let mut gains = [0.0f32; 3];
+    for c in 0..3 {
+        gains[c] = target[c] / daylight[c];
+    }
+    gains[1] *= 2f32.powf(-tint.clamp(-1.0, 1.0) / 2.0);
+    let luminance = 0.2126 * gains[0] + 0.7152 * gains[1] + 0.0722 * gains[2];
+
+    let encoder = SrgbEncoder::new();
+    let luts = gains.map(|gain| {
+        let mut lut = [0u8; 256];
+        for (out, &linear) in lut.iter_mut().zip(&to_linear) {
+            *out = encoder.encode(linear * gain / luminance);
+        }
+        lut
+    });
+    for pixel in pixels_mut(&mut image_data) {
+        for (channel, lut) in pixel.iter_mut().zip(&luts) {
+            *channel = lut[*channel as usize];
+        }
+    }
+    console_log!("Rust (WASM): Temperature / tint finished.");
+    Ok(image_data)
+}
+
+// The neutral point of the temperature slider
+const DAYLIGHT_KELVIN: f32 = 6500.0;
+
+// sRGB colour (0..1) of a blackbody at `kelvin`, Tanner Helland's fit to
+// the CIE 1964 data; good to a few percent from 1000 K to 40000 K
+fn blackbody_srgb(kelvin: f32) -> [f32; 3] {
+    let t = kelvin / 100.0;
+    let red = if t <= 66.0 { 255.0 } else { 329.698_73 * (t - 60.0).powf(-0.133_204_76) };
+    let green = if t <= 66.0 { 99.470_8 * t.ln() - 161.119_57 } else { 288.122_17 * (t - 60.0).powf(-0.075_514_85) };
+    let blue = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.517_73 * (t - 10.0).ln() - 305.044_8
+    };
+    [red, green, blue].map(|v| (v / 255.0).clamp(0.0, 1.0))
+}
+
+// --- Posterize ---
+// Quantizes each colour channel to `levels_per_channel` evenly spaced
+// values (2 to 256) spanning 0..255, so 2 gives the eight primary and
+// secondary colours plus black and white. Alpha is left alone.
+#[wasm_bindgen]
+pub fn apply_posterize(mut image_data: Vec<u8>, levels_per_channel: u32) -> Result<Vec<u8>, JsValue> {
+    check_rgba(&image_data)?;
+    check_range("levels_per_channel", levels_per_channel, 2, 256)?;
+    console_log!("Rust (WASM): Posterize started...");
+    let lut = posterize_lut(levels_per_channel);
+    for pixel in pixels_mut(&mut image_data) {
+        for channel in &mut pixel[..3] {
+            *channel = lut[*channel as usize];
+        }
+    }
+    console_log!("Rust (WASM): Posterize finished.");
+    Ok(image_data)
+}
+
+// Maps each byte to the nearest of `levels` evenly spaced values
+pub(crate) fn posterize_lut(levels: u32) -> [u8; 256] {
+    let steps = (levels - 1) as f32;
+    let mut lut = [0u8; 256];
+    for (value, out) in lut.iter_mut().enumerate() {
+        let level = (value as f32 * steps / 255.0).round();
+        *out = (level * 255.0 / steps).round() as u8;
+    }
+    lut
+}
+
+// --- Equalize ---
+// Spreads the luminance out so every level is about equally common,
+// lifting contrast in flat, hazy or underexposed images. Only the Y of
+// full-range YCbCr (BT.601) is remapped and Cb / Cr are kept, which
+// comes down to shifting R, G and B by the same amount, so hues don't
+// move. An image of a single luminance is returned unchanged. Unlike the
+// per-pixel adjustments above it takes the size, to check it against
+// the data the statistics come from.
+#[wasm_bindgen]
+pub fn apply_equalize(mut image_data: Vec<u8>, width: u32, height: u32) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    console_log!("Rust (WASM): Equalize started...");
+    let luma = |pixel: &[u8; 4]| 0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32;
+    let mut histogram = [0u32; 256];
+    for pixel in pixels(&image_data) {
+        histogram[luma(pixel).round() as usize] += 1;
+    }
+
+    // Classic CDF mapping, with the darkest occupied level sent to 0
+    let total = (image_data.len() / 4) as u32;
+    let darkest = histogram.iter().copied().find(|&count| count > 0).unwrap_or(0);
+    if total > darkest {
+        let mut lut = [0.0f32; 256];
+        let mut cumulative = 0u32;
+        for (out, &count) in lut.iter_mut().zip(&histogram) {
+            cumulative += count;
+            *out = cumulative.saturating_sub(darkest) as f32 * 255.0 / (total - darkest) as f32;
+        }
+        for pixel in pixels_mut(&mut image_data) {
+            let y = luma(pixel);
+            let shift = lut[y.round() as usize] - y;
+            for channel in &mut pixel[..3] {
+                *channel = (*channel as f32 + shift).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+    console_log!("Rust (WASM): Equalize finished.");
+    Ok(image_data)
+}
+
+// --- Duotone / Gradient Map ---
+// Both recolour the image by its luminance (as `apply_grayscale` computes
+// it), reading the colour off a ramp: black maps to its start and white to
+// its end. Colours between stops are mixed in sRGB, as design tools do.
+// Each ramp colour's alpha scales the pixel's own, so a transparent stop
+// fades that part of the image out.
+
+// `shadow_rgba` and `highlight_rgba` are 0xRRGGBBAA, e.g. 0x1E3264FF and
+// 0xF037A5FF for the familiar navy to pink
+#[wasm_bindgen]
+pub fn apply_duotone(image_data: Vec<u8>, shadow_rgba: u32, highlight_rgba: u32) -> Result<Vec<u8>, JsValue> {
+    check_rgba(&image_data)?;
+    console_log!("Rust (WASM): Duotone started...");
+    let stops = [(0.0, shadow_rgba.to_be_bytes()), (1.0, highlight_rgba.to_be_bytes())];
+    let result = map_luma(image_data, &ramp(&stops));
+    console_log!("Rust (WASM): Duotone finished.");
+    Ok(result)
+}
+
+// `stops_json` lists at least one stop, each a position along the ramp
+// (0 is black, 1 white) and a "#rrggbb" or "#rrggbbaa" colour:
+//
+//   [{ "at": 0, "color": "#000000" }, { "at": 0.6, "color": "#d7263d" }, { "at": 1, "color": "#fff3b0" }]
+//
+// Stops can come in any order; the ramp is flat before the first and
+// after the last.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GradientStop {
+    at: f32,
+    color: String,
+}
+
+#[wasm_bindgen]
+pub fn apply_gradient_map(image_data: Vec<u8>, stops_json: &str) -> Result<Vec<u8>, JsValue> {
+    check_rgba(&image_data)?;
+    let invalid = |message: String| JsValue::from(JsError::new(&format!("invalid gradient stops: {}", message)));
+    let spec: Vec<GradientStop> = serde_json::from_str(stops_json).map_err(|err| invalid(err.to_string()))?;
+    if spec.is_empty() {
+        return Err(invalid("need at least one stop".to_string()));
+    }
+    let mut stops = Vec::with_capacity(spec.len());
+    for stop in &spec {
+        if !(0.0..=1.0).contains(&stop.at) {
+            return Err(invalid(format!("position {} is outside 0..1", stop.at)));
+        }
+        let color = parse_hex_color(&stop.color).ok_or_else(|| invalid(format!("bad colour \"{}\"", stop.color)))?;
+        stops.push((stop.at, color));
+    }
+    stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    console_log!("Rust (WASM): Gradient map started...");
+    let result = map_luma(image_data, &ramp(&stops));
+    console_log!("Rust (WASM): Gradient map finished.");
+    Ok(result)
+}
+
+// "#rrggbb" or "#rrggbbaa" (the `#` optional)
+fn parse_hex_color(text: &str) -> Option<[u8; 4]> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    if !matches!(hex.len(), 6 | 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    Some(if hex.len() == 6 { (value << 8 | 0xFF).to_be_bytes() } else { value.to_be_bytes() })
+}
+
+// The colour at each of the 256 luminance levels, from stops sorted by
+// position
+fn ramp(stops: &[(f32, [u8; 4])]) -> [[u8; 4]; 256] {
+    std::array::from_fn(|level| {
+        let t = level as f32 / 255.0;
+        let next = stops.iter().position(|&(at, _)| at > t).unwrap_or(stops.len());
+        if next == 0 || next == stops.len() {
+            return stops[next.min(stops.len() - 1)].1;
+        }
+        let ((start, from), (end, to)) = (stops[next - 1], stops[next]);
+        let f = (t - start) / (end - start);
+        std::array::from_fn(|c| (from[c] as f32 + (to[c] as f32 - from[c] as f32) * f).round() as u8)
+    })
+}
+
+fn map_luma(mut image_data: Vec<u8>, ramp: &[[u8; 4]; 256]) -> Vec<u8> {
+    for pixel in pixels_mut(&mut image_data) {
+        let color = ramp[luma(pixel) as usize];
+        let alpha = (pixel[3] as u32 * color[3] as u32 + 127) / 255;
+        *pixel = [color[0], color[1], color[2], alpha as u8];
+    }
+    image_data
+}
@@ -0,0 +1,629 @@
+use wasm_bindgen::prelude::*;
+use wasmfx_core::filters::sobel_gradients;
+
+use crate::binary::luma;
+use crate::error::{check_image, check_positive, check_rgba};
+use crate::geometry::Rect;
+use crate::memory::check_memory;
+use crate::pixels::pixels;
+use crate::thumbnail::resample_area;
+
+// ========================================================================
+// IMAGE ANALYSIS
+// Measurements taken from RGBA buffers that editor UIs need as glue.
+// ========================================================================
+
+// --- Color Picker / Eyedropper ---
+// Returns the average color in a circle of `radius` pixels around (x, y),
+// packed as 0xRRGGBBAA (so `toString(16)` gives a CSS hex color). Radius 0
+// samples a single pixel. Color channels are weighted by alpha so
+// transparent pixels near an edge don't drag the result towards black.
+// Points outside the image return 0.
+#[wasm_bindgen]
+pub fn sample_color(data: &[u8], width: u32, height: u32, x: i32, y: i32, radius: u32) -> Result<u32, JsValue> {
+    check_image(data, width, height)?;
+    Ok(average_circle(data, width as usize, height as usize, x, y, radius))
+}
+
+// Batch version for swatch grids: `points` is a flat [x0, y0, x1, y1, ...]
+// list and the result holds one packed color per point
+#[wasm_bindgen]
+pub fn sample_colors_batch(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    points: &[i32],
+    radius: u32,
+) -> Result<Vec<u32>, JsValue> {
+    check_image(data, width, height)?;
+    Ok(points
+        .chunks_exact(2)
+        .map(|p| average_circle(data, width as usize, height as usize, p[0], p[1], radius))
+        .collect())
+}
+
+fn average_circle(data: &[u8], width: usize, height: usize, cx: i32, cy: i32, radius: u32) -> u32 {
+    if cx < 0 || cy < 0 || cx as usize >= width || cy as usize >= height {
+        return 0;
+    }
+
+    // Any radius past width + height already covers the whole image from
+    // every point inside it, so clamp before the loops
+    let radius = (radius as usize).min(width + height).min(i32::MAX as usize) as i32;
+    let radius_sq = radius as i64 * radius as i64;
+    let mut weighted = [0u64; 3];
+    let mut plain = [0u64; 3];
+    let mut alpha_sum = 0u64;
+    let mut count = 0u64;
+
+    for dy in -radius..=radius {
+        let y = cy + dy;
+        if y < 0 || y as usize >= height {
+            continue;
+        }
+        for dx in -radius..=radius {
+            let x = cx + dx;
+            if x < 0 || x as usize >= width || (dx as i64).pow(2) + (dy as i64).pow(2) > radius_sq {
+                continue;
+            }
+            let idx = (y as usize * width + x as usize) * 4;
+            let alpha = data[idx + 3] as u64;
+            for c in 0..3 {
+                weighted[c] += data[idx + c] as u64 * alpha;
+                plain[c] += data[idx + c] as u64;
+            }
+            alpha_sum += alpha;
+            count += 1;
+        }
+    }
+
+    // Fully transparent area: fall back to the unweighted average
+    let rgb: Vec<u32> = if alpha_sum > 0 {
+        weighted.iter().map(|&s| ((s + alpha_sum / 2) / alpha_sum) as u32).collect()
+    } else {
+        plain.iter().map(|&s| ((s + count / 2) / count) as u32).collect()
+    };
+    let alpha = ((alpha_sum + count / 2) / count) as u32;
+
+    (rgb[0] << 24) | (rgb[1] << 16) | (rgb[2] << 8) | alpha
+}
+
+// --- Histogram ---
+// 256-bin counts of red, green, blue and luminance (Rec. 601 weights, as
+// the threshold filters use) in one pass, concatenated into a single
+// Uint32Array of 1024: bins 0-255 are red, 256-511 green, 512-767 blue
+// and 768-1023 luminance. Every pixel counts, whatever its alpha.
+#[wasm_bindgen]
+pub fn compute_histogram(data: &[u8]) -> Result<Vec<u32>, JsValue> {
+    check_rgba(data)?;
+    let mut histogram = vec![0u32; 256 * 4];
+    for pixel in pixels(data) {
+        histogram[pixel[0] as usize] += 1;
+        histogram[256 + pixel[1] as usize] += 1;
+        histogram[512 + pixel[2] as usize] += 1;
+        histogram[768 + luma(pixel) as usize] += 1;
+    }
+    Ok(histogram)
+}
+
+// Straightening only looks for tilts up to this many degrees; anything
+// steeper is treated as deliberate composition
+const MAX_STRAIGHTEN_DEGREES: f32 = 15.0;
+const ANGLE_BIN_DEGREES: f32 = 0.25;
+// Sobel magnitude below which a pixel isn't considered part of an edge
+const MIN_EDGE_MAGNITUDE: f32 = 40.0;
+
+// --- Straighten Suggestion ---
+// Builds a magnitude-weighted histogram of edge orientations (from Sobel
+// gradients) and finds the dominant tilt of near-horizontal and
+// near-vertical lines. Returns the rotation in degrees that levels them,
+// positive meaning clockwise, or 0.0 when there aren't enough strong edges
+// to tell.
+#[wasm_bindgen]
+pub fn suggest_straighten_angle(data: &[u8], width: u32, height: u32) -> Result<f32, JsValue> {
+    check_image(data, width, height)?;
+    console_log!("Rust (WASM): Straighten analysis started...");
+
+    let width = width as usize;
+    let height = height as usize;
+    if width < 3 || height < 3 {
+        return Ok(0.0);
+    }
+
+    let gray: Vec<f32> = data
+        .chunks_exact(4)
+        .map(|p| p[0] as f32 * 0.299 + p[1] as f32 * 0.587 + p[2] as f32 * 0.114)
+        .collect();
+
+    let bins = (2.0 * MAX_STRAIGHTEN_DEGREES / ANGLE_BIN_DEGREES) as usize + 1;
+    let mut histogram = vec![0.0f32; bins];
+
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let at = |dx: isize, dy: isize| {
+                gray[(y as isize + dy) as usize * width + (x as isize + dx) as usize]
+            };
+            let gx = at(1, -1) + 2.0 * at(1, 0) + at(1, 1) - at(-1, -1) - 2.0 * at(-1, 0) - at(-1, 1);
+            let gy = at(-1, 1) + 2.0 * at(0, 1) + at(1, 1) - at(-1, -1) - 2.0 * at(0, -1) - at(1, -1);
+            let magnitude = (gx * gx + gy * gy).sqrt();
+            if magnitude < MIN_EDGE_MAGNITUDE {
+                continue;
+            }
+
+            // The edge runs perpendicular to the gradient. Fold its angle
+            // into [-45, 45) so vertical lines vote alongside horizontal ones.
+            let edge_angle = gx.atan2(-gy).to_degrees();
+            let folded = (edge_angle + 45.0).rem_euclid(90.0) - 45.0;
+            if folded.abs() > MAX_STRAIGHTEN_DEGREES {
+                continue;
+            }
+            let bin = ((folded + MAX_STRAIGHTEN_DEGREES) / ANGLE_BIN_DEGREES).round() as usize;
+            histogram[bin.min(bins - 1)] += magnitude;
+        }
+    }
+
+    // Light smoothing so the peak isn't split across neighbouring bins
+    let smoothed: Vec<f32> = (0..bins)
+        .map(|i| {
+            let left = histogram[i.saturating_sub(1)];
+            let right = histogram[(i + 1).min(bins - 1)];
+            left + 2.0 * histogram[i] + right
+        })
+        .collect();
+
+    let (peak, &peak_value) = smoothed
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .unwrap();
+    if peak_value <= 0.0 {
+        return Ok(0.0);
+    }
+
+    // Parabolic interpolation for a sub-bin estimate
+    let mut offset = 0.0;
+    if peak > 0 && peak + 1 < bins {
+        let (l, c, r) = (smoothed[peak - 1], smoothed[peak], smoothed[peak + 1]);
+        let denom = l - 2.0 * c + r;
+        if denom.abs() > f32::EPSILON {
+            offset = 0.5 * (l - r) / denom;
+        }
+    }
+    let tilt = (peak as f32 + offset) * ANGLE_BIN_DEGREES - MAX_STRAIGHTEN_DEGREES;
+
+    console_log!("Rust (WASM): Straighten analysis finished.");
+    // In image coordinates (y down) a positive tilt slopes down to the
+    // right, so undoing it means rotating counterclockwise
+    Ok(-tilt)
+}
+
+// --- Auto Trim ---
+// Finds the bounding box of everything that differs from the border color
+// (taken from the top-left pixel) by more than `tolerance` in any channel,
+// alpha included, so both solid and transparent margins are trimmed. An
+// image that's uniform all over returns the full frame.
+#[wasm_bindgen]
+pub fn auto_trim(data: &[u8], width: u32, height: u32, tolerance: u8) -> Result<Rect, JsValue> {
+    check_image(data, width, height)?;
+    let full = Rect::new(0, 0, width, height);
+    let width = width as usize;
+    let height = height as usize;
+
+    let border = [data[0], data[1], data[2], data[3]];
+    let is_content = |x: usize, y: usize| {
+        let idx = (y * width + x) * 4;
+        data[idx..idx + 4]
+            .iter()
+            .zip(border.iter())
+            .any(|(a, b)| a.abs_diff(*b) > tolerance)
+    };
+    let row_has_content = |y: usize| (0..width).any(|x| is_content(x, y));
+    let col_has_content = |x: usize, top: usize, bottom: usize| (top..=bottom).any(|y| is_content(x, y));
+
+    let top = match (0..height).find(|&y| row_has_content(y)) {
+        Some(top) => top,
+        None => return Ok(full),
+    };
+    let bottom = (top..height).rev().find(|&y| row_has_content(y)).unwrap_or(top);
+    let left = (0..width).find(|&x| col_has_content(x, top, bottom)).unwrap_or(0);
+    let right = (left..width)
+        .rev()
+        .find(|&x| col_has_content(x, top, bottom))
+        .unwrap_or(left);
+
+    Ok(Rect::new(
+        left as i32,
+        top as i32,
+        (right - left + 1) as u32,
+        (bottom - top + 1) as u32,
+    ))
+}
+
+// Saliency is computed on a reduced grid whose longer side is at most this
+const SALIENCY_GRID: usize = 128;
+
+// --- Saliency Crop ---
+// Proposes the largest crop with the requested aspect ratio (width /
+// height) that captures the most salient content. Saliency is the
+// frequency-tuned measure of Achanta et al.: how far each (slightly
+// blurred) pixel's color is from the image's mean color.
+#[wasm_bindgen]
+pub fn suggest_crop_saliency(data: &[u8], width: u32, height: u32, aspect: f32) -> Result<Rect, JsValue> {
+    check_image(data, width, height)?;
+    check_positive("aspect", aspect)?;
+    console_log!("Rust (WASM): Saliency crop started...");
+
+    let (w, h) = (width as usize, height as usize);
+
+    // Largest window of this aspect that fits
+    let (crop_w, crop_h) = if (w as f32 / h as f32) > aspect {
+        (((h as f32 * aspect).round() as usize).clamp(1, w), h)
+    } else {
+        (w, ((w as f32 / aspect).round() as usize).clamp(1, h))
+    };
+
+    let map = saliency_map(data, w, h);
+    let (x, y) = best_window(&map, crop_w, crop_h);
+
+    console_log!("Rust (WASM): Saliency crop finished.");
+    Ok(Rect::new(x as i32, y as i32, crop_w as u32, crop_h as u32))
+}
+
+// Saliency values on a coarse grid; cell (gx, gy) covers `step` x `step`
+// source pixels starting at (gx * step, gy * step)
+pub(crate) struct SaliencyMap {
+    pub values: Vec<f32>,
+    pub grid_width: usize,
+    pub grid_height: usize,
+    pub step: usize,
+    pub source_width: usize,
+    pub source_height: usize,
+}
+
+pub(crate) fn saliency_map(data: &[u8], width: usize, height: usize) -> SaliencyMap {
+    let step = width.max(height).div_ceil(SALIENCY_GRID).max(1);
+    let grid_width = width.div_ceil(step);
+    let grid_height = height.div_ceil(step);
+
+    // Box-average each cell, which doubles as the slight blur
+    let mut cells = vec![[0.0f32; 3]; grid_width * grid_height];
+    for (gy, row) in cells.chunks_exact_mut(grid_width).enumerate() {
+        for (gx, cell) in row.iter_mut().enumerate() {
+            let mut count = 0.0;
+            for y in gy * step..((gy + 1) * step).min(height) {
+                for x in gx * step..((gx + 1) * step).min(width) {
+                    let idx = (y * width + x) * 4;
+                    for c in 0..3 {
+                        cell[c] += data[idx + c] as f32;
+                    }
+                    count += 1.0;
+                }
+            }
+            for value in cell.iter_mut() {
+                *value /= count;
+            }
+        }
+    }
+
+    let mut mean = [0.0f32; 3];
+    for cell in cells.iter() {
+        for c in 0..3 {
+            mean[c] += cell[c];
+        }
+    }
+    for value in mean.iter_mut() {
+        *value /= cells.len() as f32;
+    }
+
+    let values = cells
+        .iter()
+        .map(|cell| {
+            let dr = cell[0] - mean[0];
+            let dg = cell[1] - mean[1];
+            let db = cell[2] - mean[2];
+            (dr * dr + dg * dg + db * db).sqrt()
+        })
+        .collect();
+
+    SaliencyMap {
+        values,
+        grid_width,
+        grid_height,
+        step,
+        source_width: width,
+        source_height: height,
+    }
+}
+
+// Top-left corner (in source pixels) of the `crop_w` x `crop_h` window
+// holding the most saliency. A slight pull towards the centre breaks ties
+// on flat images.
+pub(crate) fn best_window(map: &SaliencyMap, crop_w: usize, crop_h: usize) -> (usize, usize) {
+    let (gw, gh) = (map.grid_width, map.grid_height);
+    let win_w = (crop_w / map.step).clamp(1, gw);
+    let win_h = (crop_h / map.step).clamp(1, gh);
+
+    // Summed-area table with a zero border row/column
+    let mut sat = vec![0.0f64; (gw + 1) * (gh + 1)];
+    for y in 0..gh {
+        let mut row_sum = 0.0;
+        for x in 0..gw {
+            row_sum += map.values[y * gw + x] as f64;
+            sat[(y + 1) * (gw + 1) + x + 1] = sat[y * (gw + 1) + x + 1] + row_sum;
+        }
+    }
+    let area_sum = |x: usize, y: usize| {
+        sat[(y + win_h) * (gw + 1) + x + win_w] - sat[y * (gw + 1) + x + win_w]
+            - sat[(y + win_h) * (gw + 1) + x]
+            + sat[y * (gw + 1) + x]
+    };
+
+    let (max_x, max_y) = (gw - win_w, gh - win_h);
+    let mut best = (max_x / 2, max_y / 2);
+    let mut best_score = f64::MIN;
+    for y in 0..=max_y {
+        for x in 0..=max_x {
+            let off_center = (x as f64 - max_x as f64 / 2.0).abs() + (y as f64 - max_y as f64 / 2.0).abs();
+            let score = area_sum(x, y) - off_center * 1e-6;
+            if score > best_score {
+                best_score = score;
+                best = (x, y);
+            }
+        }
+    }
+
+    // Back to source pixels, keeping the crop inside the image
+    let x = (best.0 * map.step).min(map.source_width.saturating_sub(crop_w));
+    let y = (best.1 * map.step).min(map.source_height.saturating_sub(crop_h));
+    (x, y)
+}
+
+// --- Perceptual Hash ---
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    // dHash: whether each pixel of a 9x8 thumbnail is darker than its
+    // right neighbour. Very fast, and robust to scaling and brightness.
+    Difference,
+    // pHash: which of the 8x8 lowest frequencies of a 32x32 thumbnail's
+    // DCT are above their median. Slower, but also shrugs off blur, JPEG
+    // artefacts and mild contrast or colour changes.
+    Perceptual,
+}
+
+// 64-bit fingerprint (a BigInt in JS) that barely changes when an image
+// is resized, recompressed or lightly edited, and changes a lot between
+// different images. Compare two with `hamming_distance`: up to about 10
+// differing bits usually means the same picture.
+#[wasm_bindgen]
+pub fn perceptual_hash(data: &[u8], width: u32, height: u32, algorithm: HashAlgorithm) -> Result<u64, JsValue> {
+    check_image(data, width, height)?;
+    let (w, h) = (width as usize, height as usize);
+    let gray = |out_w: usize, out_h: usize| -> Vec<f32> {
+        pixels(&resample_area(data, w, h, (0, 0, w, h), out_w, out_h))
+            .iter()
+            .map(|pixel| luma(pixel) as f32)
+            .collect()
+    };
+    let bits: Vec<bool> = match algorithm {
+        HashAlgorithm::Difference => {
+            let thumb = gray(9, 8);
+            thumb.chunks_exact(9).flat_map(|row| row.windows(2).map(|pair| pair[0] < pair[1])).collect()
+        }
+        HashAlgorithm::Perceptual => {
+            let low = dct_low_frequencies(&gray(PHASH_SIZE, PHASH_SIZE));
+            let mut sorted = low.clone();
+            sorted.sort_by(f32::total_cmp);
+            let median = (sorted[31] + sorted[32]) / 2.0;
+            low.iter().map(|&v| v > median).collect()
+        }
+    };
+    // First bit is the most significant
+    Ok(bits.iter().fold(0u64, |hash, &bit| (hash << 1) | bit as u64))
+}
+
+// Number of bits that differ between two hashes
+#[wasm_bindgen]
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+const PHASH_SIZE: usize = 32;
+
+// The top-left 8x8 coefficients (row by row) of the 2D DCT-II of a
+// PHASH_SIZE square; the normalisation is left out since only their order
+// matters
+fn dct_low_frequencies(values: &[f32]) -> Vec<f32> {
+    let n = PHASH_SIZE;
+    let basis: Vec<f32> = (0..8 * n)
+        .map(|i| {
+            let (k, x) = (i / n, i % n);
+            (std::f32::consts::PI / n as f32 * (x as f32 + 0.5) * k as f32).cos()
+        })
+        .collect();
+    // Rows first: 8 coefficients per row, then down the columns
+    let mut rows = vec![0.0f32; n * 8];
+    for y in 0..n {
+        for k in 0..8 {
+            rows[y * 8 + k] = (0..n).map(|x| values[y * n + x] * basis[k * n + x]).sum();
+        }
+    }
+    let mut low = vec![0.0f32; 64];
+    for v in 0..8 {
+        for u in 0..8 {
+            low[v * 8 + u] = (0..n).map(|y| rows[y * 8 + u] * basis[v * n + y]).sum();
+        }
+    }
+    low
+}
+
+// --- Image Comparison ---
+// Result of `compare_images`
+#[wasm_bindgen]
+pub struct ImageComparison {
+    psnr: f64,
+    ssim: f64,
+}
+
+#[wasm_bindgen]
+impl ImageComparison {
+    // Peak signal-to-noise ratio of R, G and B in dB: higher is closer,
+    // around 40 is hard to tell apart and Infinity means identical
+    #[wasm_bindgen(getter)]
+    pub fn psnr(&self) -> f64 {
+        self.psnr
+    }
+
+    // Mean structural similarity of the luminance, up to 1 for identical
+    // images. Follows perceived quality better than PSNR: a slight
+    // brightness shift costs little, blur and blocking cost a lot.
+    #[wasm_bindgen(getter)]
+    pub fn ssim(&self) -> f64 {
+        self.ssim
+    }
+}
+
+// Wang et al.'s SSIM window: an 11-tap Gaussian with sigma 1.5
+const SSIM_RADIUS: i32 = 5;
+const SSIM_SIGMA: f32 = 1.5;
+
+// How far `b` has drifted from the reference `a`, both `width x height`
+// RGBA; alpha is ignored. Good for checking what a filter chain or a
+// codec round trip costs.
+#[wasm_bindgen]
+pub fn compare_images(a: &[u8], b: &[u8], width: u32, height: u32) -> Result<ImageComparison, JsValue> {
+    check_image(a, width, height)?;
+    check_image(b, width, height)?;
+    check_memory("compare_images", width, height)?;
+    console_log!("Rust (WASM): Image comparison started...");
+    let (w, h) = (width as usize, height as usize);
+
+    let squared_error: f64 = pixels(a)
+        .iter()
+        .zip(pixels(b))
+        .map(|(p, q)| (0..3).map(|c| (p[c] as f64 - q[c] as f64).powi(2)).sum::<f64>())
+        .sum();
+    let mse = squared_error / (w * h * 3) as f64;
+    let psnr = if mse == 0.0 { f64::INFINITY } else { 10.0 * (255.0 * 255.0 / mse).log10() };
+
+    let gray = |data: &[u8]| -> Vec<f32> {
+        pixels(data)
+            .iter()
+            .map(|p| 0.299 * p[0] as f32 + 0.587 * p[1] as f32 + 0.114 * p[2] as f32)
+            .collect()
+    };
+    let (x, y) = (gray(a), gray(b));
+    let product = |p: &[f32], q: &[f32]| -> Vec<f32> { p.iter().zip(q).map(|(u, v)| u * v).collect() };
+    let kernel = ssim_kernel();
+    let smooth = |plane: &[f32]| gaussian_plane(plane, w, h, &kernel);
+    let (mean_x, mean_y) = (smooth(&x), smooth(&y));
+    let (xx, yy, xy) = (smooth(&product(&x, &x)), smooth(&product(&y, &y)), smooth(&product(&x, &y)));
+
+    let (c1, c2) = ((0.01f32 * 255.0).powi(2), (0.03f32 * 255.0).powi(2));
+    let total: f64 = (0..w * h)
+        .map(|i| {
+            let (mx, my) = (mean_x[i], mean_y[i]);
+            let (var_x, var_y, cov) = (xx[i] - mx * mx, yy[i] - my * my, xy[i] - mx * my);
+            let ssim = ((2.0 * mx * my + c1) * (2.0 * cov + c2)) / ((mx * mx + my * my + c1) * (var_x + var_y + c2));
+            ssim as f64
+        })
+        .sum();
+    console_log!("Rust (WASM): Image comparison finished.");
+    Ok(ImageComparison {
+        psnr,
+        ssim: total / (w * h) as f64,
+    })
+}
+
+fn ssim_kernel() -> Vec<f32> {
+    let weights: Vec<f32> = (-SSIM_RADIUS..=SSIM_RADIUS)
+        .map(|d| (-((d * d) as f32) / (2.0 * SSIM_SIGMA * SSIM_SIGMA)).exp())
+        .collect();
+    let total: f32 = weights.iter().sum();
+    weights.iter().map(|w| w / total).collect()
+}
+
+// Separable blur of a float plane, repeating the edge pixels
+fn gaussian_plane(plane: &[f32], width: usize, height: usize, kernel: &[f32]) -> Vec<f32> {
+    let tap = |i: usize, k: usize, len: usize| (i + k).saturating_sub(SSIM_RADIUS as usize).min(len - 1);
+    let mut temp = vec![0.0f32; plane.len()];
+    for y in 0..height {
+        let row = &plane[y * width..][..width];
+        for x in 0..width {
+            temp[y * width + x] = kernel.iter().enumerate().map(|(k, weight)| weight * row[tap(x, k, width)]).sum();
+        }
+    }
+    let mut result = vec![0.0f32; plane.len()];
+    for y in 0..height {
+        for x in 0..width {
+            result[y * width + x] =
+                kernel.iter().enumerate().map(|(k, weight)| weight * temp[tap(y, k, height) * width + x]).sum();
+        }
+    }
+    result
+}
+
+// --- Sharpness ---
+// Result of `measure_sharpness`. Both scores only mean something relative
+// to each other for the same scene and size: compare the frames of a
+// burst or the steps of a focus sweep, and keep the highest.
+#[wasm_bindgen]
+pub struct Sharpness {
+    laplacian_variance: f64,
+    tenengrad: f64,
+}
+
+#[wasm_bindgen]
+impl Sharpness {
+    // Variance of the luminance's 4-neighbour Laplacian: cheap, and drops
+    // quickly as fine detail blurs away, but noise also raises it
+    #[wasm_bindgen(getter)]
+    pub fn laplacian_variance(&self) -> f64 {
+        self.laplacian_variance
+    }
+
+    // Mean squared Sobel gradient magnitude; the Sobel kernels average
+    // across the edge, so it holds up better on noisy frames
+    #[wasm_bindgen(getter)]
+    pub fn tenengrad(&self) -> f64 {
+        self.tenengrad
+    }
+}
+
+#[wasm_bindgen]
+pub fn measure_sharpness(image_data: &[u8], width: u32, height: u32) -> Result<Sharpness, JsValue> {
+    check_image(image_data, width, height)?;
+    check_memory("measure_sharpness", width, height)?;
+    console_log!("Rust (WASM): Sharpness measurement started...");
+    let (w, h) = (width as usize, height as usize);
+    let gray: Vec<f32> = pixels(image_data)
+        .iter()
+        .map(|p| 0.299 * p[0] as f32 + 0.587 * p[1] as f32 + 0.114 * p[2] as f32)
+        .collect();
+
+    // Edge pixels repeat outwards, as in `sobel_gradients`
+    let (mut sum, mut sum_sq) = (0.0f64, 0.0f64);
+    for y in 0..h {
+        let (up, down) = (y.saturating_sub(1), (y + 1).min(h - 1));
+        for x in 0..w {
+            let (left, right) = (x.saturating_sub(1), (x + 1).min(w - 1));
+            let laplacian = gray[y * w + left] + gray[y * w + right] + gray[up * w + x] + gray[down * w + x]
+                - 4.0 * gray[y * w + x];
+            sum += laplacian as f64;
+            sum_sq += (laplacian as f64).powi(2);
+        }
+    }
+    let count = (w * h) as f64;
+    let mean = sum / count;
+
+    let tenengrad = sobel_gradients(image_data, w, h)
+        .iter()
+        .map(|&(gx, gy)| (gx * gx + gy * gy) as f64)
+        .sum::<f64>()
+        / count;
+    console_log!("Rust (WASM): Sharpness measurement finished.");
+    Ok(Sharpness {
+        laplacian_variance: (sum_sq / count - mean * mean).max(0.0),
+        tenengrad,
+    })
+}
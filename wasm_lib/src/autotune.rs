@@ -0,0 +1,191 @@
+use wasm_bindgen::prelude::*;
+
+use crate::parallel::pool_size;
+use crate::settings::{
+    apply_tuning, is_tuned, set_simd_enabled, set_thread_count, simd_enabled, thread_count,
+    tile_size,
+};
+
+// ========================================================================
+// AUTO-TUNING
+// Picks per-device performance settings by timing small calibration
+// workloads, and lets hosts save/restore the result (e.g. in localStorage)
+// so the calibration only ever runs once per device.
+// ========================================================================
+
+// Prefix identifying a tuning blob and its format version
+const BLOB_HEADER: &str = "wasmfx-tuning/1";
+// Strip widths tried for cache-blocked passes
+const TILE_CANDIDATES: [u32; 6] = [16, 32, 64, 128, 256, 512];
+// Calibration image size: wide enough that untiled column passes thrash
+const CALIBRATION_WIDTH: usize = 1024;
+const CALIBRATION_HEIGHT: usize = 128;
+const CALIBRATION_TAPS: i32 = 4;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TuningProfile {
+    pub tile_size: u32,
+    pub thread_count: u32,
+    pub simd: bool,
+}
+
+// --- autotune ---
+// Runs the calibration on first use and applies the winner; afterwards (or
+// once a blob has been imported) it just returns the active profile.
+//
+// With a worker pool running it also times the blur at each power-of-two
+// thread count up to the pool size (more workers isn't always faster on
+// small images or throttled phones), and SIMD builds race the vector
+// kernels against the scalar ones, since some engines emulate SIMD slowly.
+#[wasm_bindgen]
+pub fn autotune() -> TuningProfile {
+    if is_tuned() {
+        return current_tuning();
+    }
+
+    console_log!("Rust (WASM): Auto-tuning started...");
+
+    let source: Vec<u8> = (0..CALIBRATION_WIDTH * CALIBRATION_HEIGHT * 4)
+        .map(|i| (i * 31 % 251) as u8)
+        .collect();
+    let mut target = vec![0u8; source.len()];
+
+    let mut best_tile = TILE_CANDIDATES[0];
+    let mut best_time = f64::MAX;
+    for &tile in TILE_CANDIDATES.iter() {
+        // Best of two runs to dodge one-off GC / JIT pauses on the JS side
+        let mut elapsed = f64::MAX;
+        for _ in 0..2 {
+            let start = crate::now();
+            vertical_box_pass(&source, &mut target, tile as usize);
+            std::hint::black_box(&target);
+            elapsed = elapsed.min(crate::now() - start);
+        }
+        if elapsed < best_time {
+            best_time = elapsed;
+            best_tile = tile;
+        }
+    }
+
+    let mut thread_candidates = vec![1];
+    while thread_candidates.last().unwrap() * 2 <= pool_size() {
+        thread_candidates.push(thread_candidates.last().unwrap() * 2);
+    }
+    if pool_size() > *thread_candidates.last().unwrap() {
+        thread_candidates.push(pool_size());
+    }
+
+    let mut best_threads = 1;
+    if thread_candidates.len() > 1 {
+        let mut best_time = f64::MAX;
+        let mut pixels = source.clone();
+        for &threads in thread_candidates.iter() {
+            set_thread_count(threads as u32);
+            let start = crate::now();
+            crate::blur_pixels(&mut pixels, CALIBRATION_WIDTH as u32, CALIBRATION_HEIGHT as u32, 3);
+            std::hint::black_box(&pixels);
+            let elapsed = crate::now() - start;
+            if elapsed < best_time {
+                best_time = elapsed;
+                best_threads = threads;
+            }
+        }
+    }
+
+    let use_simd = cfg!(feature = "simd") && {
+        let mut pixels = source.clone();
+        let mut time_grayscale = |simd: bool| {
+            set_simd_enabled(simd);
+            let mut elapsed = f64::MAX;
+            for _ in 0..2 {
+                let start = crate::now();
+                crate::grayscale_pixels(&mut pixels);
+                std::hint::black_box(&pixels);
+                elapsed = elapsed.min(crate::now() - start);
+            }
+            elapsed
+        };
+        time_grayscale(true) < time_grayscale(false)
+    };
+
+    apply_tuning(best_tile, best_threads as u32, use_simd);
+
+    console_log!(
+        "Rust (WASM): Auto-tuning finished (tile size {}, {} threads, SIMD {}).",
+        best_tile,
+        best_threads,
+        use_simd
+    );
+    current_tuning()
+}
+
+#[wasm_bindgen]
+pub fn current_tuning() -> TuningProfile {
+    TuningProfile {
+        tile_size: tile_size() as u32,
+        thread_count: thread_count(),
+        simd: simd_enabled(),
+    }
+}
+
+// Serialize the active profile, e.g. "wasmfx-tuning/1 tile=64 threads=1 simd=0"
+#[wasm_bindgen]
+pub fn export_tuning() -> String {
+    let profile = current_tuning();
+    format!(
+        "{} tile={} threads={} simd={}",
+        BLOB_HEADER,
+        profile.tile_size,
+        profile.thread_count,
+        profile.simd as u8
+    )
+}
+
+// Restore a profile saved with `export_tuning`. Returns false (leaving the
+// current settings alone) if the blob is malformed or from another version.
+#[wasm_bindgen]
+pub fn import_tuning(blob: &str) -> bool {
+    let mut parts = blob.split_whitespace();
+    if parts.next() != Some(BLOB_HEADER) {
+        return false;
+    }
+
+    let mut tile = None;
+    let mut threads = None;
+    let mut simd = None;
+    for part in parts {
+        match part.split_once('=') {
+            Some(("tile", value)) => tile = value.parse::<u32>().ok(),
+            Some(("threads", value)) => threads = value.parse::<u32>().ok(),
+            Some(("simd", value)) => simd = value.parse::<u8>().ok().map(|v| v != 0),
+            _ => return false,
+        }
+    }
+
+    match (tile, threads, simd) {
+        (Some(tile), Some(threads), Some(simd)) if tile > 0 && threads > 0 => {
+            apply_tuning(tile, threads, simd);
+            true
+        }
+        _ => false,
+    }
+}
+
+// Same access pattern as the blur's vertical pass: a box filter down each
+// column, walking the image in strips `tile` pixels wide
+fn vertical_box_pass(source: &[u8], target: &mut [u8], tile: usize) {
+    let (width, height) = (CALIBRATION_WIDTH, CALIBRATION_HEIGHT);
+    for x_start in (0..width).step_by(tile) {
+        for y in 0..height {
+            for x in x_start..(x_start + tile).min(width) {
+                let mut sum = 0u32;
+                for dy in -CALIBRATION_TAPS..=CALIBRATION_TAPS {
+                    let ny = (y as i32 + dy).clamp(0, height as i32 - 1) as usize;
+                    sum += source[(ny * width + x) * 4] as u32;
+                }
+                target[(y * width + x) * 4] = (sum / (2 * CALIBRATION_TAPS as u32 + 1)) as u8;
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use wasm_bindgen::prelude::*;
+use wasmfx_core::bench;
+
+use crate::cancel::{cancelled, CancellationToken};
+use crate::random;
+
+// ========================================================================
+// PURE COMPUTATIONAL BENCHMARKS (No Image Processing!) (`bench` feature)
+// These demonstrate WASM's raw computational power.
+// The workloads themselves are in `wasmfx_core::bench`, which natively
+// runs them under `cargo bench` too. Each takes an optional cancellation
+// token, checked every `STOP_CHECK_INTERVAL` iterations, and returns what
+// it has so far when cancelled.
+// ========================================================================
+
+// --- Benchmark 1: Prime Number Generation (CPU Intensive) ---
+#[wasm_bindgen]
+pub fn calculate_primes(limit: u32, token: Option<CancellationToken>) -> Vec<u32> {
+    console_log!("Rust (WASM): Prime calculation started...");
+    let primes = bench::calculate_primes(limit, || cancelled(token.as_ref()));
+    console_log!("Rust (WASM): Prime calculation finished.");
+    primes
+}
+
+// --- Benchmark 2: Matrix Multiplication (Linear Algebra) ---
+// Checks for cancellation once per result row; unfinished rows stay zero
+#[wasm_bindgen]
+pub fn matrix_multiply(size: u32, token: Option<CancellationToken>) -> Vec<f64> {
+    console_log!("Rust (WASM): Matrix multiplication started...");
+    let result = bench::matrix_multiply(size as usize, || cancelled(token.as_ref()));
+    console_log!("Rust (WASM): Matrix multiplication finished.");
+    result
+}
+
+// --- Benchmark 3: Fibonacci (Recursive/Memoization) ---
+#[wasm_bindgen]
+pub fn fibonacci_sequence(count: u32, token: Option<CancellationToken>) -> Vec<u64> {
+    console_log!("Rust (WASM): Fibonacci calculation started...");
+    let sequence = bench::fibonacci_sequence(count, || cancelled(token.as_ref()));
+    console_log!("Rust (WASM): Fibonacci calculation finished.");
+    sequence
+}
+
+// --- Benchmark 4: SHA-256 Hash-like Computation (Bitwise Operations) ---
+#[wasm_bindgen]
+pub fn compute_hashes(iterations: u32, token: Option<CancellationToken>) -> u32 {
+    console_log!("Rust (WASM): Hash computation started...");
+    let hash = bench::compute_hashes(iterations, || cancelled(token.as_ref()));
+    console_log!("Rust (WASM): Hash computation finished.");
+    hash
+}
+
+// --- Benchmark 5: Monte Carlo Pi Estimation (Random + Math) ---
+// A cancelled run estimates from the samples it drew
+#[wasm_bindgen]
+pub fn estimate_pi(samples: u32, token: Option<CancellationToken>) -> f64 {
+    console_log!("Rust (WASM): Pi estimation started...");
+    let seed = random::stream_seed(123456789);
+    let pi_estimate = bench::estimate_pi(samples, seed, || cancelled(token.as_ref()));
+    console_log!("Rust (WASM): Pi estimation finished.");
+    pi_estimate
+}
+
+// --- Benchmark 6: QuickSort (Algorithm Performance) ---
+// The sort itself can't be interrupted, so cancellation is only noticed
+// while generating the input, and returns an empty array
+#[wasm_bindgen]
+pub fn sort_array(size: u32, token: Option<CancellationToken>) -> Vec<i32> {
+    console_log!("Rust (WASM): Array sorting started...");
+    let arr = bench::sort_array(size, random::stream_seed(42), || cancelled(token.as_ref()));
+    console_log!("Rust (WASM): Array sorting finished.");
+    arr
+}
+
+// --- Benchmark 7: String Processing (Real-world data manipulation) ---
+#[wasm_bindgen]
+pub fn process_text(iterations: u32, token: Option<CancellationToken>) -> String {
+    console_log!("Rust (WASM): Text processing started...");
+    let result = bench::process_text(iterations, || cancelled(token.as_ref()));
+    console_log!("Rust (WASM): Text processing finished.");
+    result
+}
@@ -0,0 +1,135 @@
+use wasm_bindgen::prelude::*;
+use wasmfx_core::filters::gaussian_kernel;
+
+use crate::error::{check_image, check_radius};
+use crate::memory::check_memory;
+use crate::pixels::{pixels, pixels_mut, rows_mut};
+
+// ========================================================================
+// BINARY IMAGES
+// Filters that split an image into foreground and background, the usual
+// first step for document scanning, shape analysis and edge cleanup.
+// Output pixels are pure black or white with the original alpha.
+// ========================================================================
+
+// Same integer luminance as `apply_grayscale`
+pub(crate) fn luma(pixel: &[u8; 4]) -> u8 {
+    ((pixel[0] as u32 * 299 + pixel[1] as u32 * 587 + pixel[2] as u32 * 114) / 1000) as u8
+}
+
+// --- Global Threshold ---
+// White where the luminance is above `value`, black elsewhere
+#[wasm_bindgen]
+pub fn apply_threshold(mut image_data: Vec<u8>, width: u32, height: u32, value: u8) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    console_log!("Rust (WASM): Threshold started...");
+    for pixel in pixels_mut(&mut image_data) {
+        let level = if luma(pixel) > value { 255 } else { 0 };
+        pixel[..3].fill(level);
+    }
+    console_log!("Rust (WASM): Threshold finished.");
+    Ok(image_data)
+}
+
+// --- Adaptive Threshold ---
+// How `apply_adaptive_threshold` averages the neighbourhood
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdaptiveMethod {
+    Mean,
+    Gaussian,
+}
+
+// Compares each pixel with the average luminance around it instead of one
+// global level, so uneven lighting (shadows across a scanned page) doesn't
+// swallow half the image: white where the pixel is brighter than the local
+// average minus `c`. The neighbourhood is the (2 * radius + 1)^2 window
+// (Mean) or a Gaussian with sigma = radius / 3, the same kernel as
+// `blur` (Gaussian). A positive `c` keeps faint texture in flat areas
+// white.
+#[wasm_bindgen]
+pub fn apply_adaptive_threshold(
+    mut image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    method: AdaptiveMethod,
+    radius: u32,
+    c: f32,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_radius(radius)?;
+    check_memory("adaptive_threshold", width, height)?;
+    console_log!("Rust (WASM): Adaptive threshold started...");
+    let (w, h) = (width as usize, height as usize);
+    let lumas: Vec<u8> = pixels(&image_data).iter().map(luma).collect();
+    let local = match method {
+        AdaptiveMethod::Mean => local_mean(&lumas, w, h, radius as usize),
+        // Taps past the image only repeat its border, so the kernel is
+        // capped at the image's size
+        AdaptiveMethod::Gaussian => local_gaussian(&lumas, w, h, radius.min(width.max(height)) as i32),
+    };
+    for (y, row) in rows_mut(&mut image_data, w) {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let i = y * w + x;
+            let level = if lumas[i] as f32 > local[i] - c { 255 } else { 0 };
+            pixel[..3].fill(level);
+        }
+    }
+    console_log!("Rust (WASM): Adaptive threshold finished.");
+    Ok(image_data)
+}
+
+// Window means from a summed-area table; windows are cut off at the image
+// edges rather than padded
+fn local_mean(lumas: &[u8], width: usize, height: usize, radius: usize) -> Vec<f32> {
+    let stride = width + 1;
+    let mut sums = vec![0u64; stride * (height + 1)];
+    for y in 0..height {
+        let mut row_sum = 0u64;
+        for x in 0..width {
+            row_sum += lumas[y * width + x] as u64;
+            sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row_sum;
+        }
+    }
+
+    let mut means = vec![0.0f32; width * height];
+    for y in 0..height {
+        let (y0, y1) = (y.saturating_sub(radius), (y + radius + 1).min(height));
+        for x in 0..width {
+            let (x0, x1) = (x.saturating_sub(radius), (x + radius + 1).min(width));
+            let total = sums[y1 * stride + x1] + sums[y0 * stride + x0] - sums[y0 * stride + x1] - sums[y1 * stride + x0];
+            means[y * width + x] = total as f32 / ((x1 - x0) * (y1 - y0)) as f32;
+        }
+    }
+    means
+}
+
+// Separable Gaussian over the luminance plane, clamping at the edges
+fn local_gaussian(lumas: &[u8], width: usize, height: usize, radius: i32) -> Vec<f32> {
+    let kernel = gaussian_kernel(radius, 1);
+    let mut horizontal = vec![0.0f32; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            horizontal[y * width + x] = kernel
+                .iter()
+                .map(|&(dx, weight)| {
+                    let nx = (x as i32 + dx).clamp(0, width as i32 - 1) as usize;
+                    lumas[y * width + nx] as f32 * weight
+                })
+                .sum();
+        }
+    }
+    let mut result = vec![0.0f32; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            result[y * width + x] = kernel
+                .iter()
+                .map(|&(dy, weight)| {
+                    let ny = (y as i32 + dy).clamp(0, height as i32 - 1) as usize;
+                    horizontal[ny * width + x] * weight
+                })
+                .sum();
+        }
+    }
+    result
+}
@@ -0,0 +1,199 @@
+use wasm_bindgen::prelude::*;
+
+use crate::cancel::CancellationToken;
+use crate::denoise::for_each_output_row;
+use crate::error::{check_finite, check_image, Error};
+use crate::memory::check_memory;
+use crate::pixels::{pixels, Pixel};
+
+// ========================================================================
+// BLUR EFFECTS
+// Blurs that smear along a direction instead of spreading evenly like the
+// Gaussian / box / stack blurs, so they need their own sampling: every
+// output pixel averages bilinear samples taken along a line (or, for the
+// radial blur, an arc) through it.
+// Like the other blurs they work on R, G and B and keep alpha; if
+// cancelled, rows not reached yet keep their original pixels.
+// ========================================================================
+
+// Bilinear sample at (x, y), clamping to the nearest edge pixel outside
+// the image
+pub(crate) fn sample(source: &[Pixel], width: usize, height: usize, x: f32, y: f32) -> [f32; 3] {
+    let x = x.clamp(0.0, (width - 1) as f32);
+    let y = y.clamp(0.0, (height - 1) as f32);
+    let (x0, y0) = (x as usize, y as usize);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+    let at = |x: usize, y: usize| &source[y * width + x];
+    let mut out = [0.0f32; 3];
+    for (c, value) in out.iter_mut().enumerate() {
+        let top = at(x0, y0)[c] as f32 * (1.0 - fx) + at(x1, y0)[c] as f32 * fx;
+        let bottom = at(x0, y1)[c] as f32 * (1.0 - fx) + at(x1, y1)[c] as f32 * fx;
+        *value = top * (1.0 - fy) + bottom * fy;
+    }
+    out
+}
+
+// --- Motion Blur ---
+// Simulates the camera moving `length` pixels in a straight line during
+// the exposure: each pixel averages `length` samples one pixel apart along
+// a line centred on it. `angle_degrees` is the direction of travel, 0
+// being horizontal and positive angles turning counter-clockwise.
+// `length` is capped at the image diagonal.
+#[wasm_bindgen]
+pub fn apply_motion_blur(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    angle_degrees: f32,
+    length: u32,
+    token: Option<CancellationToken>,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    if length == 0 {
+        return Err(Error::NotPositive { name: "length" }.into());
+    }
+    check_finite("angle_degrees", angle_degrees)?;
+    check_memory("motion_blur", width, height)?;
+    console_log!("Rust (WASM): Motion blur started...");
+    let (w, h) = (width as usize, height as usize);
+    // Beyond the diagonal the extra samples all land past the edges, so a
+    // longer streak would only cost time
+    let length = length.min((w as f64).hypot(h as f64).ceil() as u32).max(1);
+    let angle = angle_degrees.to_radians();
+    // Image rows go down, so a counter-clockwise angle moves up
+    let (dx, dy) = (angle.cos(), -angle.sin());
+    let offsets: Vec<f32> = (0..length).map(|i| i as f32 - (length - 1) as f32 / 2.0).collect();
+
+    let source = pixels(&image_data);
+    let mut result = image_data.clone();
+    for_each_output_row(&mut result, w, h, token.as_ref(), |y, out_row| {
+        for (x, out) in out_row.iter_mut().enumerate() {
+            let mut sums = [0.0f32; 3];
+            for &t in &offsets {
+                let value = sample(source, w, h, x as f32 + t * dx, y as f32 + t * dy);
+                for (sum, v) in sums.iter_mut().zip(value) {
+                    *sum += v;
+                }
+            }
+            for (channel, sum) in out.iter_mut().zip(sums) {
+                *channel = (sum / length as f32 + 0.5) as u8;
+            }
+        }
+    });
+    console_log!("Rust (WASM): Motion blur finished.");
+    Ok(result)
+}
+
+// --- Zoom And Radial Blur ---
+// Both blur around a centre point (`center_x`, `center_y`, in pixels; it
+// may lie outside the image), so the centre stays sharp and the blur grows
+// towards the edges. Pixels far from the centre take one sample per pixel
+// of streak, up to this many.
+const MAX_PATH_SAMPLES: usize = 64;
+
+// Average of `count` samples at `point(t)` for t evenly spread over 0..1
+fn average_along(
+    source: &[Pixel],
+    width: usize,
+    height: usize,
+    count: usize,
+    point: impl Fn(f32) -> (f32, f32),
+) -> [f32; 3] {
+    let mut sums = [0.0f32; 3];
+    for i in 0..count {
+        let t = if count > 1 { i as f32 / (count - 1) as f32 } else { 0.0 };
+        let (x, y) = point(t);
+        for (sum, v) in sums.iter_mut().zip(sample(source, width, height, x, y)) {
+            *sum += v;
+        }
+    }
+    sums.map(|sum| sum / count as f32)
+}
+
+fn path_samples(length: f32) -> usize {
+    (length.ceil() as usize).clamp(1, MAX_PATH_SAMPLES)
+}
+
+// Streaks every pixel towards the centre, like zooming the lens during the
+// exposure: each pixel averages the line from itself `strength` of the way
+// to the centre (0 leaves the image unchanged, 1 reaches the centre;
+// values outside 0..1 are clamped).
+#[wasm_bindgen]
+pub fn apply_zoom_blur(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    center_x: f32,
+    center_y: f32,
+    strength: f32,
+    token: Option<CancellationToken>,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_finite("center_x", center_x)?;
+    check_finite("center_y", center_y)?;
+    check_finite("strength", strength)?;
+    check_memory("zoom_blur", width, height)?;
+    console_log!("Rust (WASM): Zoom blur started...");
+    let (w, h) = (width as usize, height as usize);
+    let strength = strength.clamp(0.0, 1.0);
+
+    let source = pixels(&image_data);
+    let mut result = image_data.clone();
+    for_each_output_row(&mut result, w, h, token.as_ref(), |y, out_row| {
+        for (x, out) in out_row.iter_mut().enumerate() {
+            let (px, py) = (x as f32, y as f32);
+            let (dx, dy) = ((center_x - px) * strength, (center_y - py) * strength);
+            let count = path_samples(dx.hypot(dy));
+            let value = average_along(source, w, h, count, |t| (px + t * dx, py + t * dy));
+            for (channel, v) in out.iter_mut().zip(value) {
+                *channel = (v + 0.5) as u8;
+            }
+        }
+    });
+    console_log!("Rust (WASM): Zoom blur finished.");
+    Ok(result)
+}
+
+// Spins the image around the centre: each pixel averages the arc of
+// `angle_degrees` through it (half on either side) on the circle about the
+// centre, so the blur is strongest far from the centre.
+#[wasm_bindgen]
+pub fn apply_radial_blur(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    center_x: f32,
+    center_y: f32,
+    angle_degrees: f32,
+    token: Option<CancellationToken>,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_finite("center_x", center_x)?;
+    check_finite("center_y", center_y)?;
+    check_finite("angle_degrees", angle_degrees)?;
+    check_memory("radial_blur", width, height)?;
+    console_log!("Rust (WASM): Radial blur started...");
+    let (w, h) = (width as usize, height as usize);
+    let sweep = angle_degrees.abs().min(360.0).to_radians();
+
+    let source = pixels(&image_data);
+    let mut result = image_data.clone();
+    for_each_output_row(&mut result, w, h, token.as_ref(), |y, out_row| {
+        for (x, out) in out_row.iter_mut().enumerate() {
+            let (rx, ry) = (x as f32 - center_x, y as f32 - center_y);
+            let radius = rx.hypot(ry);
+            let start = ry.atan2(rx) - sweep / 2.0;
+            let count = path_samples(radius * sweep);
+            let value = average_along(source, w, h, count, |t| {
+                let (sin, cos) = (start + t * sweep).sin_cos();
+                (center_x + radius * cos, center_y + radius * sin)
+            });
+            for (channel, v) in out.iter_mut().zip(value) {
+                *channel = (v + 0.5) as u8;
+            }
+        }
+    });
+    console_log!("Rust (WASM): Radial blur finished.");
+    Ok(result)
+}
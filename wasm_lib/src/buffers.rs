@@ -0,0 +1,90 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_radius, check_rgba, image_len, Error};
+use crate::memory::check_memory;
+use crate::{blur_pixels, grayscale_pixels, invert_pixels};
+
+// ========================================================================
+// ZERO-COPY BUFFERS
+// Passing a `Vec<u8>` copies the whole image into WASM memory and the
+// result back out again. For per-frame work JS can instead allocate a
+// buffer here once, write ImageData straight into it through a view on
+// `wasm_memory()`, and run the `_inplace` filters on it.
+//
+// Typical use:
+//   const ptr = alloc_buffer(len);
+//   new Uint8ClampedArray(wasm_memory().buffer, ptr, len).set(imageData.data);
+//   apply_grayscale_inplace(ptr, len);
+//   imageData.data.set(new Uint8ClampedArray(wasm_memory().buffer, ptr, len));
+//
+// Any allocation can grow WASM memory, which detaches existing views, so
+// create views right before using them rather than caching them.
+// ========================================================================
+
+// The module's linear memory, for building typed-array views on buffers
+#[wasm_bindgen]
+pub fn wasm_memory() -> JsValue {
+    wasm_bindgen::memory()
+}
+
+// Allocate a zeroed buffer of `len` bytes that stays valid until it's
+// passed to `free_buffer` with the same length
+#[wasm_bindgen]
+pub fn alloc_buffer(len: usize) -> *mut u8 {
+    Box::into_raw(vec![0u8; len].into_boxed_slice()) as *mut u8
+}
+
+/// # Safety
+/// `ptr` and `len` must come from one `alloc_buffer` call, and the buffer
+/// must not be used afterwards.
+#[wasm_bindgen]
+pub unsafe fn free_buffer(ptr: *mut u8, len: usize) {
+    if !ptr.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)));
+    }
+}
+
+/// # Safety
+/// `ptr`/`len` must describe a live buffer from `alloc_buffer`.
+#[wasm_bindgen]
+pub unsafe fn apply_grayscale_inplace(ptr: *mut u8, len: usize) -> Result<(), JsValue> {
+    let pixels = buffer_mut(ptr, len)?;
+    check_rgba(pixels)?;
+    console_log!("Rust: Grayscale filter (in place) started...");
+    grayscale_pixels(pixels);
+    console_log!("Rust: Grayscale filter (in place) finished.");
+    Ok(())
+}
+
+/// # Safety
+/// `ptr`/`len` must describe a live buffer from `alloc_buffer`.
+#[wasm_bindgen]
+pub unsafe fn apply_invert_inplace(ptr: *mut u8, len: usize) -> Result<(), JsValue> {
+    let pixels = buffer_mut(ptr, len)?;
+    check_rgba(pixels)?;
+    console_log!("Rust: Invert filter (in place) started...");
+    invert_pixels(pixels);
+    console_log!("Rust: Invert filter (in place) finished.");
+    Ok(())
+}
+
+/// # Safety
+/// `ptr` must point to a live buffer from `alloc_buffer` holding at least
+/// `width * height * 4` bytes.
+#[wasm_bindgen]
+pub unsafe fn apply_blur_inplace(ptr: *mut u8, width: u32, height: u32, radius: u32) -> Result<(), JsValue> {
+    let pixels = buffer_mut(ptr, image_len(width, height)?)?;
+    check_radius(radius)?;
+    check_memory("blur", width, height)?;
+    console_log!("Rust (WASM): Gaussian blur (in place) started...");
+    blur_pixels(pixels, width, height, radius);
+    console_log!("Rust (WASM): Gaussian blur (in place) finished.");
+    Ok(())
+}
+
+unsafe fn buffer_mut<'a>(ptr: *mut u8, len: usize) -> Result<&'a mut [u8], Error> {
+    if ptr.is_null() {
+        return Err(Error::NullBuffer);
+    }
+    Ok(std::slice::from_raw_parts_mut(ptr, len))
+}
@@ -0,0 +1,133 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+
+// ========================================================================
+// CANCELLATION
+// Long-running exports take an optional trailing `CancellationToken` and
+// check it every so often, stopping early with a partial result. A WASM
+// call can't be interrupted by the thread running it, so cancellation has
+// to come from somewhere the computation can poll:
+//
+//   // Shared flag: run the filter in a worker, cancel from the page
+//   const flag = new Int32Array(new SharedArrayBuffer(4));
+//   const token = CancellationToken.from_shared(flag);   // in the worker
+//   Atomics.store(flag, 0, 1);                            // on the page
+//
+//   // Callback: polled during the computation, truthy means stop
+//   const token = CancellationToken.with_callback(() => performance.now() > deadline);
+//
+// Passing a token hands it over to the call (or options object), so pass
+// `token.handle()` to keep using the original afterwards:
+//
+//   const options = new BlurOptions(8);
+//   options.set_token(token.handle());
+//   const pixels = blur(data, width, height, options);
+//   if (token.is_cancelled) { /* only partly blurred */ }
+//
+// Once a token has seen a cancellation it stays cancelled until `reset()`.
+// ========================================================================
+
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct CancellationToken {
+    state: Rc<TokenState>,
+}
+
+#[derive(Default)]
+struct TokenState {
+    shared: Option<js_sys::Int32Array>,
+    callback: Option<js_sys::Function>,
+    cancelled: Cell<bool>,
+}
+
+#[wasm_bindgen]
+impl CancellationToken {
+    // A token that's only cancelled through `cancel()`
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    // Cancelled once element 0 of `flag` (over a SharedArrayBuffer) is
+    // non-zero; any thread holding the array can set it
+    pub fn from_shared(flag: js_sys::Int32Array) -> CancellationToken {
+        CancellationToken {
+            state: Rc::new(TokenState {
+                shared: Some(flag),
+                ..TokenState::default()
+            }),
+        }
+    }
+
+    // Cancelled as soon as `callback()` returns a truthy value
+    pub fn with_callback(callback: js_sys::Function) -> CancellationToken {
+        CancellationToken {
+            state: Rc::new(TokenState {
+                callback: Some(callback),
+                ..TokenState::default()
+            }),
+        }
+    }
+
+    // Another reference to the same token, for passing into a computation
+    pub fn handle(&self) -> CancellationToken {
+        CancellationToken {
+            state: Rc::clone(&self.state),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.state.cancelled.set(true);
+        if let Some(flag) = &self.state.shared {
+            let _ = js_sys::Atomics::store(flag, 0, 1);
+        }
+    }
+
+    // Clear the cancellation (and the shared flag) so the token can be reused
+    pub fn reset(&self) {
+        self.state.cancelled.set(false);
+        if let Some(flag) = &self.state.shared {
+            let _ = js_sys::Atomics::store(flag, 0, 0);
+        }
+    }
+
+    // Whether the last computation given this token stopped early (or
+    // whether the token has been cancelled since)
+    #[wasm_bindgen(getter)]
+    pub fn is_cancelled(&self) -> bool {
+        self.poll()
+    }
+}
+
+impl CancellationToken {
+    fn poll(&self) -> bool {
+        if self.state.cancelled.get() {
+            return true;
+        }
+
+        let shared = self
+            .state
+            .shared
+            .as_ref()
+            .is_some_and(|flag| js_sys::Atomics::load(flag, 0).is_ok_and(|value| value != 0));
+        let requested = shared
+            || self
+                .state
+                .callback
+                .as_ref()
+                .is_some_and(|callback| callback.call0(&JsValue::NULL).is_ok_and(|value| value.is_truthy()));
+
+        if requested {
+            self.state.cancelled.set(true);
+        }
+        requested
+    }
+}
+
+// For the periodic checks inside long-running loops; no token means the
+// computation can't be cancelled
+pub(crate) fn cancelled(token: Option<&CancellationToken>) -> bool {
+    token.is_some_and(CancellationToken::poll)
+}
@@ -0,0 +1,116 @@
+use wasm_bindgen::prelude::*;
+pub use wasmfx_core::color::*;
+
+use crate::error::check_rgba;
+use crate::pixels::pixels;
+
+// ========================================================================
+// COLOR
+// The per-colour conversions from `wasmfx_core::color`, plus buffer-level
+// converters for JS. `rgb_to_*` turn RGBA bytes into a Float32Array of
+// four values per pixel, the colour's three components followed by alpha
+// in 0..1, and `*_to_rgb` take such an array back to bytes (clipping
+// colours that fall outside sRGB). The components are:
+//
+//   hsl, hsv  hue in degrees (0..360), saturation and lightness / value 0..1
+//   lab       CIELAB under D65: L 0..100, a and b roughly -128..128
+//   xyz       CIE XYZ, with the sRGB white at Y = 1
+//   ycbcr     JPEG's full-range BT.601: Y, Cb and Cr in 0..255, chroma
+//             centred on 128
+// ========================================================================
+
+// `convert` gets each pixel's R, G and B in 0..1
+fn to_floats(image_data: &[u8], convert: impl Fn([f32; 3]) -> [f32; 3]) -> Result<Vec<f32>, JsValue> {
+    check_rgba(image_data)?;
+    let mut result = Vec::with_capacity(image_data.len());
+    for pixel in pixels(image_data) {
+        let components = convert([pixel[0], pixel[1], pixel[2]].map(|c| c as f32 / 255.0));
+        result.extend_from_slice(&components);
+        result.push(pixel[3] as f32 / 255.0);
+    }
+    Ok(result)
+}
+
+// `convert` returns R, G and B in 0..1
+fn from_floats(data: &[f32], convert: impl Fn([f32; 3]) -> [f32; 3]) -> Result<Vec<u8>, JsValue> {
+    check_rgba(data)?;
+    let byte = |v: f32| (v * 255.0).round().clamp(0.0, 255.0) as u8;
+    let mut result = Vec::with_capacity(data.len());
+    for values in data.chunks_exact(4) {
+        let rgb = convert([values[0], values[1], values[2]]);
+        result.extend(rgb.map(byte));
+        result.push(byte(values[3]));
+    }
+    Ok(result)
+}
+
+// Lab and XYZ are defined on linear light; the `to_floats` input is whole
+// bytes, so a table decodes it
+fn decoder() -> impl Fn([f32; 3]) -> [f32; 3] {
+    let to_linear = srgb_to_linear_table();
+    move |rgb| rgb.map(|c| to_linear[(c * 255.0).round() as usize])
+}
+
+fn encode(linear: [f32; 3]) -> [f32; 3] {
+    linear.map(|c| linear_to_srgb(c) as f32 / 255.0)
+}
+
+// --- HSL / HSV ---
+#[wasm_bindgen]
+pub fn rgb_to_hsl(image_data: &[u8]) -> Result<Vec<f32>, JsValue> {
+    to_floats(image_data, wasmfx_core::color::rgb_to_hsl)
+}
+
+#[wasm_bindgen]
+pub fn hsl_to_rgb(data: &[f32]) -> Result<Vec<u8>, JsValue> {
+    from_floats(data, wasmfx_core::color::hsl_to_rgb)
+}
+
+#[wasm_bindgen]
+pub fn rgb_to_hsv(image_data: &[u8]) -> Result<Vec<f32>, JsValue> {
+    to_floats(image_data, wasmfx_core::color::rgb_to_hsv)
+}
+
+#[wasm_bindgen]
+pub fn hsv_to_rgb(data: &[f32]) -> Result<Vec<u8>, JsValue> {
+    from_floats(data, wasmfx_core::color::hsv_to_rgb)
+}
+
+// --- XYZ / Lab ---
+#[wasm_bindgen]
+pub fn rgb_to_xyz(image_data: &[u8]) -> Result<Vec<f32>, JsValue> {
+    let decode = decoder();
+    to_floats(image_data, |rgb| linear_to_xyz(decode(rgb)))
+}
+
+#[wasm_bindgen]
+pub fn xyz_to_rgb(data: &[f32]) -> Result<Vec<u8>, JsValue> {
+    from_floats(data, |xyz| encode(xyz_to_linear(xyz)))
+}
+
+#[wasm_bindgen]
+pub fn rgb_to_lab(image_data: &[u8]) -> Result<Vec<f32>, JsValue> {
+    let decode = decoder();
+    to_floats(image_data, |rgb| xyz_to_lab(linear_to_xyz(decode(rgb))))
+}
+
+#[wasm_bindgen]
+pub fn lab_to_rgb(data: &[f32]) -> Result<Vec<u8>, JsValue> {
+    from_floats(data, |lab| encode(xyz_to_linear(lab_to_xyz(lab))))
+}
+
+// --- YCbCr ---
+#[wasm_bindgen]
+pub fn rgb_to_ycbcr(image_data: &[u8]) -> Result<Vec<f32>, JsValue> {
+    to_floats(image_data, |rgb| {
+        let [y, cb, cr] = wasmfx_core::color::rgb_to_ycbcr(rgb);
+        [y * 255.0, cb * 255.0 + 128.0, cr * 255.0 + 128.0]
+    })
+}
+
+#[wasm_bindgen]
+pub fn ycbcr_to_rgb(data: &[f32]) -> Result<Vec<u8>, JsValue> {
+    from_floats(data, |[y, cb, cr]| {
+        wasmfx_core::color::ycbcr_to_rgb([y / 255.0, (cb - 128.0) / 255.0, (cr - 128.0) / 255.0])
+    })
+}
@@ -0,0 +1,160 @@
+use wasm_bindgen::prelude::*;
+
+use crate::adjust::SrgbEncoder;
+use crate::color::{linear_to_oklab, linear_to_oklab_fast, oklab_to_linear, srgb_to_linear_table};
+use crate::error::{check_image, check_rgba};
+use crate::memory::check_memory;
+use crate::pixels::{pixels, pixels_mut, Pixel};
+use crate::settings;
+
+// ========================================================================
+// COLOR TRANSFER
+// Gives one photo the colour "look" of another. `source` is the reference
+// and can be any size; `target` (width x height) is the image recoloured
+// and returned. Fully transparent pixels don't count towards either
+// image's statistics, and alpha is left alone.
+// ========================================================================
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferMethod {
+    // Matches the mean and spread of each Lab channel (Reinhard et al.,
+    // "Color Transfer between Images"): a gentle overall cast and contrast
+    Reinhard,
+    // Maps each RGB channel so its histogram matches the source's: a
+    // closer, stronger match that can posterize smooth gradients
+    Histogram,
+}
+
+#[wasm_bindgen]
+pub fn transfer_color(
+    source: &[u8],
+    mut target: Vec<u8>,
+    width: u32,
+    height: u32,
+    method: TransferMethod,
+) -> Result<Vec<u8>, JsValue> {
+    check_rgba(source)?;
+    check_image(&target, width, height)?;
+    check_memory("transfer_color", width, height)?;
+    console_log!("Rust (WASM): Color transfer started...");
+    match method {
+        TransferMethod::Reinhard => reinhard(pixels(source), pixels_mut(&mut target)),
+        TransferMethod::Histogram => match_histograms(pixels(source), pixels_mut(&mut target)),
+    }
+    console_log!("Rust (WASM): Color transfer finished.");
+    Ok(target)
+}
+
+fn visible(pixel: &&Pixel) -> bool {
+    pixel[3] > 0
+}
+
+// --- Reinhard ---
+// Statistics in OKLab, the perceptual space the other colour adjustments
+// use, standing in for the paper's l-alpha-beta
+fn reinhard(source: &[Pixel], target: &mut [Pixel]) {
+    let to_linear = srgb_to_linear_table();
+    let fast = settings::fast_math();
+    let to_lab = |pixel: &Pixel| {
+        let linear = [
+            to_linear[pixel[0] as usize],
+            to_linear[pixel[1] as usize],
+            to_linear[pixel[2] as usize],
+        ];
+        if fast { linear_to_oklab_fast(linear) } else { linear_to_oklab(linear) }
+    };
+
+    let Some((source_mean, source_std)) = lab_stats(source.iter().filter(visible).map(to_lab)) else {
+        return;
+    };
+    let target_lab: Vec<[f32; 3]> = target.iter().map(to_lab).collect();
+    let Some((target_mean, target_std)) = lab_stats(target.iter().zip(&target_lab).filter(|(p, _)| visible(p)).map(|(_, lab)| *lab))
+    else {
+        return;
+    };
+
+    let encoder = SrgbEncoder::new();
+    for (pixel, lab) in target.iter_mut().zip(&target_lab) {
+        let mut moved = [0.0f32; 3];
+        for c in 0..3 {
+            // A flat channel has nothing to stretch, so it's only shifted
+            let scale = if target_std[c] > 1e-6 { source_std[c] / target_std[c] } else { 1.0 };
+            moved[c] = (lab[c] - target_mean[c]) * scale + source_mean[c];
+        }
+        for (out, value) in pixel.iter_mut().zip(oklab_to_linear(moved)) {
+            *out = encoder.encode(value);
+        }
+    }
+}
+
+// Per-channel mean and standard deviation, or `None` with no pixels
+fn lab_stats(labs: impl Iterator<Item = [f32; 3]>) -> Option<([f32; 3], [f32; 3])> {
+    let mut count = 0u64;
+    let mut sum = [0.0f64; 3];
+    let mut sum_sq = [0.0f64; 3];
+    for lab in labs {
+        count += 1;
+        for c in 0..3 {
+            sum[c] += lab[c] as f64;
+            sum_sq[c] += lab[c] as f64 * lab[c] as f64;
+        }
+    }
+    if count == 0 {
+        return None;
+    }
+    let n = count as f64;
+    let mean = sum.map(|s| s / n);
+    let std = [0, 1, 2].map(|c| (sum_sq[c] / n - mean[c] * mean[c]).max(0.0).sqrt() as f32);
+    Some((mean.map(|m| m as f32), std))
+}
+
+// --- Histogram Matching ---
+fn match_histograms(source: &[Pixel], target: &mut [Pixel]) {
+    let source_cdf = channel_cdfs(source);
+    let target_cdf = channel_cdfs(target);
+    let (Some(source_cdf), Some(target_cdf)) = (source_cdf, target_cdf) else {
+        return;
+    };
+
+    // Each target level goes to the first source level whose cumulative
+    // share reaches the target level's
+    let mut luts = [[0u8; 256]; 3];
+    for c in 0..3 {
+        let mut level = 0;
+        for value in 0..256 {
+            while level < 255 && source_cdf[c][level] < target_cdf[c][value] {
+                level += 1;
+            }
+            luts[c][value] = level as u8;
+        }
+    }
+    for pixel in target.iter_mut() {
+        for c in 0..3 {
+            pixel[c] = luts[c][pixel[c] as usize];
+        }
+    }
+}
+
+// Cumulative share (0..1) of the visible pixels at or below each level,
+// per channel; `None` with no visible pixels
+fn channel_cdfs(image: &[Pixel]) -> Option<[[f64; 256]; 3]> {
+    let mut counts = [[0u64; 256]; 3];
+    let mut total = 0u64;
+    for pixel in image.iter().filter(visible) {
+        for c in 0..3 {
+            counts[c][pixel[c] as usize] += 1;
+        }
+        total += 1;
+    }
+    if total == 0 {
+        return None;
+    }
+    Some(counts.map(|channel| {
+        let mut running = 0u64;
+        channel.map(|count| {
+            running += count;
+            running as f64 / total as f64
+        })
+    }))
+}
@@ -0,0 +1,455 @@
+use wasm_bindgen::prelude::*;
+use wasmfx_core::color::{rgb_to_ycbcr, ycbcr_to_rgb};
+use wasmfx_core::filters::gaussian_kernel;
+
+use crate::binary::luma;
+use crate::error::{check_finite, check_image, check_range, check_rgba, image_len, MAX_RADIUS};
+use crate::memory::check_memory;
+use crate::pixels::{pixels, pixels_mut, Pixel};
+
+// ========================================================================
+// COMPOSITING
+// Layers one image over another with the blend modes of the W3C
+// Compositing and Blending spec, the ones canvas
+// `globalCompositeOperation` and CSS `mix-blend-mode` offer, so results
+// match what the browser would draw. The mode mixes the two colours where
+// both are present; the layer is then drawn source-over, so where the
+// base is transparent it shows as is. Colours are mixed as sRGB values,
+// like the browser does. `LayerStack` does the same for any number of
+// positioned layers. `render_drop_shadow` puts a shadow under an image,
+// and `apply_chroma_key` cuts a subject out of a green or blue screen to
+// go on top.
+// ========================================================================
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    // The layer covers the base
+    #[default]
+    Normal,
+    // Darkens: white leaves the base alone, black gives black
+    Multiply,
+    // Lightens: the inverse of multiply on inverted colours
+    Screen,
+    // Multiply in the base's shadows, screen in its highlights, so the
+    // base's contrast survives
+    Overlay,
+    // A gentler overlay, like lighting the base with a diffuse spotlight
+    SoftLight,
+    // Overlay with the two images swapped: the layer decides
+    HardLight,
+    Darken,
+    Lighten,
+    // Brightens the base to reflect the layer
+    ColorDodge,
+    // Darkens the base to reflect the layer
+    ColorBurn,
+    // Absolute difference; identical images give black
+    Difference,
+    // Like difference with less contrast
+    Exclusion,
+}
+
+impl BlendMode {
+    // B(Cb, Cs) from the spec, on 0..1 channel values
+    fn mix(self, base: f32, layer: f32) -> f32 {
+        match self {
+            BlendMode::Normal => layer,
+            BlendMode::Multiply => base * layer,
+            BlendMode::Screen => base + layer - base * layer,
+            BlendMode::Overlay => BlendMode::HardLight.mix(layer, base),
+            BlendMode::HardLight => {
+                if layer <= 0.5 {
+                    BlendMode::Multiply.mix(base, 2.0 * layer)
+                } else {
+                    BlendMode::Screen.mix(base, 2.0 * layer - 1.0)
+                }
+            }
+            BlendMode::SoftLight => {
+                if layer <= 0.5 {
+                    base - (1.0 - 2.0 * layer) * base * (1.0 - base)
+                } else {
+                    let d = if base <= 0.25 { ((16.0 * base - 12.0) * base + 4.0) * base } else { base.sqrt() };
+                    base + (2.0 * layer - 1.0) * (d - base)
+                }
+            }
+            BlendMode::Darken => base.min(layer),
+            BlendMode::Lighten => base.max(layer),
+            BlendMode::ColorDodge => {
+                if base == 0.0 {
+                    0.0
+                } else if layer >= 1.0 {
+                    1.0
+                } else {
+                    (base / (1.0 - layer)).min(1.0)
+                }
+            }
+            BlendMode::ColorBurn => {
+                if base >= 1.0 {
+                    1.0
+                } else if layer <= 0.0 {
+                    0.0
+                } else {
+                    1.0 - ((1.0 - base) / layer).min(1.0)
+                }
+            }
+            BlendMode::Difference => (base - layer).abs(),
+            BlendMode::Exclusion => base + layer - 2.0 * base * layer,
+        }
+    }
+}
+
+// Draws `layer` over `base` with `mode`, its alpha scaled by `opacity`
+// (0..1)
+pub(crate) fn composite_pixel(base: &mut Pixel, layer: &Pixel, mode: BlendMode, opacity: f32) {
+    let layer_alpha = layer[3] as f32 / 255.0 * opacity;
+    if layer_alpha <= 0.0 {
+        return;
+    }
+    let base_alpha = base[3] as f32 / 255.0;
+    let alpha = layer_alpha + base_alpha * (1.0 - layer_alpha);
+    for c in 0..3 {
+        let (cb, cs) = (base[c] as f32 / 255.0, layer[c] as f32 / 255.0);
+        let blended = (1.0 - base_alpha) * cs + base_alpha * mode.mix(cb, cs);
+        let premultiplied = layer_alpha * blended + (1.0 - layer_alpha) * base_alpha * cb;
+        base[c] = (premultiplied / alpha * 255.0).round().clamp(0.0, 255.0) as u8;
+    }
+    base[3] = (alpha * 255.0).round() as u8;
+}
+
+// --- Blend Images ---
+// `overlay` is drawn over `base`, both `width x height` RGBA, with
+// `opacity` from 0 (base unchanged) to 1 (values outside are clamped)
+#[wasm_bindgen]
+pub fn blend_images(
+    mut base: Vec<u8>,
+    overlay: &[u8],
+    width: u32,
+    height: u32,
+    mode: BlendMode,
+    opacity: f32,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&base, width, height)?;
+    check_image(overlay, width, height)?;
+    check_finite("opacity", opacity)?;
+    check_memory("blend_images", width, height)?;
+    console_log!("Rust (WASM): Blend ({:?}) started...", mode);
+    let opacity = opacity.clamp(0.0, 1.0);
+    for (pixel, layer) in pixels_mut(&mut base).iter_mut().zip(pixels(overlay)) {
+        composite_pixel(pixel, layer, mode, opacity);
+    }
+    console_log!("Rust (WASM): Blend finished.");
+    Ok(base)
+}
+
+// --- Layer Stack ---
+// A canvas-sized stack of layers composited in one pass, instead of a
+// canvas `drawImage` per layer:
+//
+//   const stack = new LayerStack(1280, 720);
+//   const photo = stack.add_layer(photoPixels, 1280, 720);
+//   const sticker = stack.add_layer(stickerPixels, 200, 200);
+//   stack.set_position(sticker, 900, 40);
+//   stack.set_blend_mode(sticker, BlendMode.Screen);
+//   stack.set_opacity(sticker, 0.8);
+//   const frame = stack.composite();
+//
+// Layers are drawn bottom first onto a transparent canvas, in the order
+// they were added, and may hang off its edges. The stack keeps its own
+// copy of each layer's pixels; `update_layer` swaps them for animation.
+// Layer indices stay valid until a layer below them is removed.
+#[wasm_bindgen]
+pub struct LayerStack {
+    width: u32,
+    height: u32,
+    layers: Vec<Layer>,
+}
+
+struct Layer {
+    data: Vec<u8>,
+    width: usize,
+    height: usize,
+    // Canvas position of the layer's top-left pixel
+    x: i32,
+    y: i32,
+    opacity: f32,
+    mode: BlendMode,
+    // Per-pixel opacity (0..1) from the mask's luminance
+    mask: Option<Vec<f32>>,
+    visible: bool,
+}
+
+#[wasm_bindgen]
+impl LayerStack {
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: u32, height: u32) -> Result<LayerStack, JsValue> {
+        image_len(width, height)?;
+        Ok(LayerStack { width, height, layers: Vec::new() })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // Number of layers
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> u32 {
+        self.layers.len() as u32
+    }
+
+    // Adds a `width x height` RGBA layer on top, at the canvas origin,
+    // fully opaque and in `Normal` mode; returns its index
+    pub fn add_layer(&mut self, data: Vec<u8>, width: u32, height: u32) -> Result<u32, JsValue> {
+        check_image(&data, width, height)?;
+        self.layers.push(Layer {
+            data,
+            width: width as usize,
+            height: height as usize,
+            x: 0,
+            y: 0,
+            opacity: 1.0,
+            mode: BlendMode::Normal,
+            mask: None,
+            visible: true,
+        });
+        Ok(self.layers.len() as u32 - 1)
+    }
+
+    // New pixels for a layer, the same size as before
+    pub fn update_layer(&mut self, index: u32, data: Vec<u8>) -> Result<(), JsValue> {
+        let layer = self.layer(index)?;
+        check_image(&data, layer.width as u32, layer.height as u32)?;
+        layer.data = data;
+        Ok(())
+    }
+
+    pub fn remove_layer(&mut self, index: u32) -> Result<(), JsValue> {
+        self.layer(index)?;
+        self.layers.remove(index as usize);
+        Ok(())
+    }
+
+    pub fn set_position(&mut self, index: u32, x: i32, y: i32) -> Result<(), JsValue> {
+        let layer = self.layer(index)?;
+        (layer.x, layer.y) = (x, y);
+        Ok(())
+    }
+
+    // 0 hides the layer, 1 (the default) draws it at its own alpha; values
+    // outside are clamped
+    pub fn set_opacity(&mut self, index: u32, opacity: f32) -> Result<(), JsValue> {
+        check_finite("opacity", opacity)?;
+        self.layer(index)?.opacity = opacity.clamp(0.0, 1.0);
+        Ok(())
+    }
+
+    pub fn set_blend_mode(&mut self, index: u32, mode: BlendMode) -> Result<(), JsValue> {
+        self.layer(index)?.mode = mode;
+        Ok(())
+    }
+
+    // An RGBA image the layer's size whose luminance scales its opacity
+    // (white shows the layer, black hides it), as in a layer mask; `None`
+    // removes the mask
+    pub fn set_mask(&mut self, index: u32, mask: Option<Vec<u8>>) -> Result<(), JsValue> {
+        let layer = self.layer(index)?;
+        layer.mask = match mask {
+            Some(mask) => {
+                check_image(&mask, layer.width as u32, layer.height as u32)?;
+                Some(pixels(&mask).iter().map(|pixel| luma(pixel) as f32 / 255.0).collect())
+            }
+            None => None,
+        };
+        Ok(())
+    }
+
+    pub fn set_visible(&mut self, index: u32, visible: bool) -> Result<(), JsValue> {
+        self.layer(index)?.visible = visible;
+        Ok(())
+    }
+
+    // The canvas-sized RGBA result
+    pub fn composite(&self) -> Result<Vec<u8>, JsValue> {
+        check_memory("layer_stack", self.width, self.height)?;
+        console_log!("Rust (WASM): Layer stack ({} layers) started...", self.layers.len());
+        let (w, h) = (self.width as i64, self.height as i64);
+        let mut canvas = vec![0u8; image_len(self.width, self.height)?];
+        let canvas_pixels = pixels_mut(&mut canvas);
+        for layer in self.layers.iter().filter(|layer| layer.visible && layer.opacity > 0.0) {
+            // The part of the layer that lands on the canvas
+            let (left, top) = (layer.x as i64, layer.y as i64);
+            let columns = left.max(0)..(left + layer.width as i64).min(w);
+            let rows = top.max(0)..(top + layer.height as i64).min(h);
+            let source = pixels(&layer.data);
+            for y in rows {
+                let layer_row = (y - top) as usize * layer.width;
+                for x in columns.clone() {
+                    let i = layer_row + (x - left) as usize;
+                    let opacity = layer.opacity * layer.mask.as_ref().map_or(1.0, |mask| mask[i]);
+                    composite_pixel(&mut canvas_pixels[(y * w + x) as usize], &source[i], layer.mode, opacity);
+                }
+            }
+        }
+        console_log!("Rust (WASM): Layer stack finished.");
+        Ok(canvas)
+    }
+}
+
+impl LayerStack {
+    fn layer(&mut self, index: u32) -> Result<&mut Layer, JsValue> {
+        let count = self.layers.len();
+        self.layers
+            .get_mut(index as usize)
+            .ok_or_else(|| JsError::new(&format!("no layer {} (the stack has {})", index, count)).into())
+    }
+}
+
+// --- Drop Shadow ---
+// Draws the image over its own shadow: the alpha channel moved by
+// (`offset_x`, `offset_y`), blurred with a Gaussian of radius `blur` (0
+// for a hard shadow, up to 1000) and filled with `color` (0xRRGGBBAA, whose alpha sets
+// the shadow's strength, e.g. 0x00000080). The canvas doesn't grow, so pad
+// the image first if the shadow mustn't be cut off at the edges.
+#[wasm_bindgen]
+pub fn render_drop_shadow(
+    mut image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    offset_x: i32,
+    offset_y: i32,
+    blur: u32,
+    color: u32,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_range("blur", blur, 0, MAX_RADIUS)?;
+    check_memory("render_drop_shadow", width, height)?;
+    let (w, h) = (width as usize, height as usize);
+
+    console_log!("Rust (WASM): Drop shadow started...");
+    // Beyond the image's edges the shadow's source is transparent
+    let mut shadow = vec![0.0f32; w * h];
+    for y in 0..h {
+        let source_y = y as i64 - offset_y as i64;
+        if !(0..h as i64).contains(&source_y) {
+            continue;
+        }
+        for x in 0..w {
+            let source_x = x as i64 - offset_x as i64;
+            if (0..w as i64).contains(&source_x) {
+                shadow[y * w + x] = image_data[(source_y as usize * w + source_x as usize) * 4 + 3] as f32;
+            }
+        }
+    }
+    if blur > 0 {
+        shadow = blur_alpha(&shadow, w, h, blur as i32);
+    }
+    let [r, g, b, a] = color.to_be_bytes();
+    let strength = a as f32 / 255.0;
+    for (pixel, coverage) in pixels_mut(&mut image_data).iter_mut().zip(shadow) {
+        let original = *pixel;
+        *pixel = [r, g, b, (coverage * strength).round().min(255.0) as u8];
+        composite_pixel(pixel, &original, BlendMode::Normal, 1.0);
+    }
+    console_log!("Rust (WASM): Drop shadow finished.");
+    Ok(image_data)
+}
+
+// Separable Gaussian over an alpha plane, treating everything outside it
+// as transparent so the shadow fades out at the edges instead of smearing
+fn blur_alpha(plane: &[f32], width: usize, height: usize, radius: i32) -> Vec<f32> {
+    let kernel = gaussian_kernel(radius, 1);
+    let sum = |at: &dyn Fn(i32) -> Option<f32>| -> f32 {
+        kernel.iter().filter_map(|&(d, weight)| at(d).map(|value| value * weight)).sum()
+    };
+    let mut horizontal = vec![0.0f32; plane.len()];
+    for y in 0..height {
+        let row = &plane[y * width..][..width];
+        for x in 0..width {
+            horizontal[y * width + x] = sum(&|dx| row.get(usize::try_from(x as i32 + dx).ok()?).copied());
+        }
+    }
+    let mut result = vec![0.0f32; plane.len()];
+    for y in 0..height {
+        for x in 0..width {
+            result[y * width + x] = sum(&|dy| {
+                let ny = usize::try_from(y as i32 + dy).ok().filter(|&ny| ny < height)?;
+                Some(horizontal[ny * width + x])
+            });
+        }
+    }
+    result
+}
+
+// --- Chroma Key ---
+// Makes pixels near `key_rgba` (0xRRGGBBAA, alpha ignored; 0x00B140FF for
+// a typical green screen) transparent, ready to draw over a new
+// background. Colours are compared on YCbCr's chroma plane, so shadows and
+// creases in the screen key out like its lit parts. Distances are
+// measured so gray is 1 away from the key: within `tolerance` a pixel is
+// removed, beyond `tolerance + softness` it's kept, and in between it
+// fades, giving soft edges on hair and motion blur (0.3 and 0.15 suit an
+// evenly lit screen). `spill_suppression` (0..1) then strips that much of
+// the key's hue from the remaining pixels, removing the green cast the
+// screen throws on the subject without changing its brightness. The
+// matte multiplies the existing alpha.
+#[wasm_bindgen]
+pub fn apply_chroma_key(
+    mut image_data: Vec<u8>,
+    key_rgba: u32,
+    tolerance: f32,
+    softness: f32,
+    spill_suppression: f32,
+) -> Result<Vec<u8>, JsValue> {
+    check_rgba(&image_data)?;
+    check_finite("tolerance", tolerance)?;
+    check_finite("softness", softness)?;
+    check_finite("spill_suppression", spill_suppression)?;
+    let chroma = |[r, g, b]: [u8; 3]| {
+        let [_, cb, cr] = rgb_to_ycbcr([r, g, b].map(|c| c as f32 / 255.0));
+        [cb, cr]
+    };
+    let [key_r, key_g, key_b, _] = key_rgba.to_be_bytes();
+    let key = chroma([key_r, key_g, key_b]);
+    let key_length = key[0].hypot(key[1]);
+    if key_length < MIN_KEY_CHROMA {
+        return Err(JsError::new("the key colour is too close to gray to key on").into());
+    }
+    let direction = [key[0] / key_length, key[1] / key_length];
+    let (tolerance, softness) = (tolerance.max(0.0), softness.max(0.0));
+    let spill = spill_suppression.clamp(0.0, 1.0);
+
+    console_log!("Rust (WASM): Chroma key started...");
+    for pixel in pixels_mut(&mut image_data) {
+        let [y, cb, cr] = rgb_to_ycbcr([pixel[0], pixel[1], pixel[2]].map(|c| c as f32 / 255.0));
+        let distance = (cb - key[0]).hypot(cr - key[1]) / key_length;
+        let matte = if distance <= tolerance {
+            0.0
+        } else if distance >= tolerance + softness {
+            1.0
+        } else {
+            (distance - tolerance) / softness
+        };
+        if matte == 0.0 {
+            pixel[3] = 0;
+            continue;
+        }
+        // The part of the chroma pointing towards the key is the spill
+        let along = (cb * direction[0] + cr * direction[1]).max(0.0) * spill;
+        let rgb = ycbcr_to_rgb([y, cb - along * direction[0], cr - along * direction[1]]);
+        for (out, value) in pixel.iter_mut().zip(rgb) {
+            *out = (value * 255.0).round().clamp(0.0, 255.0) as u8;
+        }
+        pixel[3] = (pixel[3] as f32 * matte).round() as u8;
+    }
+    console_log!("Rust (WASM): Chroma key finished.");
+    Ok(image_data)
+}
+
+// Chroma (Cb / Cr length) a key colour needs; grays have none to key on
+const MIN_KEY_CHROMA: f32 = 0.02;
@@ -0,0 +1,261 @@
+use wasm_bindgen::prelude::*;
+
+use crate::cancel::{cancelled, CancellationToken};
+use crate::error::{check_image, check_positive, check_range};
+use crate::memory::check_memory;
+use crate::pixels::{par_rows_mut, pixels, Pixel};
+use crate::settings::{self, QualityTier};
+
+// ========================================================================
+// DENOISING
+// Noise-removal and smoothing filters that, unlike the blurs, keep edges
+// intact.
+// ========================================================================
+
+// With a token, rows go to the worker bands in chunks this size
+const CANCEL_CHECK_ROWS: usize = 32;
+
+// Calls `f(y, row)` for every row of `output`, in worker bands, checking
+// the token between chunks of rows; rows after a cancellation are skipped
+pub(crate) fn for_each_output_row<F>(output: &mut [u8], width: usize, height: usize, token: Option<&CancellationToken>, f: F)
+where
+    F: Fn(usize, &mut [Pixel]) + Send + Sync,
+{
+    let chunk_rows = if token.is_some() { CANCEL_CHECK_ROWS } else { height.max(1) };
+    let chunk_bytes = (width * 4 * chunk_rows).max(1);
+
+    for (chunk_index, chunk) in output.chunks_mut(chunk_bytes).enumerate() {
+        if cancelled(token) {
+            return;
+        }
+        let chunk_row = chunk_index * chunk_rows;
+        par_rows_mut(chunk, width, |row, out_row| f(chunk_row + row, out_row));
+    }
+}
+
+// --- Median Filter ---
+// Replaces each channel with the median of the (2 * radius + 1)^2 window
+// around it, which removes salt-and-pepper noise without smearing edges.
+// Each row keeps one 256-bin histogram per channel and slides it along
+// (Huang's algorithm): moving one pixel right only swaps a column in and
+// out, so the cost per pixel grows with the radius rather than its square.
+// Alpha is left as it is. If cancelled, rows not reached yet keep their
+// original pixels.
+//
+// Each row still starts from a full window, so `radius` stops at
+// `MAX_MEDIAN_RADIUS`, well below the blurs' limit.
+pub(crate) const MAX_MEDIAN_RADIUS: u32 = 100;
+
+#[wasm_bindgen]
+pub fn apply_median(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    radius: u32,
+    token: Option<CancellationToken>,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_range("radius", radius, 1, MAX_MEDIAN_RADIUS)?;
+    check_memory("median", width, height)?;
+    console_log!("Rust (WASM): Median filter started...");
+    let result = median_pixels(&image_data, width as usize, height as usize, radius as usize, token.as_ref());
+    console_log!("Rust (WASM): Median filter finished.");
+    Ok(result)
+}
+
+pub(crate) fn median_pixels(
+    image_data: &[u8],
+    width: usize,
+    height: usize,
+    radius: usize,
+    token: Option<&CancellationToken>,
+) -> Vec<u8> {
+    let mut result = image_data.to_vec();
+    for_each_output_row(&mut result, width, height, token, |y, out_row| {
+        median_row(image_data, out_row, width, height, y, radius);
+    });
+    result
+}
+
+fn median_row(src: &[u8], dst: &mut [Pixel], width: usize, height: usize, y: usize, radius: usize) {
+    let r = radius as isize;
+    let clamp_x = |x: isize| x.clamp(0, width as isize - 1) as usize;
+    let rows: Vec<usize> = (-r..=r)
+        .map(|dy| (y as isize + dy).clamp(0, height as isize - 1) as usize)
+        .collect();
+    let window = ((2 * radius + 1) * (2 * radius + 1)) as u32;
+    let mut medians = [RunningMedian::new(window), RunningMedian::new(window), RunningMedian::new(window)];
+
+    for dx in -r..=r {
+        let x = clamp_x(dx);
+        for &sy in &rows {
+            let idx = (sy * width + x) * 4;
+            for (c, median) in medians.iter_mut().enumerate() {
+                median.add(src[idx + c]);
+            }
+        }
+    }
+
+    for (x, out) in dst.iter_mut().enumerate() {
+        for (c, median) in medians.iter_mut().enumerate() {
+            out[c] = median.value();
+        }
+        if x + 1 == width {
+            break;
+        }
+        let leaving = clamp_x(x as isize - r);
+        let entering = clamp_x(x as isize + r + 1);
+        for &sy in &rows {
+            let out_idx = (sy * width + leaving) * 4;
+            let in_idx = (sy * width + entering) * 4;
+            for (c, median) in medians.iter_mut().enumerate() {
+                median.remove(src[out_idx + c]);
+                median.add(src[in_idx + c]);
+            }
+        }
+    }
+}
+
+// Histogram that tracks its median incrementally: `below` counts the
+// samples under the current median, which only ever has to move a few
+// bins after each update
+struct RunningMedian {
+    histogram: [u32; 256],
+    median: usize,
+    below: u32,
+    half: u32,
+}
+
+impl RunningMedian {
+    fn new(window: u32) -> RunningMedian {
+        RunningMedian {
+            histogram: [0; 256],
+            median: 0,
+            below: 0,
+            half: window / 2,
+        }
+    }
+
+    fn add(&mut self, value: u8) {
+        self.histogram[value as usize] += 1;
+        if (value as usize) < self.median {
+            self.below += 1;
+        }
+    }
+
+    fn remove(&mut self, value: u8) {
+        self.histogram[value as usize] -= 1;
+        if (value as usize) < self.median {
+            self.below -= 1;
+        }
+    }
+
+    // Smallest value with more than half the window at or below it
+    fn value(&mut self) -> u8 {
+        while self.below > self.half {
+            self.median -= 1;
+            self.below -= self.histogram[self.median];
+        }
+        while self.below + self.histogram[self.median] <= self.half {
+            self.below += self.histogram[self.median];
+            self.median += 1;
+        }
+        self.median as u8
+    }
+}
+
+// --- Bilateral Filter ---
+// Averages each pixel with its neighbours weighted both by distance
+// (`spatial_sigma`, in pixels) and by how close their colour is
+// (`range_sigma`, in 0-255 channel units), so flat areas are smoothed while
+// strong edges, whose other side is far away in colour, stay sharp. The
+// window reaches out to twice the spatial sigma; lower quality tiers
+// sample it sparsely, the same way the Gaussian blur skips taps.
+#[wasm_bindgen]
+pub fn apply_bilateral(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    spatial_sigma: f32,
+    range_sigma: f32,
+    token: Option<CancellationToken>,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_positive("spatial_sigma", spatial_sigma)?;
+    check_positive("range_sigma", range_sigma)?;
+    check_memory("bilateral", width, height)?;
+    console_log!("Rust (WASM): Bilateral filter started...");
+    let result = bilateral_pixels(
+        &image_data,
+        width as usize,
+        height as usize,
+        spatial_sigma,
+        range_sigma,
+        token.as_ref(),
+    );
+    console_log!("Rust (WASM): Bilateral filter finished.");
+    Ok(result)
+}
+
+pub(crate) fn bilateral_pixels(
+    image_data: &[u8],
+    width: usize,
+    height: usize,
+    spatial_sigma: f32,
+    range_sigma: f32,
+    token: Option<&CancellationToken>,
+) -> Vec<u8> {
+    // Taps further out than the image is wide only repeat its edge pixels
+    let radius = (2.0 * spatial_sigma).ceil().min(width.max(height) as f32) as i32;
+    let tap_step = match settings::effective_quality() {
+        QualityTier::High => 1,
+        QualityTier::Balanced => if radius > 4 { 2 } else { 1 },
+        QualityTier::Low => if radius > 2 { (radius / 4).max(2) } else { 1 },
+    };
+
+    // (dx, dy, spatial weight) for every sampled offset
+    let two_spatial_sq = 2.0 * spatial_sigma * spatial_sigma;
+    let taps: Vec<(i32, i32, f32)> = (-radius..=radius)
+        .filter(|dy| dy % tap_step == 0)
+        .flat_map(|dy| {
+            (-radius..=radius)
+                .filter(|dx| dx % tap_step == 0)
+                .map(move |dx| (dx, dy, (-((dx * dx + dy * dy) as f32) / two_spatial_sq).exp()))
+        })
+        .collect();
+
+    // The range weight factors into one term per channel difference
+    let two_range_sq = 2.0 * range_sigma * range_sigma;
+    let mut range = [0.0f32; 256];
+    for (diff, weight) in range.iter_mut().enumerate() {
+        *weight = (-((diff * diff) as f32) / two_range_sq).exp();
+    }
+
+    let source = pixels(image_data);
+    let mut result = image_data.to_vec();
+    for_each_output_row(&mut result, width, height, token, |y, out_row| {
+        for (x, out) in out_row.iter_mut().enumerate() {
+            let center = &source[y * width + x];
+            let mut sums = [0.0f32; 3];
+            let mut total = 0.0f32;
+            for &(dx, dy, spatial) in &taps {
+                let nx = (x as i32 + dx).clamp(0, width as i32 - 1) as usize;
+                let ny = (y as i32 + dy).clamp(0, height as i32 - 1) as usize;
+                let pixel = &source[ny * width + nx];
+                let weight = spatial
+                    * range[pixel[0].abs_diff(center[0]) as usize]
+                    * range[pixel[1].abs_diff(center[1]) as usize]
+                    * range[pixel[2].abs_diff(center[2]) as usize];
+                for c in 0..3 {
+                    sums[c] += pixel[c] as f32 * weight;
+                }
+                total += weight;
+            }
+            // The center tap always has weight 1, so `total` is never 0
+            for c in 0..3 {
+                out[c] = (sums[c] / total + 0.5) as u8;
+            }
+        }
+    });
+    result
+}
@@ -0,0 +1,386 @@
+use wasm_bindgen::prelude::*;
+
+use crate::binary::luma;
+use crate::error::{check_finite, check_image, check_range, Error};
+use crate::pixels::pixels;
+
+// ========================================================================
+// FEATURE DETECTION
+// Finds structure in the image (lines, outlines, matches) and returns it
+// as flat numeric arrays for JS to draw over the canvas or feed into its
+// own geometry, rather than as another image.
+// ========================================================================
+
+// --- Hough Lines ---
+// Angle steps over the half turn; 1 degree each
+const HOUGH_THETA_STEPS: usize = 180;
+// Peaks must beat every cell this many angle and distance steps away, so
+// one line doesn't come back again at a slightly different angle
+const HOUGH_PEAK_RADIUS: isize = 2;
+
+// Straight lines through the white pixels of `edge_data` (anything with
+// luminance above 127, such as the output of `apply_canny` or
+// `apply_threshold`). Each edge pixel votes for every line through it;
+// lines with at least `threshold` votes (roughly, pixels on them) that
+// beat their neighbouring angles and distances come back as
+// [rho, theta, rho, theta, ...], strongest first. A line is the points
+// where x * cos(theta) + y * sin(theta) = rho, with theta in radians in
+// [0, pi) and rho in pixels from the top-left corner (negative when the
+// line passes on the other side of it).
+#[wasm_bindgen]
+pub fn detect_lines_hough(edge_data: &[u8], width: u32, height: u32, threshold: u32) -> Result<Vec<f32>, JsValue> {
+    check_image(edge_data, width, height)?;
+    if threshold == 0 {
+        return Err(Error::NotPositive { name: "threshold" }.into());
+    }
+    console_log!("Rust (WASM): Hough line detection started...");
+    let w = width as usize;
+    // rho runs over -max_rho..=max_rho in one-pixel bins
+    let max_rho = (width as f32).hypot(height as f32).ceil() as usize;
+    let rho_bins = 2 * max_rho + 1;
+    let trig: Vec<(f32, f32)> = (0..HOUGH_THETA_STEPS)
+        .map(|t| (t as f32 * std::f32::consts::PI / HOUGH_THETA_STEPS as f32).sin_cos())
+        .collect();
+
+    // Accumulator indexed [theta][rho]
+    let mut votes = vec![0u32; HOUGH_THETA_STEPS * rho_bins];
+    for (i, pixel) in pixels(edge_data).iter().enumerate() {
+        if luma(pixel) <= 127 {
+            continue;
+        }
+        let (x, y) = ((i % w) as f32, (i / w) as f32);
+        for (t, &(sin, cos)) in trig.iter().enumerate() {
+            let rho = (x * cos + y * sin).round() as isize + max_rho as isize;
+            votes[t * rho_bins + rho as usize] += 1;
+        }
+    }
+
+    // Keep cells over the threshold that are maxima of their
+    // neighbourhood; ties go to the first cell so a plateau reports once.
+    // Theta wraps around: just past pi is just past 0 with rho negated.
+    let at = |t: isize, r: isize| {
+        let steps = HOUGH_THETA_STEPS as isize;
+        let (t, r) = if t < 0 || t >= steps { (t.rem_euclid(steps), rho_bins as isize - 1 - r) } else { (t, r) };
+        if r < 0 || r >= rho_bins as isize {
+            0
+        } else {
+            votes[t as usize * rho_bins + r as usize]
+        }
+    };
+    let mut lines: Vec<(u32, usize, usize)> = Vec::new();
+    for t in 0..HOUGH_THETA_STEPS {
+        for r in 0..rho_bins {
+            let count = votes[t * rho_bins + r];
+            if count < threshold {
+                continue;
+            }
+            let (ti, ri) = (t as isize, r as isize);
+            let window = -HOUGH_PEAK_RADIUS..=HOUGH_PEAK_RADIUS;
+            let is_peak = window.clone().all(|dt| {
+                window.clone().all(|dr| {
+                    let neighbour = at(ti + dt, ri + dr);
+                    // Earlier cells must be strictly weaker, later ones no stronger
+                    if (dt, dr) < (0, 0) { neighbour < count } else { neighbour <= count }
+                })
+            });
+            if is_peak {
+                lines.push((count, t, r));
+            }
+        }
+    }
+    lines.sort_by_key(|&(count, _, _)| std::cmp::Reverse(count));
+
+    let result = lines
+        .iter()
+        .flat_map(|&(_, t, r)| {
+            let theta = t as f32 * std::f32::consts::PI / HOUGH_THETA_STEPS as f32;
+            [r as f32 - max_rho as f32, theta]
+        })
+        .collect();
+    console_log!("Rust (WASM): Hough line detection finished ({} lines).", lines.len());
+    Ok(result)
+}
+
+// --- Contours ---
+// Neighbour offsets (dx, dy) clockwise on screen (y down), starting east
+const NEIGHBOURS: [(isize, isize); 8] = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+
+// Outline of every white region (luminance above 127) in `binary_data`,
+// and of every black hole inside one, traced with Suzuki and Abe's border
+// following (8-connected foreground). With `epsilon` above 0 each outline
+// is simplified with Douglas-Peucker, dropping points that lie within
+// `epsilon` pixels of the simplified polygon; 0 keeps every border pixel.
+//
+// Returned flat as Uint32Array records, one per contour in raster order
+// of their starting pixel:
+//   [point_count, kind, x0, y0, x1, y1, ...]
+// where kind is 0 for an outer border and 1 for a hole. Points run
+// anticlockwise on screen around outer borders and clockwise around holes.
+#[wasm_bindgen]
+pub fn find_contours(binary_data: &[u8], width: u32, height: u32, epsilon: f32) -> Result<Vec<u32>, JsValue> {
+    check_image(binary_data, width, height)?;
+    check_finite("epsilon", epsilon)?;
+    console_log!("Rust (WASM): Contour tracing started...");
+    let contours = trace_contours(binary_data, width as usize, height as usize);
+    let mut result = Vec::new();
+    for (hole, points) in &contours {
+        let points = if epsilon > 0.0 { simplify_closed(points, epsilon) } else { points.clone() };
+        result.push(points.len() as u32);
+        result.push(*hole as u32);
+        result.extend(points.iter().flat_map(|&(x, y)| [x as u32, y as u32]));
+    }
+    console_log!("Rust (WASM): Contour tracing finished ({} contours).", contours.len());
+    Ok(result)
+}
+
+// (is_hole, border pixels) per contour. Works on a copy with a one-pixel
+// background frame so tracing never leaves the grid; border pixels get
+// labelled with their contour's number as they're visited (negated where
+// the pixel to their east is background), as the algorithm requires.
+fn trace_contours(data: &[u8], width: usize, height: usize) -> Vec<(bool, Vec<(usize, usize)>)> {
+    let stride = width + 2;
+    let mut f = vec![0i32; stride * (height + 2)];
+    for (i, pixel) in pixels(data).iter().enumerate() {
+        if luma(pixel) > 127 {
+            f[(i / width + 1) * stride + i % width + 1] = 1;
+        }
+    }
+    let at = |x: usize, y: usize| y * stride + x;
+    let step = |(x, y): (usize, usize), d: usize| {
+        let (dx, dy) = NEIGHBOURS[d % 8];
+        ((x as isize + dx) as usize, (y as isize + dy) as usize)
+    };
+    let direction = |from: (usize, usize), to: (usize, usize)| {
+        let offset = (to.0 as isize - from.0 as isize, to.1 as isize - from.1 as isize);
+        NEIGHBOURS.iter().position(|&n| n == offset).unwrap_or(0)
+    };
+
+    let mut contours = Vec::new();
+    let mut label = 1;
+    for y in 1..=height {
+        for x in 1..=width {
+            let value = f[at(x, y)];
+            let (hole, back) = if value == 1 && f[at(x - 1, y)] == 0 {
+                (false, (x - 1, y))
+            } else if value >= 1 && f[at(x + 1, y)] == 0 {
+                (true, (x + 1, y))
+            } else {
+                continue;
+            };
+            label += 1;
+            let start = (x, y);
+            let mut points = Vec::new();
+
+            // First foreground neighbour clockwise from the background side
+            let first_dir = direction(start, back);
+            let Some(first) = (0..8).map(|k| step(start, first_dir + k)).find(|&p| f[at(p.0, p.1)] != 0) else {
+                // Isolated pixel
+                f[at(x, y)] = -label;
+                contours.push((hole, vec![(x - 1, y - 1)]));
+                continue;
+            };
+
+            let (mut previous, mut current) = (first, start);
+            loop {
+                points.push((current.0 - 1, current.1 - 1));
+                // Next foreground neighbour anticlockwise, starting just
+                // after the one we came from
+                let from = direction(current, previous);
+                let mut east_is_background = false;
+                let mut next = previous;
+                for k in 1..=8 {
+                    let d = (from + 8 - k) % 8;
+                    let p = step(current, d);
+                    if f[at(p.0, p.1)] != 0 {
+                        next = p;
+                        break;
+                    }
+                    if d == 0 {
+                        east_is_background = true;
+                    }
+                }
+                let cell = &mut f[at(current.0, current.1)];
+                if east_is_background {
+                    *cell = -label;
+                } else if *cell == 1 {
+                    *cell = label;
+                }
+                if next == start && current == first {
+                    break;
+                }
+                previous = current;
+                current = next;
+            }
+            contours.push((hole, points));
+        }
+    }
+    contours
+}
+
+// Douglas-Peucker on a closed polygon: split at the point farthest from
+// the first one and simplify both halves as open polylines
+fn simplify_closed(points: &[(usize, usize)], epsilon: f32) -> Vec<(usize, usize)> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let distance_sq = |a: (usize, usize), b: (usize, usize)| {
+        let (dx, dy) = (a.0 as f32 - b.0 as f32, a.1 as f32 - b.1 as f32);
+        dx * dx + dy * dy
+    };
+    let far = (1..points.len())
+        .max_by(|&a, &b| distance_sq(points[0], points[a]).total_cmp(&distance_sq(points[0], points[b])))
+        .unwrap_or(0);
+    let mut first_half = points[..=far].to_vec();
+    let mut second_half = points[far..].to_vec();
+    second_half.push(points[0]);
+    first_half = simplify_open(&first_half, epsilon);
+    second_half = simplify_open(&second_half, epsilon);
+    // Both halves end where the other starts
+    first_half.pop();
+    second_half.pop();
+    first_half.extend(second_half);
+    first_half
+}
+
+fn simplify_open(points: &[(usize, usize)], epsilon: f32) -> Vec<(usize, usize)> {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let (ax, ay) = (first.0 as f32, first.1 as f32);
+    let (dx, dy) = (last.0 as f32 - ax, last.1 as f32 - ay);
+    let length = dx.hypot(dy);
+    // Distance from the chord, or from `first` when the ends coincide
+    let distance = |p: (usize, usize)| {
+        let (px, py) = (p.0 as f32 - ax, p.1 as f32 - ay);
+        if length > 0.0 { (px * dy - py * dx).abs() / length } else { px.hypot(py) }
+    };
+    let farthest = (1..points.len().saturating_sub(1))
+        .max_by(|&a, &b| distance(points[a]).total_cmp(&distance(points[b])));
+    match farthest {
+        Some(i) if distance(points[i]) > epsilon => {
+            let mut left = simplify_open(&points[..=i], epsilon);
+            left.pop();
+            left.extend(simplify_open(&points[i..], epsilon));
+            left
+        }
+        _ => vec![first, last],
+    }
+}
+
+// --- Template Matching ---
+// Result of `match_template`
+#[wasm_bindgen]
+pub struct TemplateMatch {
+    x: u32,
+    y: u32,
+    score: f32,
+    score_map: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl TemplateMatch {
+    // Top-left corner of the best match in the image
+    #[wasm_bindgen(getter)]
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+
+    // Correlation at the best match, -1..1; 1 is a perfect match up to
+    // brightness and contrast
+    #[wasm_bindgen(getter)]
+    pub fn score(&self) -> f32 {
+        self.score
+    }
+
+    // Score for every placement of the template, row by row over
+    // (iw - tw + 1) x (ih - th + 1) positions; empty unless asked for
+    #[wasm_bindgen(getter)]
+    pub fn score_map(&self) -> Vec<f32> {
+        self.score_map.clone()
+    }
+}
+
+// Finds where `template` (tw x th RGBA) best fits inside `image` (iw x ih)
+// by zero-mean normalized cross-correlation of their luminance, which
+// ignores overall brightness and contrast changes, so a logo still matches
+// on a darker photo. Alpha is not used. Placements over flat image areas,
+// and every placement of a flat template, score 0.
+#[wasm_bindgen]
+pub fn match_template(
+    image: &[u8],
+    iw: u32,
+    ih: u32,
+    template: &[u8],
+    tw: u32,
+    th: u32,
+    with_score_map: bool,
+) -> Result<TemplateMatch, JsValue> {
+    check_image(image, iw, ih)?;
+    check_image(template, tw, th)?;
+    check_range("template width", tw, 1, iw)?;
+    check_range("template height", th, 1, ih)?;
+    console_log!("Rust (WASM): Template matching started...");
+    let (iw, ih, tw, th) = (iw as usize, ih as usize, tw as usize, th as usize);
+    let gray = |data: &[u8]| -> Vec<f64> { pixels(data).iter().map(|p| luma(p) as f64).collect() };
+    let image = gray(image);
+    let mut template = gray(template);
+    let n = (tw * th) as f64;
+    let template_mean = template.iter().sum::<f64>() / n;
+    for value in template.iter_mut() {
+        *value -= template_mean;
+    }
+    let template_energy: f64 = template.iter().map(|v| v * v).sum();
+
+    // Summed-area tables of the image and its square for the window sums
+    let stride = iw + 1;
+    let mut sums = vec![0.0f64; stride * (ih + 1)];
+    let mut squares = vec![0.0f64; stride * (ih + 1)];
+    for y in 0..ih {
+        let (mut row_sum, mut row_square) = (0.0, 0.0);
+        for x in 0..iw {
+            let v = image[y * iw + x];
+            row_sum += v;
+            row_square += v * v;
+            sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row_sum;
+            squares[(y + 1) * stride + x + 1] = squares[y * stride + x + 1] + row_square;
+        }
+    }
+    let window = |table: &[f64], x: usize, y: usize| {
+        let (top, bottom) = (y * stride, (y + th) * stride);
+        table[bottom + x + tw] + table[top + x] - table[top + x + tw] - table[bottom + x]
+    };
+
+    let (positions_x, positions_y) = (iw - tw + 1, ih - th + 1);
+    let mut score_map = Vec::with_capacity(if with_score_map { positions_x * positions_y } else { 0 });
+    let mut best = TemplateMatch { x: 0, y: 0, score: f32::NEG_INFINITY, score_map: Vec::new() };
+    for y in 0..positions_y {
+        for x in 0..positions_x {
+            let sum = window(&sums, x, y);
+            let variance = window(&squares, x, y) - sum * sum / n;
+            let denominator = (variance * template_energy).sqrt();
+            let score = if denominator > 1e-6 {
+                let mut numerator = 0.0;
+                for ty in 0..th {
+                    let row = &image[(y + ty) * iw + x..][..tw];
+                    numerator += row.iter().zip(&template[ty * tw..][..tw]).map(|(a, b)| a * b).sum::<f64>();
+                }
+                (numerator / denominator) as f32
+            } else {
+                0.0
+            };
+            if with_score_map {
+                score_map.push(score);
+            }
+            if score > best.score {
+                best = TemplateMatch { x: x as u32, y: y as u32, score, score_map: Vec::new() };
+            }
+        }
+    }
+    best.score = best.score.max(-1.0);
+    best.score_map = score_map;
+    console_log!("Rust (WASM): Template matching finished.");
+    Ok(best)
+}
@@ -0,0 +1,171 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_image, check_one_of, check_range, check_rgba, Error};
+use crate::pixels::{pixels_mut, rows_mut};
+
+// ========================================================================
+// DITHERING
+// Quantizes each colour channel to a few levels (1 bit is pure black and
+// white per channel), or the whole colour to a palette, while spreading
+// the rounding error around, so gradients turn into patterns of the
+// available values instead of flat bands. Alpha is left alone.
+// ========================================================================
+
+// Nearest of the evenly spaced levels 0, 255 / steps, ..., 255
+fn quantize(value: f32, steps: f32) -> f32 {
+    ((value.clamp(0.0, 255.0) * steps / 255.0).round() * 255.0 / steps).round()
+}
+
+// --- Floyd-Steinberg ---
+// Error diffusion: each pixel's rounding error is pushed onto the
+// neighbours not yet visited (7/16 ahead, 3/16, 5/16 and 1/16 on the next
+// row). Rows alternate direction (serpentine scan), which avoids the
+// diagonal "worm" artefacts of always scanning left to right.
+#[wasm_bindgen]
+pub fn apply_dither_fs(mut image_data: Vec<u8>, width: u32, height: u32, bits_per_channel: u32) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_range("bits_per_channel", bits_per_channel, 1, 8)?;
+    console_log!("Rust (WASM): Floyd-Steinberg dither started...");
+    let steps = ((1u32 << bits_per_channel) - 1) as f32;
+    diffuse_errors(&mut image_data, width as usize, |values| values.map(|v| quantize(v, steps) as u8));
+    console_log!("Rust (WASM): Floyd-Steinberg dither finished.");
+    Ok(image_data)
+}
+
+// Floyd-Steinberg over the whole image, with `nearest` picking the output
+// colour for each pixel's value plus the error carried into it
+fn diffuse_errors(image_data: &mut [u8], w: usize, nearest: impl Fn([f32; 3]) -> [u8; 3]) {
+    // Error carried into the current and the next row, one slot per
+    // channel with a pixel of padding on either side
+    let mut current = vec![[0.0f32; 3]; w + 2];
+    let mut next = vec![[0.0f32; 3]; w + 2];
+    for (y, row) in rows_mut(image_data, w) {
+        let forward = y % 2 == 0;
+        for i in 0..w {
+            let x = if forward { i } else { w - 1 - i };
+            // Padded indices of this pixel and its neighbours in the scan
+            // direction
+            let (here, ahead, behind) = if forward { (x + 1, x + 2, x) } else { (x + 1, x, x + 2) };
+            let pixel = &mut row[x];
+            let values = [0, 1, 2].map(|c| pixel[c] as f32 + current[here][c]);
+            let chosen = nearest(values);
+            pixel[..3].copy_from_slice(&chosen);
+            for c in 0..3 {
+                let error = values[c] - chosen[c] as f32;
+                current[ahead][c] += error * 7.0 / 16.0;
+                next[behind][c] += error * 3.0 / 16.0;
+                next[here][c] += error * 5.0 / 16.0;
+                next[ahead][c] += error * 1.0 / 16.0;
+            }
+        }
+        std::mem::swap(&mut current, &mut next);
+        next.fill([0.0; 3]);
+    }
+}
+
+// --- Ordered (Bayer) ---
+// Thresholds each channel against a tiled Bayer matrix instead of diffusing
+// error, giving the regular cross-hatch of old consoles and print
+// halftones. Every pixel depends only on its own value and position, so
+// the output is the same however the image is split up. 1 bit per
+// channel; `matrix_size` is 2, 4 or 8 (larger gives finer gradients).
+#[wasm_bindgen]
+pub fn apply_dither_bayer(mut image_data: Vec<u8>, width: u32, height: u32, matrix_size: u32) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_one_of("matrix_size", matrix_size, &[2, 4, 8])?;
+    console_log!("Rust (WASM): Bayer dither started...");
+    let n = matrix_size as usize;
+    // Thresholds spread evenly through 0..255, centred in their steps
+    let cells = (n * n) as f32;
+    let thresholds: Vec<f32> = bayer_matrix(n).iter().map(|&m| (m as f32 + 0.5) / cells * 255.0).collect();
+    for (y, row) in rows_mut(&mut image_data, width as usize) {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let threshold = thresholds[(y % n) * n + x % n];
+            for channel in &mut pixel[..3] {
+                *channel = if *channel as f32 > threshold { 255 } else { 0 };
+            }
+        }
+    }
+    console_log!("Rust (WASM): Bayer dither finished.");
+    Ok(image_data)
+}
+
+// Index matrix of size `n` (a power of two), row-major, built by doubling:
+// M(2k) = [4M, 4M + 2; 4M + 3, 4M + 1]
+fn bayer_matrix(n: usize) -> Vec<u32> {
+    let mut matrix = vec![0u32];
+    let mut size = 1;
+    while size < n {
+        let mut next = vec![0u32; 4 * size * size];
+        for y in 0..size {
+            for x in 0..size {
+                let m = 4 * matrix[y * size + x];
+                next[y * 2 * size + x] = m;
+                next[y * 2 * size + x + size] = m + 2;
+                next[(y + size) * 2 * size + x] = m + 3;
+                next[(y + size) * 2 * size + x + size] = m + 1;
+            }
+        }
+        matrix = next;
+        size *= 2;
+    }
+    matrix
+}
+
+// --- Palette Recolouring ---
+// Maps every pixel to the nearest colour of `palette` (0xRRGGBB values, up
+// to 256 of them: brand colours, or retro palettes such as the GameBoy's
+// four greens), by distance in RGB. With `dither` the leftover error is
+// diffused Floyd-Steinberg style, which keeps gradients and shading
+// readable even with very few colours; without it areas snap to flat
+// colour, the crisper pixel-art look.
+#[wasm_bindgen]
+pub fn recolor_to_palette(
+    mut image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    palette: Vec<u32>,
+    dither: bool,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    let colors = palette_colors(&palette)?;
+    console_log!("Rust (WASM): Palette recolor started...");
+    let nearest = |values: [f32; 3]| nearest_color(&colors, values);
+    if dither {
+        diffuse_errors(&mut image_data, width as usize, nearest);
+    } else {
+        for pixel in pixels_mut(&mut image_data) {
+            let color = nearest([pixel[0] as f32, pixel[1] as f32, pixel[2] as f32]);
+            pixel[..3].copy_from_slice(&color);
+        }
+    }
+    console_log!("Rust (WASM): Palette recolor finished.");
+    Ok(image_data)
+}
+
+// `recolor_to_palette` without dithering, which needs no image size: the
+// usual second step after `extract_palette_kmeans`
+#[wasm_bindgen]
+pub fn quantize_to_palette(mut image_data: Vec<u8>, palette: Vec<u32>) -> Result<Vec<u8>, JsValue> {
+    check_rgba(&image_data)?;
+    let colors = palette_colors(&palette)?;
+    console_log!("Rust (WASM): Palette quantize started...");
+    for pixel in pixels_mut(&mut image_data) {
+        let color = nearest_color(&colors, [pixel[0] as f32, pixel[1] as f32, pixel[2] as f32]);
+        pixel[..3].copy_from_slice(&color);
+    }
+    console_log!("Rust (WASM): Palette quantize finished.");
+    Ok(image_data)
+}
+
+// Unpacks 1 to 256 0xRRGGBB values
+fn palette_colors(palette: &[u32]) -> Result<Vec<[u8; 3]>, Error> {
+    check_range("palette length", palette.len().min(u32::MAX as usize) as u32, 1, 256)?;
+    Ok(palette.iter().map(|&rgb| [(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8]).collect())
+}
+
+// Closest of `colors` to `values` by squared RGB distance
+fn nearest_color(colors: &[[u8; 3]], values: [f32; 3]) -> [u8; 3] {
+    let distance = |color: &[u8; 3]| -> f32 { (0..3).map(|c| (values[c] - color[c] as f32).powi(2)).sum() };
+    *colors.iter().min_by(|a, b| distance(a).total_cmp(&distance(b))).unwrap_or(&[0; 3])
+}
@@ -0,0 +1,237 @@
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use crate::settings;
+
+// ========================================================================
+// ERRORS
+// Exports that can be handed bad input return `Result<_, JsValue>`, which
+// wasm-bindgen turns into a thrown JS `Error` carrying the message below,
+// instead of panicking or quietly producing a corrupt image.
+// ========================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Error {
+    ZeroDimension { width: u32, height: u32 },
+    TooLarge { width: u32, height: u32 },
+    LengthMismatch { expected: usize, actual: usize },
+    NotRgba { len: usize },
+    NullBuffer,
+    ZeroRadius,
+    NotPositive { name: &'static str },
+    NotFinite { name: &'static str },
+    OutOfRange { name: &'static str, min: u32, max: u32 },
+    OutsideImage { x: u32, y: u32, width: u32, height: u32, image_width: u32, image_height: u32 },
+    NotOneOf { name: &'static str, allowed: &'static [u32] },
+    FrameLength { width: u32, height: u32, expected: usize, actual: usize },
+    FloatLength { expected: usize, actual: usize },
+    FrameMismatch { first: usize, second: usize },
+    PartialFrames { len: usize, frame_len: usize },
+    OverMemoryLimit { bytes: usize, limit: usize },
+    OverMemoryCeiling { filter: &'static str, width: u32, height: u32, bytes: u64, ceiling: u64, tile: u32 },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Error::ZeroDimension { width, height } => {
+                write!(f, "image dimensions must be non-zero, got {}x{}", width, height)
+            }
+            Error::TooLarge { width, height } => {
+                write!(f, "a {}x{} RGBA image doesn't fit in memory", width, height)
+            }
+            Error::LengthMismatch { expected, actual } => write!(
+                f,
+                "image data has {} bytes but width * height * 4 is {}",
+                actual, expected
+            ),
+            Error::NotRgba { len } => {
+                write!(f, "image data length {} isn't a multiple of 4 (RGBA)", len)
+            }
+            Error::NullBuffer => write!(f, "buffer pointer is null; allocate it with alloc_buffer"),
+            Error::ZeroRadius => write!(f, "radius must be at least 1"),
+            Error::NotPositive { name } => write!(f, "{} must be a positive number", name),
+            Error::NotFinite { name } => write!(f, "{} must be a finite number", name),
+            Error::OutOfRange { name, min, max } => {
+                write!(f, "{} must be between {} and {}", name, min, max)
+            }
+            Error::OutsideImage { x, y, width, height, image_width, image_height } => write!(
+                f,
+                "the {}x{} region at ({}, {}) reaches outside the {}x{} image",
+                width, height, x, y, image_width, image_height
+            ),
+            Error::NotOneOf { name, allowed } => {
+                let allowed: Vec<String> = allowed.iter().map(|value| value.to_string()).collect();
+                write!(f, "{} must be one of {}", name, allowed.join(", "))
+            }
+            Error::FrameLength { width, height, expected, actual } => write!(
+                f,
+                "YUV frame has {} bytes but a {}x{} 4:2:0 frame is {}",
+                actual, width, height, expected
+            ),
+            Error::FloatLength { expected, actual } => write!(
+                f,
+                "float image has {} values but width * height * 4 is {}",
+                actual, expected
+            ),
+            Error::FrameMismatch { first, second } => {
+                write!(f, "frames must be the same size, got {} and {} bytes", first, second)
+            }
+            Error::PartialFrames { len, frame_len } => write!(
+                f,
+                "frame sequence has {} bytes, which isn't a whole number of {} byte frames",
+                len, frame_len
+            ),
+            Error::OverMemoryLimit { bytes, limit } => write!(
+                f,
+                "a {} byte image is over the {} byte limit set in init()",
+                bytes, limit
+            ),
+            Error::OverMemoryCeiling { filter, width, height, bytes, ceiling, tile } => write!(
+                f,
+                "{} on a {}x{} image needs about {} bytes, over the {} byte memory ceiling; \
+                 process it in tiles of at most {}x{} pixels",
+                filter, width, height, bytes, ceiling, tile, tile
+            ),
+        }
+    }
+}
+
+// Errors hosts are expected to recover from also carry their details as
+// properties of the thrown object (`err.code`, `err.suggested_tile`, ...)
+impl From<Error> for JsValue {
+    fn from(err: Error) -> JsValue {
+        let value: JsValue = JsError::new(&err.to_string()).into();
+        if let Error::OverMemoryCeiling { filter, bytes, ceiling, tile, .. } = err {
+            let details: [(&str, JsValue); 5] = [
+                ("code", "memory_ceiling".into()),
+                ("filter", filter.into()),
+                ("estimated_bytes", (bytes as f64).into()),
+                ("ceiling_bytes", (ceiling as f64).into()),
+                ("suggested_tile", tile.into()),
+            ];
+            for (key, detail) in details {
+                let _ = js_sys::Reflect::set(&value, &key.into(), &detail);
+            }
+        }
+        value
+    }
+}
+
+// Byte length of a `width x height` RGBA image, if it's addressable and
+// within the configured memory limit
+pub(crate) fn image_len(width: u32, height: u32) -> Result<usize, Error> {
+    if width == 0 || height == 0 {
+        return Err(Error::ZeroDimension { width, height });
+    }
+    let bytes = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|pixels| pixels.checked_mul(4))
+        .ok_or(Error::TooLarge { width, height })?;
+    match settings::max_image_bytes() {
+        Some(limit) if bytes > limit => Err(Error::OverMemoryLimit { bytes, limit }),
+        _ => Ok(bytes),
+    }
+}
+
+// `data` must be exactly one `width x height` RGBA image
+pub(crate) fn check_image(data: &[u8], width: u32, height: u32) -> Result<(), Error> {
+    let expected = image_len(width, height)?;
+    if data.len() != expected {
+        return Err(Error::LengthMismatch {
+            expected,
+            actual: data.len(),
+        });
+    }
+    Ok(())
+}
+
+// For filters that don't need dimensions: whole RGBA pixels only
+pub(crate) fn check_rgba<T>(data: &[T]) -> Result<(), Error> {
+    if !data.len().is_multiple_of(4) {
+        return Err(Error::NotRgba { len: data.len() });
+    }
+    Ok(())
+}
+
+// Largest radius the neighbourhood filters accept: far past any visible
+// difference, and small enough that kernels stay a few thousand taps and
+// the stack blur's integer sums can't overflow
+pub(crate) const MAX_RADIUS: u32 = 1000;
+
+pub(crate) fn check_radius(radius: u32) -> Result<(), Error> {
+    if radius == 0 {
+        return Err(Error::ZeroRadius);
+    }
+    check_range("radius", radius, 1, MAX_RADIUS)
+}
+
+// For float parameters such as sigmas; NaN and infinity are rejected too
+pub(crate) fn check_positive(name: &'static str, value: f32) -> Result<(), Error> {
+    if !value.is_finite() || value <= 0.0 {
+        return Err(Error::NotPositive { name });
+    }
+    Ok(())
+}
+
+// For float parameters that can take any value except NaN and infinity
+pub(crate) fn check_finite(name: &'static str, value: f32) -> Result<(), Error> {
+    if !value.is_finite() {
+        return Err(Error::NotFinite { name });
+    }
+    Ok(())
+}
+
+// For integer parameters with a fixed valid range (inclusive)
+pub(crate) fn check_range(name: &'static str, value: u32, min: u32, max: u32) -> Result<(), Error> {
+    if value < min || value > max {
+        return Err(Error::OutOfRange { name, min, max });
+    }
+    Ok(())
+}
+
+// Float parameters bounded by whole numbers; NaN and infinity are out of
+// range too
+pub(crate) fn check_float_range(name: &'static str, value: f32, min: u32, max: u32) -> Result<(), Error> {
+    if !(value >= min as f32 && value <= max as f32) {
+        return Err(Error::OutOfRange { name, min, max });
+    }
+    Ok(())
+}
+
+pub(crate) fn check_one_of(name: &'static str, value: u32, allowed: &'static [u32]) -> Result<(), Error> {
+    if !allowed.contains(&value) {
+        return Err(Error::NotOneOf { name, allowed });
+    }
+    Ok(())
+}
+
+// Planar or semi-planar 4:2:0 frame (I420, NV12): a full-size Y plane plus
+// two chroma planes at half resolution, rounded up
+pub(crate) fn check_yuv420(data: &[u8], width: u32, height: u32) -> Result<(), Error> {
+    let luma = image_len(width, height)? / 4;
+    let chroma = (width as usize).div_ceil(2) * (height as usize).div_ceil(2);
+    let expected = luma + 2 * chroma;
+    if data.len() != expected {
+        return Err(Error::FrameLength {
+            width,
+            height,
+            expected,
+            actual: data.len(),
+        });
+    }
+    Ok(())
+}
+
+// `data` must be one `width x height` image of f32 RGBA values
+pub(crate) fn check_float_image(data: &[f32], width: u32, height: u32) -> Result<(), Error> {
+    let expected = image_len(width, height)?;
+    if data.len() != expected {
+        return Err(Error::FloatLength {
+            expected,
+            actual: data.len(),
+        });
+    }
+    Ok(())
+}
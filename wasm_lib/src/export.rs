@@ -0,0 +1,293 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::check_image;
+
+// ========================================================================
+// EXPORT (`export` feature)
+// Turns results into files users can download or share, without a canvas
+// round trip on the JS side: zip archives, PNG and BMP images.
+// ========================================================================
+
+// --- Zip Archives ---
+// Bundles several outputs into one download. `entries` is an array of
+// `{ name, data }` objects, where `data` is a Uint8Array (a PNG, a preset
+// blob) or a string (a CSV report, JSON), stored as UTF-8:
+//
+//   const zip = package_zip([
+//     { name: "blurred.png", data: to_png_bytes(blurred, width, height) },
+//     { name: "report.csv", data: csv },
+//   ]);
+//   const url = URL.createObjectURL(new Blob([zip], { type: "application/zip" }));
+//
+// Names may contain `/` for folders and must be unique. Entries are
+// deflated unless that doesn't make them smaller (already-compressed PNGs
+// usually end up stored as they are). Timestamps are fixed, so the same
+// entries always give the same bytes.
+#[wasm_bindgen]
+pub fn package_zip(entries: JsValue) -> Result<Vec<u8>, JsValue> {
+    let entries = zip_entries(&entries)?;
+    console_log!("Rust (WASM): Packaging {} files...", entries.len());
+    let archive = zip_archive(&entries)?;
+    console_log!("Rust (WASM): Packaging finished ({} bytes).", archive.len());
+    Ok(archive)
+}
+
+fn invalid(message: String) -> JsValue {
+    JsError::new(&format!("invalid zip entries: {}", message)).into()
+}
+
+fn zip_entries(entries: &JsValue) -> Result<Vec<(String, Vec<u8>)>, JsValue> {
+    if !js_sys::Array::is_array(entries) {
+        return Err(invalid("expected an array of { name, data } objects".into()));
+    }
+    let mut files: Vec<(String, Vec<u8>)> = Vec::new();
+    for (index, entry) in js_sys::Array::from(entries).iter().enumerate() {
+        let field = |key: &str| js_sys::Reflect::get(&entry, &key.into()).unwrap_or(JsValue::UNDEFINED);
+        let name = field("name")
+            .as_string()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| invalid(format!("entry {} needs a non-empty `name`", index)))?;
+        let data = field("data");
+        let bytes = if let Some(text) = data.as_string() {
+            text.into_bytes()
+        } else if data.is_instance_of::<js_sys::Uint8Array>() || data.is_instance_of::<js_sys::ArrayBuffer>() {
+            js_sys::Uint8Array::new(&data).to_vec()
+        } else {
+            return Err(invalid(format!("`{}` needs `data` as a Uint8Array or string", name)));
+        };
+        if files.iter().any(|(existing, _)| *existing == name) {
+            return Err(invalid(format!("`{}` appears more than once", name)));
+        }
+        files.push((name, bytes));
+    }
+    Ok(files)
+}
+
+// 1980-01-01 00:00, the earliest DOS timestamp
+const DOS_TIME: u16 = 0;
+const DOS_DATE: u16 = (1 << 5) | 1;
+// Language encoding flag: names are UTF-8
+const FLAG_UTF8: u16 = 1 << 11;
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+const VERSION: u16 = 20;
+
+struct CentralRecord {
+    method: u16,
+    crc: u32,
+    compressed: u32,
+    size: u32,
+    offset: u32,
+}
+
+// Plain zip (no zip64), so the archive and every entry must stay under
+// 4 GiB and there can be at most 65535 entries
+fn zip_archive(entries: &[(String, Vec<u8>)]) -> Result<Vec<u8>, JsValue> {
+    let too_big = || invalid("the archive would be over the 4 GiB zip limit".into());
+    let count = u16::try_from(entries.len()).map_err(|_| invalid("more than 65535 entries".into()))?;
+    let mut out = Vec::new();
+    let mut records = Vec::with_capacity(entries.len());
+    for (name, data) in entries {
+        let deflated = miniz_oxide::deflate::compress_to_vec(data, 6);
+        let (method, body) = if deflated.len() < data.len() {
+            (METHOD_DEFLATED, &deflated[..])
+        } else {
+            (METHOD_STORED, &data[..])
+        };
+        let record = CentralRecord {
+            method,
+            crc: crc32fast::hash(data),
+            compressed: u32::try_from(body.len()).map_err(|_| too_big())?,
+            size: u32::try_from(data.len()).map_err(|_| too_big())?,
+            offset: u32::try_from(out.len()).map_err(|_| too_big())?,
+        };
+        put_u32(&mut out, 0x0403_4b50);
+        put_common(&mut out, &record, name);
+        put_u16(&mut out, 0); // extra field length
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(body);
+        records.push(record);
+    }
+
+    let directory_offset = u32::try_from(out.len()).map_err(|_| too_big())?;
+    for ((name, _), record) in entries.iter().zip(&records) {
+        put_u32(&mut out, 0x0201_4b50);
+        put_u16(&mut out, VERSION); // made by
+        put_common(&mut out, record, name);
+        put_u16(&mut out, 0); // extra field length
+        put_u16(&mut out, 0); // comment length
+        put_u16(&mut out, 0); // disk number
+        put_u16(&mut out, 0); // internal attributes
+        put_u32(&mut out, 0); // external attributes
+        put_u32(&mut out, record.offset);
+        out.extend_from_slice(name.as_bytes());
+    }
+    let directory_size = u32::try_from(out.len()).map_err(|_| too_big())? - directory_offset;
+
+    put_u32(&mut out, 0x0605_4b50);
+    put_u16(&mut out, 0); // this disk
+    put_u16(&mut out, 0); // disk with the directory
+    put_u16(&mut out, count);
+    put_u16(&mut out, count);
+    put_u32(&mut out, directory_size);
+    put_u32(&mut out, directory_offset);
+    put_u16(&mut out, 0); // comment length
+    Ok(out)
+}
+
+// Fields shared by the local header and the central directory record, from
+// "version needed" up to the name length
+fn put_common(out: &mut Vec<u8>, record: &CentralRecord, name: &str) {
+    put_u16(out, VERSION);
+    put_u16(out, FLAG_UTF8);
+    put_u16(out, record.method);
+    put_u16(out, DOS_TIME);
+    put_u16(out, DOS_DATE);
+    put_u32(out, record.crc);
+    put_u32(out, record.compressed);
+    put_u32(out, record.size);
+    put_u16(out, name.len() as u16);
+}
+
+fn put_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+// --- Image Files ---
+// Complete files for an RGBA buffer, ready for a download link or (PNG
+// only; browsers don't accept BMP there) the clipboard:
+//
+//   const png = to_png_bytes(pixels, width, height);
+//   await navigator.clipboard.write([
+//     new ClipboardItem({ "image/png": new Blob([png], { type: "image/png" }) }),
+//   ]);
+
+// 8-bit RGBA PNG. Each row gets whichever PNG filter leaves the smallest
+// residuals (the usual minimum-sum heuristic) before deflate.
+#[wasm_bindgen]
+pub fn to_png_bytes(image_data: &[u8], width: u32, height: u32) -> Result<Vec<u8>, JsValue> {
+    check_image(image_data, width, height)?;
+    let row_bytes = width as usize * 4;
+    let mut filtered = Vec::with_capacity((row_bytes + 1) * height as usize);
+    let zero_row = vec![0u8; row_bytes];
+    let mut previous: &[u8] = &zero_row;
+    let mut candidate = vec![0u8; row_bytes];
+    let mut best = vec![0u8; row_bytes];
+    for row in image_data.chunks_exact(row_bytes) {
+        let mut best_filter = 0;
+        let mut best_cost = u64::MAX;
+        for filter in 0..5u8 {
+            png_filter_row(filter, row, previous, &mut candidate);
+            // Residuals as signed bytes, so small negative ones count as small
+            let cost: u64 = candidate.iter().map(|&b| (b as i8).unsigned_abs() as u64).sum();
+            if cost < best_cost {
+                best_cost = cost;
+                best_filter = filter;
+                std::mem::swap(&mut best, &mut candidate);
+            }
+        }
+        filtered.push(best_filter);
+        filtered.extend_from_slice(&best);
+        previous = row;
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8 bits per channel, colour type 6 (RGBA), deflate, adaptive
+    // filtering, no interlacing
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    put_png_chunk(&mut out, b"IHDR", &header);
+    put_png_chunk(&mut out, b"IDAT", &miniz_oxide::deflate::compress_to_vec_zlib(&filtered, 6));
+    put_png_chunk(&mut out, b"IEND", &[]);
+    Ok(out)
+}
+
+// Filter types 0-4 (None, Sub, Up, Average, Paeth) over 4-byte pixels
+fn png_filter_row(filter: u8, row: &[u8], above: &[u8], out: &mut [u8]) {
+    for i in 0..row.len() {
+        let left = if i >= 4 { row[i - 4] } else { 0 };
+        let up = above[i];
+        let upper_left = if i >= 4 { above[i - 4] } else { 0 };
+        let prediction = match filter {
+            0 => 0,
+            1 => left,
+            2 => up,
+            3 => ((left as u16 + up as u16) / 2) as u8,
+            _ => paeth(left, up, upper_left),
+        };
+        out[i] = row[i].wrapping_sub(prediction);
+    }
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+fn put_png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32fast::hash(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+// 32-bit BMP with a BITMAPV4HEADER, so the alpha channel survives; rows
+// are stored bottom-up as most readers expect
+#[wasm_bindgen]
+pub fn to_bmp_bytes(image_data: &[u8], width: u32, height: u32) -> Result<Vec<u8>, JsValue> {
+    check_image(image_data, width, height)?;
+    const FILE_HEADER: u32 = 14;
+    const INFO_HEADER: u32 = 108;
+    let pixel_bytes = u32::try_from(image_data.len())
+        .ok()
+        .filter(|&len| len <= u32::MAX - FILE_HEADER - INFO_HEADER)
+        .ok_or_else(|| JsValue::from(JsError::new("image is too large for a BMP file")))?;
+    let offset = FILE_HEADER + INFO_HEADER;
+
+    let mut out = Vec::with_capacity((offset + pixel_bytes) as usize);
+    out.extend_from_slice(b"BM");
+    put_u32(&mut out, offset + pixel_bytes);
+    put_u32(&mut out, 0); // reserved
+    put_u32(&mut out, offset);
+
+    put_u32(&mut out, INFO_HEADER);
+    put_u32(&mut out, width);
+    put_u32(&mut out, height); // positive: bottom-up
+    put_u16(&mut out, 1); // planes
+    put_u16(&mut out, 32); // bits per pixel
+    put_u32(&mut out, 3); // BI_BITFIELDS
+    put_u32(&mut out, pixel_bytes);
+    put_u32(&mut out, 2835); // 72 DPI, in pixels per metre
+    put_u32(&mut out, 2835);
+    put_u32(&mut out, 0); // palette colours
+    put_u32(&mut out, 0); // important colours
+    // Channel masks for the little-endian BGRA words written below
+    for mask in [0x00FF_0000u32, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000] {
+        put_u32(&mut out, mask);
+    }
+    out.extend_from_slice(b"BGRs"); // LCS_sRGB colour space, stored little-endian
+    out.extend_from_slice(&[0; 48]); // endpoints and gamma, unused with sRGB
+
+    for row in image_data.chunks_exact(width as usize * 4).rev() {
+        for pixel in row.chunks_exact(4) {
+            out.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,577 @@
+use wasm_bindgen::prelude::*;
+use wasmfx_core::filters;
+
+use crate::api::{BlurMethod, BlurOptions, SharpenOptions};
+use crate::cancel::{cancelled, CancellationToken};
+use crate::error::{check_finite, check_image, check_radius, check_range, check_rgba};
+use crate::memory::check_memory;
+use crate::parallel;
+use crate::settings::{self, QualityTier};
+use crate::transform::{from_premultiplied_linear, to_premultiplied_linear, weighted_sum};
+
+// ========================================================================
+// IMAGE FILTERS (`filters-core` feature)
+// The original demo filters. Everything here is re-exported from the crate
+// root, so other modules reach the pixel functions as `crate::blur_pixels`.
+// The kernels themselves are in `wasmfx_core::filters`; this side adds the
+// input checks, cancellation, quality tiers and worker bands.
+// ========================================================================
+
+// --- Demo 1: Grayscale Filter ---
+// This function is exported to JavaScript.
+// It takes a `Vec<u8>` which is the raw RGBA pixel data from a canvas.
+// It returns a new `Vec<u8>` with the filter applied, or throws if the
+// data isn't whole RGBA pixels.
+#[wasm_bindgen]
+pub fn apply_grayscale(mut image_data: Vec<u8>) -> Result<Vec<u8>, JsValue> {
+    check_rgba(&image_data)?;
+    console_log!("Rust: Grayscale filter started...");
+    grayscale_pixels(&mut image_data);
+    console_log!("Rust: Grayscale filter finished.");
+    Ok(image_data) // Return the modified vector
+}
+
+// The grayscale loop itself, shared with the zero-copy entry point.
+// SIMD builds hand off to the v128 kernel unless it's been switched off.
+pub(crate) fn grayscale_pixels(image_data: &mut [u8]) {
+    #[cfg(all(feature = "simd", target_arch = "wasm32"))]
+    if settings::simd_enabled() {
+        // Safety: the SIMD build only loads on engines with SIMD128
+        unsafe { crate::simd::grayscale(image_data) };
+        return;
+    }
+    grayscale_scalar(image_data);
+}
+
+// Also the tail for the SIMD kernel
+pub(crate) fn grayscale_scalar(image_data: &mut [u8]) {
+    wasmfx_core::filters::grayscale(image_data);
+}
+
+// --- Demo 2: Invert Filter (for another example) ---
+#[wasm_bindgen]
+pub fn apply_invert(mut image_data: Vec<u8>) -> Result<Vec<u8>, JsValue> {
+    check_rgba(&image_data)?;
+    console_log!("Rust: Invert filter started...");
+    invert_pixels(&mut image_data);
+    console_log!("Rust: Invert filter finished.");
+    Ok(image_data)
+}
+
+pub(crate) fn invert_pixels(image_data: &mut [u8]) {
+    #[cfg(all(feature = "simd", target_arch = "wasm32"))]
+    if settings::simd_enabled() {
+        // Safety: the SIMD build only loads on engines with SIMD128
+        unsafe { crate::simd::invert(image_data) };
+        return;
+    }
+    invert_scalar(image_data);
+}
+
+pub(crate) fn invert_scalar(image_data: &mut [u8]) {
+    wasmfx_core::filters::invert(image_data);
+}
+
+// --- Demo 3: Gaussian Blur (Computationally Intensive) ---
+// This filter is MUCH more complex than grayscale/invert
+// It performs many floating-point operations per pixel.
+// If cancelled, rows the vertical pass hasn't reached keep their original
+// pixels.
+// `options.radius` may be 1..1000, and `options.method` picks between this
+// and the box blur below.
+// `options.linear_light` blurs in linear light instead (see
+// `convolve_linear` below).
+#[wasm_bindgen]
+pub fn blur(mut image_data: Vec<u8>, width: u32, height: u32, options: &BlurOptions) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_radius(options.radius())?;
+    let name = match options.method() {
+        _ if options.linear_light() => "linear_convolution",
+        BlurMethod::Gaussian => "blur",
+        BlurMethod::Box => "box_blur",
+        BlurMethod::Stack => "stack_blur",
+    };
+    check_memory(name, width, height)?;
+    if options.linear_light() {
+        console_log!("Rust (WASM): Linear light blur started...");
+        let (w, h) = (width as usize, height as usize);
+        if let Some(result) = linear_blur_pixels(&image_data, w, h, options.method(), options.radius(), options.token()) {
+            image_data = result;
+        }
+        console_log!("Rust (WASM): Linear light blur finished.");
+        return Ok(image_data);
+    }
+    match options.method() {
+        BlurMethod::Gaussian => {
+            console_log!("Rust (WASM): Gaussian blur started...");
+            blur_pixels_cancellable(&mut image_data, width, height, options.radius(), options.token());
+            console_log!("Rust (WASM): Gaussian blur finished.");
+        }
+        BlurMethod::Box => {
+            console_log!("Rust (WASM): Box blur started...");
+            box_blur_pixels(&mut image_data, width as usize, height as usize, options.radius(), options.token());
+            console_log!("Rust (WASM): Box blur finished.");
+        }
+        BlurMethod::Stack => {
+            console_log!("Rust (WASM): Stack blur started...");
+            stack_blur_pixels(&mut image_data, width as usize, height as usize, options.radius(), options.token());
+            console_log!("Rust (WASM): Stack blur finished.");
+        }
+    }
+    Ok(image_data)
+}
+
+// Deprecated: API version 1 entry point, use `blur` with `BlurOptions`
+#[wasm_bindgen]
+pub fn apply_blur(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    radius: u32,
+    token: Option<CancellationToken>,
+) -> Result<Vec<u8>, JsValue> {
+    let mut options = BlurOptions::new(radius);
+    options.set_token(token);
+    blur(image_data, width, height, &options)
+}
+
+pub(crate) fn blur_pixels(image_data: &mut [u8], width: u32, height: u32, radius: u32) {
+    blur_pixels_cancellable(image_data, width, height, radius, None);
+}
+
+// Rows are handed to the worker bands this many at a time, with a
+// cancellation check in between
+const CANCEL_CHECK_ROWS: usize = 32;
+
+// Lower quality tiers skip taps (keeping the kernel symmetric) so large
+// radii stay interactive on slow devices
+pub(crate) fn gaussian_kernel(radius: i32) -> Vec<(i32, f32)> {
+    let tap_step = match settings::effective_quality() {
+        QualityTier::High => 1,
+        QualityTier::Balanced => if radius > 4 { 2 } else { 1 },
+        QualityTier::Low => if radius > 2 { (radius / 4).max(2) } else { 1 },
+    };
+    filters::gaussian_kernel(radius, tap_step)
+}
+
+pub(crate) fn blur_pixels_cancellable(
+    image_data: &mut [u8],
+    width: u32,
+    height: u32,
+    radius: u32,
+    token: Option<&CancellationToken>,
+) {
+    let width = width as usize;
+    let height = height as usize;
+    
+    // Create a temporary buffer
+    let mut temp = image_data.to_vec();
+    
+    // The kernel is computed once and shared by both passes
+    let kernel = gaussian_kernel(radius as i32);
+    
+    let row_bytes = width * 4;
+    // Without a token each pass hands all rows to the bands at once
+    let chunk_rows = if token.is_some() { CANCEL_CHECK_ROWS } else { height.max(1) };
+    let chunk_bytes = (row_bytes * chunk_rows).max(1);
+    
+    // Horizontal pass.
+    // Both passes work on bands of whole rows, which run on separate
+    // workers in `parallel` builds.
+    let source = &*image_data;
+    for (chunk_index, chunk) in temp.chunks_mut(chunk_bytes).enumerate() {
+        if cancelled(token) {
+            return;
+        }
+        let chunk_row = chunk_index * chunk_rows;
+        parallel::for_each_row_band(chunk, row_bytes, |first_row, band| {
+            for (row, out_row) in band.chunks_exact_mut(row_bytes).enumerate() {
+                let y = chunk_row + first_row + row;
+                let src_row = &source[y * row_bytes..][..row_bytes];
+                filters::gaussian_pass_row(src_row, out_row, width, &kernel);
+            }
+        });
+    }
+    
+    // Vertical pass.
+    // Columns are processed in strips of the tuned tile width so the rows
+    // each tap reads stay in cache on wide images.
+    let strip = settings::tile_size();
+    let temp = &temp;
+    for (chunk_index, chunk) in image_data.chunks_mut(chunk_bytes).enumerate() {
+        if cancelled(token) {
+            return;
+        }
+        let chunk_row = chunk_index * chunk_rows;
+        parallel::for_each_row_band(chunk, row_bytes, |first_row, band| {
+            filters::gaussian_pass_columns(temp, band, chunk_row + first_row, width, height, &kernel, strip);
+        });
+    }
+}
+
+// --- Demo 3b: Box Blur (Fast Gaussian Approximation) ---
+// Three box blurs in a row converge on a Gaussian. Each box pass keeps a
+// running sum, so the cost per pixel doesn't depend on the radius at all.
+// The box widths are chosen so the result matches the variance of
+// `apply_blur` with the same radius.
+// Deprecated: use `blur` with `BlurOptions` and `BlurMethod.Box`
+#[wasm_bindgen]
+pub fn apply_box_blur(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    radius: u32,
+    token: Option<CancellationToken>,
+) -> Result<Vec<u8>, JsValue> {
+    let mut options = BlurOptions::new(radius);
+    options.set_method(BlurMethod::Box);
+    options.set_token(token);
+    blur(image_data, width, height, &options)
+}
+
+pub(crate) fn box_blur_pixels(
+    image_data: &mut [u8],
+    width: usize,
+    height: usize,
+    radius: u32,
+    token: Option<&CancellationToken>,
+) {
+    let row_bytes = width * 4;
+    let mut temp = image_data.to_vec();
+    
+    for box_radius in filters::box_radii(radius as f32 / 3.0) {
+        if box_radius == 0 {
+            continue;
+        }
+        
+        // Horizontal: image_data -> temp
+        if cancelled(token) {
+            return;
+        }
+        let source = &*image_data;
+        parallel::for_each_row_band(&mut temp, row_bytes, |first_row, band| {
+            for (row, out_row) in band.chunks_exact_mut(row_bytes).enumerate() {
+                let src_row = &source[(first_row + row) * row_bytes..][..row_bytes];
+                filters::box_pass_row(src_row, out_row, width, box_radius);
+            }
+        });
+        
+        // Vertical: temp -> image_data. Each band primes its column sums
+        // from the rows around its first row, then slides down.
+        if cancelled(token) {
+            return;
+        }
+        let source = &temp;
+        parallel::for_each_row_band(image_data, row_bytes, |first_row, band| {
+            filters::box_pass_columns(source, band, first_row, width, height, box_radius);
+        });
+    }
+}
+
+// --- Demo 3c: Stack Blur (Realtime Preview) ---
+// One horizontal and one vertical pass of a triangle-shaped kernel, kept as
+// three running sums (Klingemann's stack blur), so like the box blur the
+// cost per pixel doesn't depend on the radius. It's a single pass each way
+// instead of three, which makes it the cheapest blur here and good enough
+// for previews; the kernel radius is picked to match the variance of
+// `apply_blur` with the same radius, so switching to the Gaussian for the
+// final export doesn't visibly change the result.
+#[wasm_bindgen]
+pub fn apply_stack_blur(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    radius: u32,
+    token: Option<CancellationToken>,
+) -> Result<Vec<u8>, JsValue> {
+    let mut options = BlurOptions::new(radius);
+    options.set_method(BlurMethod::Stack);
+    options.set_token(token);
+    blur(image_data, width, height, &options)
+}
+
+pub(crate) fn stack_blur_pixels(
+    image_data: &mut [u8],
+    width: usize,
+    height: usize,
+    radius: u32,
+    token: Option<&CancellationToken>,
+) {
+    let stack_radius = filters::stack_radius(radius as f32 / 3.0);
+    if stack_radius == 0 {
+        return;
+    }
+    let row_bytes = width * 4;
+    let mut temp = image_data.to_vec();
+
+    // Horizontal: image_data -> temp
+    if cancelled(token) {
+        return;
+    }
+    let source = &*image_data;
+    parallel::for_each_row_band(&mut temp, row_bytes, |first_row, band| {
+        for (row, out_row) in band.chunks_exact_mut(row_bytes).enumerate() {
+            let src_row = &source[(first_row + row) * row_bytes..][..row_bytes];
+            filters::stack_pass_row(src_row, out_row, width, stack_radius);
+        }
+    });
+
+    // Vertical: temp -> image_data
+    if cancelled(token) {
+        return;
+    }
+    let source = &temp;
+    parallel::for_each_row_band(image_data, row_bytes, |first_row, band| {
+        filters::stack_pass_columns(source, band, first_row, width, height, stack_radius);
+    });
+}
+
+// --- Demo 4: Sobel Edge Detection (Complex Math) ---
+#[wasm_bindgen]
+pub fn apply_edge_detection(image_data: Vec<u8>, width: u32, height: u32) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_memory("edge_detection", width, height)?;
+    console_log!("Rust (WASM): Edge detection started...");
+    let result = edge_detection_pixels(&image_data, width, height);
+    console_log!("Rust (WASM): Edge detection finished.");
+    Ok(result)
+}
+
+pub(crate) fn edge_detection_pixels(image_data: &[u8], width: u32, height: u32) -> Vec<u8> {
+    filters::edge_detection(image_data, width as usize, height as usize)
+}
+
+// --- Canny Edge Detection ---
+// Thin, connected outlines instead of the raw gradient: white where
+// `wasmfx_core::filters::canny` finds an edge, black elsewhere, alpha
+// kept. Gradients of at least `high` start an edge and those down to
+// `low` continue one; a hard black-to-white step scores about 540, so
+// 40 / 120 is a reasonable start. `low` above `high` is treated as
+// `high`.
+#[wasm_bindgen]
+pub fn apply_canny(image_data: Vec<u8>, width: u32, height: u32, low: f32, high: f32) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_finite("low", low)?;
+    check_finite("high", high)?;
+    check_memory("canny", width, height)?;
+    console_log!("Rust (WASM): Canny edge detection started...");
+    let result = filters::canny(&image_data, width as usize, height as usize, low.min(high), high);
+    console_log!("Rust (WASM): Canny edge detection finished.");
+    Ok(result)
+}
+
+// Sharpening strengths are percentages of the detail added back. At 100
+// times the detail every edge already clips to black or white, and the
+// integer kernel's sums stay well inside an i32.
+pub(crate) const MAX_SHARPEN_STRENGTH: u32 = 10_000;
+
+// --- Demo 6: Matrix Multiplication (INTEGER HEAVY) ---
+// Apply a complex convolution kernel - lots of integer math.
+// `options.strength` may be 0..10000.
+#[wasm_bindgen]
+pub fn sharpen(image_data: Vec<u8>, width: u32, height: u32, options: &SharpenOptions) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_range("strength", options.strength, 0, MAX_SHARPEN_STRENGTH)?;
+    check_memory(if options.linear_light { "linear_convolution" } else { "sharpen" }, width, height)?;
+    console_log!("Rust (WASM): Sharpen filter started...");
+    let result = if options.linear_light {
+        sharpen_linear(&image_data, width as usize, height as usize, options.strength)
+    } else {
+        sharpen_pixels(&image_data, width, height, options.strength)
+    };
+    console_log!("Rust (WASM): Sharpen filter finished.");
+    Ok(result)
+}
+
+// Deprecated: API version 1 entry point, use `sharpen` with `SharpenOptions`
+#[wasm_bindgen]
+pub fn apply_sharpen(image_data: Vec<u8>, width: u32, height: u32, strength: u32) -> Result<Vec<u8>, JsValue> {
+    sharpen(image_data, width, height, &SharpenOptions::new(strength))
+}
+
+// The scheduler's stages aren't validated when they're added, so the
+// strength is clamped here as well
+pub(crate) fn sharpen_pixels(image_data: &[u8], width: u32, height: u32, strength: u32) -> Vec<u8> {
+    filters::sharpen(image_data, width as usize, height as usize, strength.min(MAX_SHARPEN_STRENGTH))
+}
+
+// --- Linear Light ---
+// Averaging sRGB values darkens wherever bright and dark meet: the sRGB
+// midpoint between black and white, 128, is only a fifth of white's light,
+// so blurred highlights pick up dark rims and sharpening halos go muddy.
+// The `linear_light` options decode the image through a table to
+// premultiplied linear floats, convolve those, and encode the result
+// again. That costs a float pass per kernel tap, so it's several times
+// slower than the integer paths, and the box and stack blurs lose their
+// constant cost per pixel.
+
+// `None` if cancelled, which leaves the caller's image as it was
+pub(crate) fn linear_blur_pixels(
+    image_data: &[u8],
+    width: usize,
+    height: usize,
+    method: BlurMethod,
+    radius: u32,
+    token: Option<&CancellationToken>,
+) -> Option<Vec<u8>> {
+    convolve_linear(image_data, width, height, &blur_kernels(method, radius), token)
+}
+
+// The separable kernel(s) each blur method amounts to, applied one after
+// another along both axes
+fn blur_kernels(method: BlurMethod, radius: u32) -> Vec<Vec<(i32, f32)>> {
+    let sigma = radius as f32 / 3.0;
+    match method {
+        BlurMethod::Gaussian => vec![gaussian_kernel(radius as i32)],
+        BlurMethod::Box => filters::box_radii(sigma)
+            .iter()
+            .filter(|&&r| r > 0)
+            .map(|&r| {
+                let r = r as i32;
+                let weight = 1.0 / (2 * r + 1) as f32;
+                (-r..=r).map(|d| (d, weight)).collect()
+            })
+            .collect(),
+        BlurMethod::Stack => {
+            let r = filters::stack_radius(sigma) as i32;
+            let divisor = ((r + 1) * (r + 1)) as f32;
+            vec![(-r..=r).map(|d| (d, (r + 1 - d.abs()) as f32 / divisor)).collect()]
+        }
+    }
+}
+
+// Edge pixels repeat outwards, as in the integer passes
+fn convolve_linear(
+    image_data: &[u8],
+    width: usize,
+    height: usize,
+    kernels: &[Vec<(i32, f32)>],
+    token: Option<&CancellationToken>,
+) -> Option<Vec<u8>> {
+    let mut pixels = to_premultiplied_linear(image_data);
+    let mut temp = pixels.clone();
+    let clamp = |i: i32, len: usize| i.clamp(0, len as i32 - 1) as usize;
+    for kernel in kernels {
+        for (y, out_row) in temp.chunks_exact_mut(width).enumerate() {
+            if y % CANCEL_CHECK_ROWS == 0 && cancelled(token) {
+                return None;
+            }
+            let row = &pixels[y * width..][..width];
+            for (x, out) in out_row.iter_mut().enumerate() {
+                *out = weighted_sum(kernel.iter().map(|&(d, weight)| (&row[clamp(x as i32 + d, width)], weight)));
+            }
+        }
+        for (y, out_row) in pixels.chunks_exact_mut(width).enumerate() {
+            if y % CANCEL_CHECK_ROWS == 0 && cancelled(token) {
+                return None;
+            }
+            for (x, out) in out_row.iter_mut().enumerate() {
+                let taps = kernel.iter().map(|&(d, weight)| (&temp[clamp(y as i32 + d, height) * width + x], weight));
+                *out = weighted_sum(taps);
+            }
+        }
+    }
+    Some(from_premultiplied_linear(&pixels))
+}
+
+// `wasmfx_core::filters::sharpen`'s 5x5 kernel on linear light, leaving
+// the same two-pixel transparent black border
+pub(crate) fn sharpen_linear(image_data: &[u8], width: usize, height: usize, strength: u32) -> Vec<u8> {
+    #[rustfmt::skip]
+    const KERNEL: [[f32; 5]; 5] = [
+        [-1.0, -1.0, -1.0, -1.0, -1.0],
+        [-1.0,  2.0,  2.0,  2.0, -1.0],
+        [-1.0,  2.0,  8.0,  2.0, -1.0],
+        [-1.0,  2.0,  2.0,  2.0, -1.0],
+        [-1.0, -1.0, -1.0, -1.0, -1.0],
+    ];
+    let amount = strength as f32 / (8.0 * 100.0);
+    let source = to_premultiplied_linear(image_data);
+    let mut result = vec![[0.0f32; 4]; source.len()];
+    for y in 2..height.saturating_sub(2) {
+        for x in 2..width.saturating_sub(2) {
+            let taps = KERNEL.iter().enumerate().flat_map(|(ky, row)| {
+                let source = &source;
+                row.iter().enumerate().map(move |(kx, &k)| (&source[(y + ky - 2) * width + x + kx - 2], k))
+            });
+            let detail = weighted_sum(taps);
+            let centre = source[y * width + x];
+            // Sharpened colour can't exceed what full coverage allows
+            let alpha = centre[3];
+            let out = &mut result[y * width + x];
+            for c in 0..3 {
+                out[c] = (centre[c] + detail[c] * amount).clamp(0.0, alpha);
+            }
+            out[3] = alpha;
+        }
+    }
+    from_premultiplied_linear(&result)
+}
+
+// --- Unsharp Mask ---
+// The darkroom technique behind most "sharpen" sliders: the detail a
+// Gaussian blur of `radius` removes (original minus blurred) is added back
+// `amount` times over, so 1.0 doubles local contrast at that scale and a
+// larger radius sharpens coarser structure. Channels that differ from the
+// blur by `threshold` levels or less are left alone, which keeps flat
+// areas and sensor noise from being amplified. Alpha is kept. If
+// cancelled, rows the blur hasn't reached are left unsharpened.
+#[wasm_bindgen]
+pub fn apply_unsharp_mask(
+    mut image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    radius: u32,
+    amount: f32,
+    threshold: u8,
+    token: Option<CancellationToken>,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_radius(radius)?;
+    check_finite("amount", amount)?;
+    check_memory("unsharp_mask", width, height)?;
+    console_log!("Rust (WASM): Unsharp mask started...");
+    let mut blurred = image_data.clone();
+    blur_pixels_cancellable(&mut blurred, width, height, radius, token.as_ref());
+    for (pixel, soft) in image_data.chunks_exact_mut(4).zip(blurred.chunks_exact(4)) {
+        for c in 0..3 {
+            let detail = pixel[c] as f32 - soft[c] as f32;
+            if detail.abs() > threshold as f32 {
+                pixel[c] = (pixel[c] as f32 + amount * detail).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+    console_log!("Rust (WASM): Unsharp mask finished.");
+    Ok(image_data)
+}
+
+// --- High Pass ---
+// Just the detail layer of the unsharp mask: original minus its Gaussian
+// blur of `radius`, shifted so "no detail" is mid-gray (128). Added back
+// onto the blurred image (blurred + layer - 128, a grain-merge blend) it
+// rebuilds the original, so frequency-separation retouching can edit the
+// two halves apart; over the original, overlay or soft-light sharpens.
+// Alpha is kept. If cancelled, rows the blur hasn't reached come out flat
+// gray.
+#[wasm_bindgen]
+pub fn apply_high_pass(
+    mut image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    radius: u32,
+    token: Option<CancellationToken>,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_radius(radius)?;
+    check_memory("high_pass", width, height)?;
+    console_log!("Rust (WASM): High pass started...");
+    let mut blurred = image_data.clone();
+    blur_pixels_cancellable(&mut blurred, width, height, radius, token.as_ref());
+    for (pixel, soft) in image_data.chunks_exact_mut(4).zip(blurred.chunks_exact(4)) {
+        for c in 0..3 {
+            pixel[c] = (pixel[c] as i32 - soft[c] as i32 + 128).clamp(0, 255) as u8;
+        }
+    }
+    console_log!("Rust (WASM): High pass finished.");
+    Ok(image_data)
+}
@@ -0,0 +1,37 @@
+use wasm_bindgen::prelude::*;
+
+// --- Rect: an axis-aligned pixel rectangle shared by JS-facing APIs ---
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[wasm_bindgen]
+impl Rect {
+    #[wasm_bindgen(constructor)]
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Rect {
+        Rect { x, y, width, height }
+    }
+}
+
+// --- Insets: how far to extend (or trim) each edge of an image ---
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Insets {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+}
+
+#[wasm_bindgen]
+impl Insets {
+    #[wasm_bindgen(constructor)]
+    pub fn new(left: u32, top: u32, right: u32, bottom: u32) -> Insets {
+        Insets { left, top, right, bottom }
+    }
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::settings::{self, get_math_mode, get_quality, set_math_mode, set_quality, LogLevel, MathMode, QualityTier};
+use crate::{api, build_features, parallel, simd};
+
+// ========================================================================
+// INITIALIZATION
+// Loading the module does nothing by itself; hosts call `init()` once with
+// whatever they want to change and get back what the binary can do:
+//
+//   const caps = init({ log_level: "off", threads: 2, simd: false });
+//   if (!caps.simd_enabled) { /* scalar kernels */ }
+//
+// Every option is optional, and `init()` can be called again later to
+// reconfigure. Unknown options throw, so typos don't go unnoticed.
+//
+//   log_level      "off" | "error" | "warn" | "info" (default) | "debug"
+//   threads        how many pool workers filters may split work across
+//                  (the pool itself still comes from `init_thread_pool`)
+//   reserved_workers
+//                  workers kept free for interactive jobs, same as
+//                  `reserve_workers(n)`
+//   max_memory_mb  largest image buffer, and so the largest single heap
+//                  growth, a call may ask for; bigger images throw
+//   memory_ceiling_mb
+//                  most working memory a heavy filter (blur, median, ...)
+//                  may need, per `estimate_memory`; over it, the call
+//                  throws with a suggested tile size
+//   simd           use the SIMD kernels (only meaningful in SIMD builds)
+//   quality        "low" | "balanced" | "high"
+//   math_mode      "exact" (default, bit-identical everywhere) | "fast"
+//   seed           same as `set_seed(seed)`
+// ========================================================================
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct InitOptions {
+    log_level: Option<LogLevel>,
+    threads: Option<u32>,
+    reserved_workers: Option<usize>,
+    max_memory_mb: Option<u32>,
+    memory_ceiling_mb: Option<u32>,
+    simd: Option<bool>,
+    quality: Option<QualityTier>,
+    math_mode: Option<MathMode>,
+    seed: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct Capabilities {
+    api_version: u32,
+    features: Vec<String>,
+    simd_build: bool,
+    simd_supported: bool,
+    simd_enabled: bool,
+    // Workers in the pool (0 without `init_thread_pool`) and how many of
+    // them filters will use
+    pool_size: usize,
+    threads: u32,
+    // Workers background jobs leave for interactive ones
+    reserved_workers: usize,
+    quality: QualityTier,
+    math_mode: MathMode,
+    log_level: LogLevel,
+    max_memory_mb: Option<usize>,
+    memory_ceiling_mb: Option<u64>,
+    // Current size of the module's linear memory
+    memory_bytes: usize,
+}
+
+// --- Init ---
+// Takes an options object (or nothing) and returns the capabilities report
+#[wasm_bindgen]
+pub fn init(options: JsValue) -> Result<JsValue, JsValue> {
+    let options: InitOptions = if options.is_undefined() || options.is_null() {
+        InitOptions::default()
+    } else {
+        let json = js_sys::JSON::stringify(&options)
+            .ok()
+            .and_then(|json| json.as_string())
+            .unwrap_or_default();
+        serde_json::from_str(&json)
+            .map_err(|err| JsValue::from(JsError::new(&format!("invalid init options: {}", err))))?
+    };
+
+    if let Some(level) = options.log_level {
+        settings::set_log_level(level);
+    }
+    if let Some(threads) = options.threads {
+        settings::set_thread_count(threads);
+    }
+    if let Some(count) = options.reserved_workers {
+        parallel::reserve_workers(count);
+    }
+    if let Some(limit) = options.max_memory_mb {
+        settings::set_max_image_bytes((limit > 0).then(|| limit as usize * 1024 * 1024));
+    }
+    if let Some(ceiling) = options.memory_ceiling_mb {
+        settings::set_memory_ceiling((ceiling > 0).then(|| ceiling as u64 * 1024 * 1024));
+    }
+    if let Some(enabled) = options.simd {
+        settings::set_simd_enabled(enabled && simd::simd_build());
+    }
+    if let Some(tier) = options.quality {
+        set_quality(tier);
+    }
+    if let Some(mode) = options.math_mode {
+        set_math_mode(mode);
+    }
+    if let Some(seed) = options.seed {
+        settings::set_seed(Some(seed));
+    }
+
+    let report = Capabilities {
+        api_version: api::API_VERSION,
+        features: build_features(),
+        simd_build: simd::simd_build(),
+        simd_supported: simd::simd_supported(),
+        simd_enabled: settings::simd_enabled(),
+        pool_size: parallel::pool_size(),
+        threads: settings::thread_count(),
+        reserved_workers: parallel::reserved_workers(),
+        quality: get_quality(),
+        math_mode: get_math_mode(),
+        log_level: settings::log_level(),
+        max_memory_mb: settings::max_image_bytes().map(|bytes| bytes / (1024 * 1024)),
+        memory_ceiling_mb: settings::memory_ceiling().map(|bytes| bytes / (1024 * 1024)),
+        memory_bytes: memory_bytes(),
+    };
+    console_log!("Rust (WASM): Initialized ({}).", report.features.join(", "));
+
+    serde_json::to_string(&report)
+        .ok()
+        .and_then(|json| js_sys::JSON::parse(&json).ok())
+        .ok_or_else(|| JsError::new("couldn't build the capabilities report").into())
+}
+
+fn memory_bytes() -> usize {
+    #[cfg(target_arch = "wasm32")]
+    return core::arch::wasm32::memory_size(0) * 65536;
+    #[cfg(not(target_arch = "wasm32"))]
+    0
+}
@@ -0,0 +1,186 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::binary::luma;
+use crate::error::check_image;
+use crate::memory::check_memory;
+use crate::pixels::pixels;
+
+// ========================================================================
+// INPAINTING
+// Fills a masked region from its surroundings (Telea, "An Image Inpainting
+// Technique Based on the Fast Marching Method", 2004). The fill front
+// moves inwards from the mask's edge in order of distance, and each pixel
+// it reaches becomes a weighted average of the already known pixels near
+// it (each extended along its own gradient), favouring close ones, ones
+// along the front's direction of travel and ones at the same distance
+// from the edge. That continues lines and gradients into small holes;
+// large ones come out smooth rather than textured.
+// ========================================================================
+
+// Filled pixels average the known ones up to this many pixels away
+const INPAINT_RADIUS: isize = 5;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Known,
+    // On the fill front: filled, with its distance settled or about to be
+    Band,
+    Inside,
+}
+
+// --- Inpaint ---
+// `mask` is an RGBA image the size of `image_data`, white (luminance above
+// 127) where pixels should be reconstructed; a canvas the user brushed
+// over in white works as is. All four channels are filled. An image that
+// is masked everywhere has nothing to fill from and comes back unchanged.
+#[wasm_bindgen]
+pub fn inpaint(image_data: Vec<u8>, width: u32, height: u32, mask: &[u8]) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_image(mask, width, height)?;
+    check_memory("inpaint", width, height)?;
+    console_log!("Rust (WASM): Inpainting started...");
+    let (w, h) = (width as usize, height as usize);
+    let mut state: Vec<State> = pixels(mask)
+        .iter()
+        .map(|pixel| if luma(pixel) > 127 { State::Inside } else { State::Known })
+        .collect();
+    if !state.contains(&State::Known) {
+        console_log!("Rust (WASM): Inpainting finished (nothing known).");
+        return Ok(image_data);
+    }
+
+    let mut result = image_data;
+    let mut distance = vec![0.0f32; w * h];
+    // Distances are never negative, so their bit patterns sort the same
+    // way the floats do
+    let mut front = BinaryHeap::new();
+    for i in 0..w * h {
+        if state[i] == State::Inside {
+            distance[i] = f32::INFINITY;
+        } else if neighbours(i, w, h).any(|n| state[n] == State::Inside) {
+            state[i] = State::Band;
+            front.push(Reverse((0u32, i)));
+        }
+    }
+
+    while let Some(Reverse((_, i))) = front.pop() {
+        if state[i] == State::Known {
+            continue;
+        }
+        state[i] = State::Known;
+        for n in neighbours(i, w, h) {
+            if state[n] != State::Inside {
+                continue;
+            }
+            let (x, y) = ((n % w) as isize, (n / w) as isize);
+            // The front's arrival time from each pair of axis neighbours
+            let solve = |a: (isize, isize), b: (isize, isize)| {
+                let t = |(x, y): (isize, isize)| {
+                    if settled(&state, w, h, x, y) { distance[y as usize * w + x as usize] } else { f32::INFINITY }
+                };
+                eikonal(t(a), t(b))
+            };
+            distance[n] = solve((x - 1, y), (x, y - 1))
+                .min(solve((x + 1, y), (x, y - 1)))
+                .min(solve((x - 1, y), (x, y + 1)))
+                .min(solve((x + 1, y), (x, y + 1)));
+            fill(&mut result, &state, &distance, w, h, n);
+            state[n] = State::Band;
+            front.push(Reverse((distance[n].to_bits(), n)));
+        }
+    }
+    console_log!("Rust (WASM): Inpainting finished.");
+    Ok(result)
+}
+
+// The 4-connected neighbours of pixel `i`
+fn neighbours(i: usize, width: usize, height: usize) -> impl Iterator<Item = usize> {
+    let (x, y) = (i % width, i / width);
+    [
+        (x > 0).then(|| i - 1),
+        (x + 1 < width).then(|| i + 1),
+        (y > 0).then(|| i - width),
+        (y + 1 < height).then(|| i + width),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+// Whether (x, y) is in the image and already has its final value
+fn settled(state: &[State], width: usize, height: usize, x: isize, y: isize) -> bool {
+    let in_image = (0..width as isize).contains(&x) && (0..height as isize).contains(&y);
+    in_image && state[y as usize * width + x as usize] != State::Inside
+}
+
+// Solution of |grad T| = 1 from two perpendicular neighbours' times
+fn eikonal(a: f32, b: f32) -> f32 {
+    match (a.is_finite(), b.is_finite()) {
+        (true, true) => {
+            let discriminant = 2.0 - (a - b) * (a - b);
+            if discriminant >= 0.0 {
+                let t = (a + b + discriminant.sqrt()) / 2.0;
+                if t >= a && t >= b {
+                    return t;
+                }
+            }
+            a.min(b) + 1.0
+        }
+        (true, false) => a + 1.0,
+        (false, true) => b + 1.0,
+        (false, false) => f32::INFINITY,
+    }
+}
+
+// Sets pixel `n` from the settled pixels around it
+fn fill(data: &mut [u8], state: &[State], distance: &[f32], width: usize, height: usize, n: usize) {
+    let (x, y) = ((n % width) as isize, (n / width) as isize);
+    let settled = |x: isize, y: isize| settled(state, width, height, x, y);
+    let t = |x: isize, y: isize| distance[y as usize * width + x as usize];
+    // Direction the front travels in, from one-sided differences where
+    // a neighbour isn't settled yet
+    let gradient = |dx: isize, dy: isize| match (settled(x + dx, y + dy), settled(x - dx, y - dy)) {
+        (true, true) => (t(x + dx, y + dy) - t(x - dx, y - dy)) / 2.0,
+        (true, false) => t(x + dx, y + dy) - t(x, y),
+        (false, true) => t(x, y) - t(x - dx, y - dy),
+        (false, false) => 0.0,
+    };
+    let (gx, gy) = (gradient(1, 0), gradient(0, 1));
+
+    let mut sum = [0.0f32; 4];
+    let mut total = 0.0f32;
+    for qy in y - INPAINT_RADIUS..=y + INPAINT_RADIUS {
+        for qx in x - INPAINT_RADIUS..=x + INPAINT_RADIUS {
+            let (dx, dy) = ((x - qx) as f32, (y - qy) as f32);
+            let length_sq = dx * dx + dy * dy;
+            if length_sq == 0.0 || length_sq > (INPAINT_RADIUS * INPAINT_RADIUS) as f32 || !settled(qx, qy) {
+                continue;
+            }
+            let length = length_sq.sqrt();
+            let direction = ((dx * gx + dy * gy) / length).abs().max(1e-6);
+            let level = 1.0 / (1.0 + (t(qx, qy) - t(x, y)).abs());
+            let weight = direction * level / length_sq;
+            // Extrapolated from q along its own gradient, so ramps carry on
+            // into the hole instead of flattening out
+            let value = |x: isize, y: isize, c: usize| data[(y as usize * width + x as usize) * 4 + c] as f32;
+            for (c, sum) in sum.iter_mut().enumerate() {
+                let slope = |sx: isize, sy: isize| match (settled(qx + sx, qy + sy), settled(qx - sx, qy - sy)) {
+                    (true, true) => (value(qx + sx, qy + sy, c) - value(qx - sx, qy - sy, c)) / 2.0,
+                    (true, false) => value(qx + sx, qy + sy, c) - value(qx, qy, c),
+                    (false, true) => value(qx, qy, c) - value(qx - sx, qy - sy, c),
+                    (false, false) => 0.0,
+                };
+                *sum += weight * (value(qx, qy, c) + slope(1, 0) * dx + slope(0, 1) * dy);
+            }
+            total += weight;
+        }
+    }
+    if total > 0.0 {
+        let out = &mut data[n * 4..][..4];
+        for c in 0..4 {
+            out[c] = (sum[c] / total).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
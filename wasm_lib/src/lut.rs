@@ -0,0 +1,170 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::check_rgba;
+use crate::pixels::pixels_mut;
+
+// ========================================================================
+// 3D LUTS
+// Colour grades exported as Adobe / Resolve `.cube` files. The file is
+// parsed once into a `LutHandle`, which JS keeps and passes to
+// `apply_lut3d` for every frame (and frees with `handle.free()` when done):
+//
+//   const lut = load_cube_lut(await (await fetch("teal-orange.cube")).text());
+//   const graded = apply_lut3d(pixels, lut);
+//
+// The table is applied to the sRGB values as they are, which is what
+// grades exported for display-referred footage expect.
+// ========================================================================
+
+// Largest LUT_3D_SIZE accepted; grading tools export 17, 33 or 65
+const MAX_LUT_SIZE: usize = 256;
+
+#[wasm_bindgen]
+pub struct LutHandle {
+    size: usize,
+    // Output colours, red varying fastest, then green, then blue
+    table: Vec<[f32; 3]>,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+}
+
+#[wasm_bindgen]
+impl LutHandle {
+    // Points along each axis of the cube
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> u32 {
+        self.size as u32
+    }
+}
+
+// --- Load .cube ---
+// Reads LUT_3D_SIZE, the optional DOMAIN_MIN / DOMAIN_MAX and the table
+// rows, ignoring TITLE and `#` comments. Throws naming the line at fault,
+// and for 1D LUTs (LUT_1D_SIZE), which aren't supported.
+#[wasm_bindgen]
+pub fn load_cube_lut(text: &str) -> Result<LutHandle, JsValue> {
+    parse_cube(text).map_err(|message| JsError::new(&format!("invalid .cube file: {}", message)).into())
+}
+
+fn parse_cube(text: &str) -> Result<LutHandle, String> {
+    let mut size = None;
+    let mut domain_min = [0.0f32; 3];
+    let mut domain_max = [1.0f32; 3];
+    let mut table = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let at = |message: &str| format!("line {}: {}", index + 1, message);
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let keyword = words.next().unwrap_or_default();
+        let triple = |words: std::str::SplitWhitespace| -> Result<[f32; 3], String> {
+            let values: Vec<f32> =
+                words.map(|word| word.parse::<f32>()).collect::<Result<_, _>>().map_err(|_| at("expected numbers"))?;
+            match values[..] {
+                [r, g, b] if values.iter().all(|v| v.is_finite()) => Ok([r, g, b]),
+                _ => Err(at("expected three finite numbers")),
+            }
+        };
+        match keyword {
+            "TITLE" => {}
+            "LUT_1D_SIZE" => return Err(at("1D LUTs aren't supported")),
+            "LUT_3D_SIZE" => {
+                let n = words
+                    .next()
+                    .and_then(|word| word.parse::<usize>().ok())
+                    .filter(|n| (2..=MAX_LUT_SIZE).contains(n))
+                    .ok_or_else(|| at(&format!("LUT_3D_SIZE must be between 2 and {}", MAX_LUT_SIZE)))?;
+                size = Some(n);
+            }
+            "DOMAIN_MIN" => domain_min = triple(words)?,
+            "DOMAIN_MAX" => domain_max = triple(words)?,
+            _ if keyword.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.') => {
+                table.push(triple(line.split_whitespace())?);
+            }
+            // Vendor keywords (LUT_IN_VIDEO_RANGE and the like)
+            _ => {}
+        }
+    }
+
+    let size = size.ok_or("missing LUT_3D_SIZE")?;
+    if table.len() != size * size * size {
+        return Err(format!("a size {} LUT has {} rows, found {}", size, size * size * size, table.len()));
+    }
+    if (0..3).any(|c| domain_max[c] <= domain_min[c]) {
+        return Err("DOMAIN_MAX must be above DOMAIN_MIN".to_string());
+    }
+    Ok(LutHandle { size, table, domain_min, domain_max })
+}
+
+// --- Apply 3D LUT ---
+// Tetrahedral interpolation between the table's points, as grading
+// software uses: it follows the gray axis exactly and bends hues less
+// than trilinear. Alpha is kept.
+#[wasm_bindgen]
+pub fn apply_lut3d(mut image_data: Vec<u8>, lut: &LutHandle) -> Result<Vec<u8>, JsValue> {
+    check_rgba(&image_data)?;
+    console_log!("Rust (WASM): 3D LUT started...");
+    // Every sRGB byte's position on the cube's axes
+    let positions: [[f32; 256]; 3] = std::array::from_fn(|c| {
+        let (min, max) = (lut.domain_min[c], lut.domain_max[c]);
+        let last = (lut.size - 1) as f32;
+        std::array::from_fn(|v| ((v as f32 / 255.0 - min) / (max - min)).clamp(0.0, 1.0) * last)
+    });
+    for pixel in pixels_mut(&mut image_data) {
+        let rgb = lut.sample([
+            positions[0][pixel[0] as usize],
+            positions[1][pixel[1] as usize],
+            positions[2][pixel[2] as usize],
+        ]);
+        for (out, value) in pixel.iter_mut().zip(rgb) {
+            *out = (value * 255.0).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+    console_log!("Rust (WASM): 3D LUT finished.");
+    Ok(image_data)
+}
+
+impl LutHandle {
+    fn at(&self, r: usize, g: usize, b: usize) -> [f32; 3] {
+        self.table[(b * self.size + g) * self.size + r]
+    }
+
+    // `position` is in table steps, 0..size - 1 on each axis
+    fn sample(&self, position: [f32; 3]) -> [f32; 3] {
+        let base = position.map(|p| (p as usize).min(self.size - 2));
+        let [fr, fg, fb] = [0, 1, 2].map(|c| position[c] - base[c] as f32);
+        let [r, g, b] = base;
+        let corner = |dr: usize, dg: usize, db: usize| self.at(r + dr, g + dg, b + db);
+        // The cell splits into six tetrahedra along its gray diagonal; the
+        // order of the fractions picks the one holding the point, and the
+        // path from (0, 0, 0) to (1, 1, 1) through its corners
+        let path = if fr > fg {
+            if fg > fb {
+                [(corner(1, 0, 0), fr), (corner(1, 1, 0), fg), (corner(1, 1, 1), fb)]
+            } else if fr > fb {
+                [(corner(1, 0, 0), fr), (corner(1, 0, 1), fb), (corner(1, 1, 1), fg)]
+            } else {
+                [(corner(0, 0, 1), fb), (corner(1, 0, 1), fr), (corner(1, 1, 1), fg)]
+            }
+        } else if fb > fg {
+            [(corner(0, 0, 1), fb), (corner(0, 1, 1), fg), (corner(1, 1, 1), fr)]
+        } else if fb > fr {
+            [(corner(0, 1, 0), fg), (corner(0, 1, 1), fb), (corner(1, 1, 1), fr)]
+        } else {
+            [(corner(0, 1, 0), fg), (corner(1, 1, 0), fr), (corner(1, 1, 1), fb)]
+        };
+        // c000 + f1 (c1 - c000) + f2 (c2 - c1) + f3 (c3 - c2)
+        let mut previous = corner(0, 0, 0);
+        let mut result = previous;
+        for (point, fraction) in path {
+            for c in 0..3 {
+                result[c] += fraction * (point[c] - previous[c]);
+            }
+            previous = point;
+        }
+        result
+    }
+}
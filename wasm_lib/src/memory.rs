@@ -0,0 +1,159 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::settings;
+
+// ========================================================================
+// MEMORY ESTIMATES
+// How much wasm memory a filter needs at its peak, so hosts can check an
+// 8K canvas before handing it over instead of finding out when the tab is
+// killed. The heavy filters (the ones that allocate whole extra images)
+// also check themselves against the ceiling set with
+// `init({ memory_ceiling_mb })`, and throw an error whose
+// `suggested_tile` says how big a tile would fit.
+// ========================================================================
+
+// Peak bytes per pixel: the copy of the input wasm-bindgen makes, plus
+// every image-sized buffer the filter allocates. Row-sized scratch space
+// is left out. Names match the exports without their `apply_` prefix.
+const BYTES_PER_PIXEL: &[(&str, u64)] = &[
+    ("grayscale", 4),
+    ("invert", 4),
+    ("saturation", 4),
+    ("vibrance", 4),
+    ("curves", 4),
+    ("sepia", 4),
+    ("posterize", 4),
+    ("equalize", 4),
+    ("exposure", 4),
+    ("temperature_tint", 4),
+    ("threshold", 4),
+    ("dither_fs", 4),
+    ("dither_bayer", 4),
+    ("recolor_to_palette", 4),
+    ("quantize_to_palette", 4),
+    ("pixelate", 4),
+    ("pixel_sort", 4),
+    ("grain", 4),
+    ("mandelbrot", 4),
+    ("fractal_tile", 4),
+    ("decode_blurhash", 4),
+    // Input plus one scratch or output image
+    ("blur", 8),
+    ("box_blur", 8),
+    ("stack_blur", 8),
+    // `linear_light` blurs and sharpening: the input, two f32 working
+    // images and the output
+    ("linear_convolution", 40),
+    ("motion_blur", 8),
+    ("zoom_blur", 8),
+    ("radial_blur", 8),
+    ("chromatic_aberration", 8),
+    ("channel_glitch", 8),
+    ("halftone", 8),
+    ("sharpen", 8),
+    ("unsharp_mask", 8),
+    ("high_pass", 8),
+    ("median", 8),
+    ("bilateral", 8),
+    // Input, the previous frame and the output
+    ("deinterlace", 12),
+    // Input, output and the pass in between, plus the first pass's result
+    // when opening or closing
+    ("morph", 16),
+    // Input, the f32 output and f64 squared distances
+    ("distance_transform", 16),
+    // The input, the smaller levels (a third as much again), f32 residuals
+    // for every level and the expand scratch
+    ("pyramid", 48),
+    // Measured at the larger of the source and target sizes: the image,
+    // its working copy, gradients, seam costs and the seam bookkeeping
+    ("seam_carve", 40),
+    // Measured at the output size: the input copy (no larger) and the
+    // output
+    ("crop", 8),
+    // Measured at the larger of the source and target sizes: the input,
+    // f32 premultiplied pixels, the horizontal pass, the f32 result and
+    // the output
+    ("resize", 56),
+    ("resize_with_par", 56),
+    // Measured at the larger of the source and rotated sizes: the input,
+    // f32 premultiplied pixels, the f32 result and the output
+    ("rotate", 40),
+    ("pad", 8),
+    // Input, mask, f32 arrival times, pixel states and the fill front
+    ("inpaint", 24),
+    // Measured at the output size: f32 working pixels, the bicubic's
+    // half-way pass and the output
+    ("upscale", 36),
+    // Input, output and a summed-area table of six u64 sums
+    ("kuwahara", 56),
+    // Input, output (or smoothed copy) and the f32 (x, y) gradient pair
+    // per pixel
+    ("edge_detection", 16),
+    // Input, smoothed copy and its blur scratch, gradients, magnitudes,
+    // labels and the output
+    ("canny", 29),
+    ("cartoon", 16),
+    // The target plus its OKLab values as f32 (the source isn't counted)
+    ("transfer_color", 16),
+    // The f32 input and the output
+    ("tone_map", 20),
+    // Both inputs, then two luminance planes, their five f32 local means
+    // and the blur scratch
+    ("compare_images", 40),
+    // The input, its luminance and the Sobel gradient pairs
+    ("measure_sharpness", 16),
+    // The base, blended in place, and the overlay
+    ("blend_images", 8),
+    // The canvas; the layers are already held by the stack
+    ("layer_stack", 4),
+    // The image and two f32 planes for the shadow's blur
+    ("render_drop_shadow", 12),
+    // Luminance plane, f32 local averages and (for the mean) a u64
+    // summed-area table
+    ("adaptive_threshold", 17),
+];
+
+pub(crate) fn bytes_per_pixel(filter: &str) -> Option<u64> {
+    BYTES_PER_PIXEL
+        .iter()
+        .find(|&&(name, _)| name == filter)
+        .map(|&(_, bytes)| bytes)
+}
+
+// --- Estimate ---
+// Peak bytes `filter` needs for a `width x height` image; throws for a
+// filter name it doesn't know
+#[wasm_bindgen]
+pub fn estimate_memory(filter: &str, width: u32, height: u32) -> Result<u64, JsValue> {
+    let bytes = bytes_per_pixel(filter).ok_or_else(|| JsError::new(&format!("unknown filter `{}`", filter)))?;
+    Ok(width as u64 * height as u64 * bytes)
+}
+
+// Called by the heavy filters before they allocate anything
+pub(crate) fn check_memory(filter: &'static str, width: u32, height: u32) -> Result<(), Error> {
+    let Some(ceiling) = settings::memory_ceiling() else {
+        return Ok(());
+    };
+    let per_pixel = bytes_per_pixel(filter).unwrap_or(4);
+    let bytes = width as u64 * height as u64 * per_pixel;
+    if bytes <= ceiling {
+        return Ok(());
+    }
+    Err(Error::OverMemoryCeiling {
+        filter,
+        width,
+        height,
+        bytes,
+        ceiling,
+        tile: suggested_tile(ceiling, per_pixel),
+    })
+}
+
+// Side of the largest square tile that fits under the ceiling, rounded
+// down to a multiple of 64 pixels once it's that big
+fn suggested_tile(ceiling: u64, per_pixel: u64) -> u32 {
+    let side = ((ceiling / per_pixel) as f64).sqrt() as u32;
+    if side >= 64 { side / 64 * 64 } else { side.max(1) }
+}
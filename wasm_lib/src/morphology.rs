@@ -0,0 +1,255 @@
+use wasm_bindgen::prelude::*;
+
+use crate::binary::luma;
+use crate::cancel::CancellationToken;
+use crate::denoise::for_each_output_row;
+use crate::error::{check_image, check_radius};
+use crate::memory::check_memory;
+use crate::pixels::{pixels, Pixel};
+
+// ========================================================================
+// MORPHOLOGY
+// Erosion (each channel becomes the minimum over a neighbourhood shaped
+// by the structuring element), dilation (the maximum) and the two ways of
+// chaining them. On a black and white mask, such as the output of
+// `apply_threshold`, that shrinks or grows the white regions; on colour
+// or gray images it works on R, G and B independently. Alpha is kept,
+// and neighbours outside the image are ignored rather than padded. The
+// distance transform at the end measures the same masks for skeletons and
+// proximity effects.
+// ========================================================================
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MorphOp {
+    // Shrinks white areas and removes specks smaller than the element
+    Erode,
+    // Grows white areas and fills gaps smaller than the element
+    Dilate,
+    // Erode then dilate: removes small bright details, keeps the rest
+    Open,
+    // Dilate then erode: fills small dark holes and joins nearby shapes
+    Close,
+}
+
+// The structuring element, (2 * radius + 1) pixels across
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KernelShape {
+    Square,
+    // The centre row and column only
+    Cross,
+    // Pixels within `radius` of the centre; slower, since it can't be split
+    // into a horizontal and a vertical pass
+    Disk,
+}
+
+// --- Morph ---
+// If cancelled, rows not reached yet keep their original pixels (for Open
+// and Close, those of the first pass).
+#[wasm_bindgen]
+pub fn morph(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    op: MorphOp,
+    kernel_shape: KernelShape,
+    radius: u32,
+    token: Option<CancellationToken>,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_radius(radius)?;
+    check_memory("morph", width, height)?;
+    console_log!("Rust (WASM): Morphology started...");
+    let (w, h, r) = (width as usize, height as usize, radius as usize);
+    let pass = |data: &[u8], dilate: bool| match kernel_shape {
+        KernelShape::Square => square_pass(data, w, h, r, dilate, token.as_ref()),
+        KernelShape::Cross => span_pass(data, w, h, &cross_spans(r), dilate, token.as_ref()),
+        KernelShape::Disk => span_pass(data, w, h, &disk_spans(r), dilate, token.as_ref()),
+    };
+    let result = match op {
+        MorphOp::Erode => pass(&image_data, false),
+        MorphOp::Dilate => pass(&image_data, true),
+        MorphOp::Open => pass(&pass(&image_data, false), true),
+        MorphOp::Close => pass(&pass(&image_data, true), false),
+    };
+    console_log!("Rust (WASM): Morphology finished.");
+    Ok(result)
+}
+
+// Min or max of each colour channel over `window`, alpha from `centre`
+fn extreme<'a>(centre: &Pixel, window: impl Iterator<Item = &'a Pixel>, dilate: bool) -> Pixel {
+    let mut out = *centre;
+    for pixel in window {
+        for c in 0..3 {
+            out[c] = if dilate { out[c].max(pixel[c]) } else { out[c].min(pixel[c]) };
+        }
+    }
+    out
+}
+
+// The square element splits into a horizontal line then a vertical one
+fn square_pass(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    radius: usize,
+    dilate: bool,
+    token: Option<&CancellationToken>,
+) -> Vec<u8> {
+    let source = pixels(data);
+    let mut horizontal = data.to_vec();
+    for_each_output_row(&mut horizontal, width, height, token, |y, out_row| {
+        let row = &source[y * width..][..width];
+        for (x, out) in out_row.iter_mut().enumerate() {
+            let window = &row[x.saturating_sub(radius)..(x + radius + 1).min(width)];
+            *out = extreme(&row[x], window.iter(), dilate);
+        }
+    });
+
+    let horizontal_pixels = pixels(&horizontal);
+    let mut result = data.to_vec();
+    for_each_output_row(&mut result, width, height, token, |y, out_row| {
+        let rows = y.saturating_sub(radius)..(y + radius + 1).min(height);
+        for (x, out) in out_row.iter_mut().enumerate() {
+            let column = rows.clone().map(|ny| &horizontal_pixels[ny * width + x]);
+            *out = extreme(&horizontal_pixels[y * width + x], column, dilate);
+        }
+    });
+    result
+}
+
+// (dy, half width) rows of a cross and a disk
+fn cross_spans(radius: usize) -> Vec<(isize, usize)> {
+    let r = radius as isize;
+    (-r..=r).map(|dy| (dy, if dy == 0 { radius } else { 0 })).collect()
+}
+
+fn disk_spans(radius: usize) -> Vec<(isize, usize)> {
+    let r = radius as isize;
+    (-r..=r)
+        .map(|dy| {
+            let half = ((radius * radius) as f32 - (dy * dy) as f32).sqrt() as usize;
+            (dy, half)
+        })
+        .collect()
+}
+
+// Any element given as horizontal spans centred on each of its rows
+fn span_pass(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    spans: &[(isize, usize)],
+    dilate: bool,
+    token: Option<&CancellationToken>,
+) -> Vec<u8> {
+    let source = pixels(data);
+    let mut result = data.to_vec();
+    for_each_output_row(&mut result, width, height, token, |y, out_row| {
+        for (x, out) in out_row.iter_mut().enumerate() {
+            let window = spans.iter().flat_map(|&(dy, half)| {
+                let ny = y as isize + dy;
+                let row = if ny < 0 || ny >= height as isize { &[][..] } else { &source[ny as usize * width..][..width] };
+                &row[x.saturating_sub(half).min(row.len())..(x + half + 1).min(row.len())]
+            });
+            *out = extreme(&source[y * width + x], window, dilate);
+        }
+    });
+    result
+}
+
+// --- Distance Transform ---
+// Euclidean distance from every pixel to the nearest black one (luminance
+// 127 or below) in `binary_data`, as a Float32Array in row order: 0 on
+// black pixels, growing towards the middle of white regions, whose ridge
+// is their skeleton. Infinity everywhere if the image has no black pixel.
+// Exact, using Felzenszwalb and Huttenlocher's lower envelope of
+// parabolas: a pass down each column, then one along each row.
+#[wasm_bindgen]
+pub fn distance_transform(binary_data: &[u8], width: u32, height: u32) -> Result<Vec<f32>, JsValue> {
+    check_image(binary_data, width, height)?;
+    check_memory("distance_transform", width, height)?;
+    console_log!("Rust (WASM): Distance transform started...");
+    let (w, h) = (width as usize, height as usize);
+    let mut squared: Vec<f64> = pixels(binary_data)
+        .iter()
+        .map(|pixel| if luma(pixel) > 127 { FAR } else { 0.0 })
+        .collect();
+
+    let mut line = vec![0.0; w.max(h)];
+    let mut envelope = Envelope::new(w.max(h));
+    for x in 0..w {
+        for y in 0..h {
+            line[y] = squared[y * w + x];
+        }
+        envelope.transform(&mut line[..h]);
+        for y in 0..h {
+            squared[y * w + x] = line[y];
+        }
+    }
+    for row in squared.chunks_exact_mut(w) {
+        envelope.transform(row);
+    }
+
+    let result = squared
+        .iter()
+        .map(|&d| if d >= FAR { f32::INFINITY } else { d.sqrt() as f32 })
+        .collect();
+    console_log!("Rust (WASM): Distance transform finished.");
+    Ok(result)
+}
+
+// Stands in for "no black pixel yet"; far above any real squared distance
+// but finite, so the parabola intersections stay well defined
+const FAR: f64 = 1e20;
+
+// Scratch for the 1D transform, reused across lines
+struct Envelope {
+    // Positions of the parabolas in the lower envelope, and the boundaries
+    // between them
+    vertices: Vec<usize>,
+    bounds: Vec<f64>,
+    input: Vec<f64>,
+}
+
+impl Envelope {
+    fn new(len: usize) -> Self {
+        Envelope { vertices: vec![0; len], bounds: vec![0.0; len + 1], input: vec![0.0; len] }
+    }
+
+    // Replaces each f[p] with min over q of f[q] + (p - q)²
+    fn transform(&mut self, f: &mut [f64]) {
+        let n = f.len();
+        let input = &mut self.input[..n];
+        input.copy_from_slice(f);
+        let (v, z) = (&mut self.vertices, &mut self.bounds);
+        let intersect = |q: usize, p: usize| {
+            let (qf, pf) = (q as f64, p as f64);
+            ((input[q] + qf * qf) - (input[p] + pf * pf)) / (2.0 * qf - 2.0 * pf)
+        };
+        let mut k = 0;
+        v[0] = 0;
+        z[0] = f64::NEG_INFINITY;
+        z[1] = f64::INFINITY;
+        for q in 1..n {
+            let mut s = intersect(q, v[k]);
+            while s <= z[k] {
+                k -= 1;
+                s = intersect(q, v[k]);
+            }
+            k += 1;
+            v[k] = q;
+            z[k] = s;
+            z[k + 1] = f64::INFINITY;
+        }
+        k = 0;
+        for (p, out) in f.iter_mut().enumerate() {
+            while z[k + 1] < p as f64 {
+                k += 1;
+            }
+            let d = p as f64 - v[k] as f64;
+            *out = d * d + input[v[k]];
+        }
+    }
+}
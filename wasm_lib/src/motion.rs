@@ -0,0 +1,284 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_image, check_rgba, image_len, Error};
+
+// ========================================================================
+// MOTION DETECTION
+// Stateful video-analysis helpers that keep per-pixel history between
+// frames, so JS only has to hand over each new camera frame.
+// ========================================================================
+
+// Variance a freshly seeded background starts with; large enough that the
+// first few frames don't light up the whole mask while the model settles.
+const INITIAL_VARIANCE: f32 = 15.0 * 15.0;
+// Floor on the per-pixel variance so perfectly static pixels (e.g. a
+// letterbox bar) don't flag tiny sensor noise as motion.
+const MIN_VARIANCE: f32 = 4.0 * 4.0;
+
+// --- Background Subtractor (Running Gaussian Average) ---
+// Each pixel's luminance is modelled as a Gaussian with its own running mean
+// and variance. A pixel is foreground when it's more than `threshold`
+// standard deviations away from its mean. Only background pixels update the
+// model, so a person standing still isn't absorbed straight away.
+#[wasm_bindgen]
+pub struct BackgroundSubtractor {
+    width: u32,
+    height: u32,
+    learning_rate: f32,
+    threshold: f32,
+    mean: Vec<f32>,
+    variance: Vec<f32>,
+    initialized: bool,
+    foreground_pixels: u32,
+}
+
+#[wasm_bindgen]
+impl BackgroundSubtractor {
+    // `learning_rate` is in 0..1 (0.01-0.05 works well for webcams),
+    // `threshold` is in standard deviations (2.5 is a common choice).
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: u32, height: u32, learning_rate: f32, threshold: f32) -> Result<BackgroundSubtractor, JsValue> {
+        let pixel_count = image_len(width, height)? / 4;
+
+        Ok(BackgroundSubtractor {
+            width,
+            height,
+            learning_rate: learning_rate.clamp(0.0, 1.0),
+            threshold: threshold.max(0.0),
+            mean: vec![0.0; pixel_count],
+            variance: vec![INITIAL_VARIANCE; pixel_count],
+            initialized: false,
+            foreground_pixels: 0,
+        })
+    }
+
+    // Feed one RGBA frame and get back an RGBA mask: white where motion was
+    // detected, black elsewhere, fully opaque so it can be drawn directly.
+    pub fn process(&mut self, frame: Vec<u8>) -> Result<Vec<u8>, JsValue> {
+        check_image(&frame, self.width, self.height)?;
+        let mut mask = vec![0u8; self.mean.len() * 4];
+
+        // The first frame seeds the model, so nothing counts as motion yet
+        if !self.initialized {
+            for (mean, pixel) in self.mean.iter_mut().zip(frame.chunks_exact(4)) {
+                *mean = luminance(pixel);
+            }
+            for out in mask.chunks_exact_mut(4) {
+                out[3] = 255;
+            }
+            self.initialized = true;
+            self.foreground_pixels = 0;
+            return Ok(mask);
+        }
+
+        let alpha = self.learning_rate;
+        let threshold_sq = self.threshold * self.threshold;
+        let mut foreground = 0u32;
+
+        for (((mean, variance), pixel), out) in self
+            .mean
+            .iter_mut()
+            .zip(self.variance.iter_mut())
+            .zip(frame.chunks_exact(4))
+            .zip(mask.chunks_exact_mut(4))
+        {
+            let value = luminance(pixel);
+            let diff = value - *mean;
+            let is_foreground = diff * diff > threshold_sq * *variance;
+
+            if is_foreground {
+                foreground += 1;
+                out[0] = 255;
+                out[1] = 255;
+                out[2] = 255;
+            } else {
+                // Blend the new observation into the background model
+                *mean += alpha * diff;
+                *variance = ((1.0 - alpha) * *variance + alpha * diff * diff).max(MIN_VARIANCE);
+            }
+            out[3] = 255;
+        }
+
+        self.foreground_pixels = foreground;
+        Ok(mask)
+    }
+
+    // Percentage (0-100) of pixels flagged as motion in the last frame
+    pub fn motion_percent(&self) -> f32 {
+        if self.mean.is_empty() {
+            return 0.0;
+        }
+        self.foreground_pixels as f32 * 100.0 / self.mean.len() as f32
+    }
+
+    // Forget the learned background; the next frame re-seeds the model
+    pub fn reset(&mut self) {
+        self.mean.iter_mut().for_each(|m| *m = 0.0);
+        self.variance.iter_mut().for_each(|v| *v = INITIAL_VARIANCE);
+        self.initialized = false;
+        self.foreground_pixels = 0;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+// --- Frame Difference ---
+// Holds both outputs of `frame_diff` so JS gets them from a single pass.
+#[wasm_bindgen]
+pub struct FrameDiff {
+    mask: Vec<u8>,
+    heatmap: Vec<u8>,
+    changed_pixels: u32,
+}
+
+#[wasm_bindgen]
+impl FrameDiff {
+    // Binary RGBA mask: white where the pixel changed by more than the threshold
+    #[wasm_bindgen(getter)]
+    pub fn mask(&self) -> Vec<u8> {
+        self.mask.clone()
+    }
+
+    // Difference magnitude mapped through a black-red-yellow-white ramp
+    #[wasm_bindgen(getter)]
+    pub fn heatmap(&self) -> Vec<u8> {
+        self.heatmap.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn changed_pixels(&self) -> u32 {
+        self.changed_pixels
+    }
+}
+
+// Compares two RGBA frames of the same size. A pixel's difference is the
+// largest absolute change across its R, G and B channels, so pure hue
+// changes are caught as well as brightness changes.
+#[wasm_bindgen]
+pub fn frame_diff(frame_a: Vec<u8>, frame_b: Vec<u8>, threshold: u8) -> Result<FrameDiff, JsValue> {
+    check_rgba(&frame_a)?;
+    if frame_a.len() != frame_b.len() {
+        return Err(Error::FrameMismatch {
+            first: frame_a.len(),
+            second: frame_b.len(),
+        }
+        .into());
+    }
+    console_log!("Rust (WASM): Frame diff started...");
+
+    let len = frame_a.len();
+    let mut mask = vec![0u8; len];
+    let mut heatmap = vec![0u8; len];
+    let mut changed_pixels = 0u32;
+
+    for (((a, b), m), h) in frame_a
+        .chunks_exact(4)
+        .zip(frame_b.chunks_exact(4))
+        .zip(mask.chunks_exact_mut(4))
+        .zip(heatmap.chunks_exact_mut(4))
+    {
+        let diff = pixel_diff(a, b);
+
+        if diff > threshold {
+            changed_pixels += 1;
+            m[0] = 255;
+            m[1] = 255;
+            m[2] = 255;
+        }
+        m[3] = 255;
+
+        let [r, g, bl] = heat_color(diff);
+        h[0] = r;
+        h[1] = g;
+        h[2] = bl;
+        h[3] = 255;
+    }
+
+    console_log!("Rust (WASM): Frame diff finished.");
+    Ok(FrameDiff {
+        mask,
+        heatmap,
+        changed_pixels,
+    })
+}
+
+// --- Motion History Image ---
+// `frames` is a sequence of RGBA frames concatenated oldest-first. Every
+// pixel that changed between consecutive frames is stamped at full
+// intensity, and older stamps fade linearly, so the result shows a trail
+// behind moving objects: white-hot where they are now, dark red where they
+// were at the start of the sequence.
+#[wasm_bindgen]
+pub fn motion_history(frames: Vec<u8>, width: u32, height: u32, threshold: u8) -> Result<Vec<u8>, JsValue> {
+    let frame_len = image_len(width, height)?;
+    if !frames.len().is_multiple_of(frame_len) {
+        return Err(Error::PartialFrames {
+            len: frames.len(),
+            frame_len,
+        }
+        .into());
+    }
+    console_log!("Rust (WASM): Motion history started...");
+
+    let mut result = vec![0u8; frame_len];
+
+    let frame_count = frames.len() / frame_len;
+    let mut history = vec![0.0f32; frame_len / 4];
+
+    if frame_count >= 2 {
+        // Spread the decay so a stamp from the first transition has faded
+        // to almost nothing by the last frame
+        let decay = 255.0 / (frame_count - 1) as f32;
+
+        for pair in 1..frame_count {
+            let prev = &frames[(pair - 1) * frame_len..pair * frame_len];
+            let curr = &frames[pair * frame_len..(pair + 1) * frame_len];
+
+            for ((h, a), b) in history.iter_mut().zip(prev.chunks_exact(4)).zip(curr.chunks_exact(4)) {
+                if pixel_diff(a, b) > threshold {
+                    *h = 255.0;
+                } else {
+                    *h = (*h - decay).max(0.0);
+                }
+            }
+        }
+    }
+
+    for (h, out) in history.iter().zip(result.chunks_exact_mut(4)) {
+        let [r, g, b] = heat_color(*h as u8);
+        out[0] = r;
+        out[1] = g;
+        out[2] = b;
+        out[3] = 255;
+    }
+
+    console_log!("Rust (WASM): Motion history finished.");
+    Ok(result)
+}
+
+// Largest absolute per-channel difference between two RGBA pixels
+fn pixel_diff(a: &[u8], b: &[u8]) -> u8 {
+    a[0].abs_diff(b[0]).max(a[1].abs_diff(b[1])).max(a[2].abs_diff(b[2]))
+}
+
+// Maps 0..=255 onto black -> red -> yellow -> white
+fn heat_color(value: u8) -> [u8; 3] {
+    let v = value as u32 * 3;
+    let r = v.min(255) as u8;
+    let g = v.saturating_sub(255).min(255) as u8;
+    let b = v.saturating_sub(510).min(255) as u8;
+    [r, g, b]
+}
+
+// Same integer-weighted luminance the grayscale filter uses
+fn luminance(pixel: &[u8]) -> f32 {
+    (pixel[0] as u32 * 299 + pixel[1] as u32 * 587 + pixel[2] as u32 * 114) as f32 / 1000.0
+}
@@ -0,0 +1,117 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::check_image;
+use crate::text::{blend_pixel, draw_text, measure_text};
+
+// ========================================================================
+// OVERLAYS
+// Debug annotations burned directly into frames.
+// ========================================================================
+
+// Gap between the overlay box and the frame edge, and between the box and
+// the text inside it, in font pixels (multiplied by the text scale)
+const MARGIN: usize = 4;
+const PADDING: usize = 2;
+const TEXT_COLOR: [u8; 4] = [255, 255, 255, 255];
+const BOX_COLOR: [u8; 4] = [0, 0, 0, 160];
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverlayPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+// --- Timecode / Frame Burn-in ---
+// Stamps an optional timestamp (in seconds, rendered as HH:MM:SS.mmm) above
+// `text` in one corner of the frame, on a translucent box so it stays
+// readable over any content. `text` may span several lines, e.g.
+// "FRAME 120\nBLUR R=4". The text scale follows the frame height so the
+// readout stays legible on HD frames.
+#[wasm_bindgen]
+pub fn burn_in_overlay(
+    mut data: Vec<u8>,
+    width: u32,
+    height: u32,
+    text: &str,
+    position: OverlayPosition,
+    timestamp: Option<f64>,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&data, width, height)?;
+    let width = width as usize;
+    let height = height as usize;
+
+    let mut label = String::new();
+    if let Some(seconds) = timestamp {
+        label.push_str(&format_timecode(seconds));
+    }
+    if !text.is_empty() {
+        if !label.is_empty() {
+            label.push('\n');
+        }
+        label.push_str(text);
+    }
+
+    let scale = (height / 240).clamp(1, 4);
+    let (text_w, text_h) = measure_text(&label, scale);
+    if text_w == 0 {
+        return Ok(data);
+    }
+
+    let margin = MARGIN * scale;
+    let padding = PADDING * scale;
+    let box_w = text_w + padding * 2;
+    let box_h = text_h + padding * 2;
+
+    let box_x = match position {
+        OverlayPosition::TopLeft | OverlayPosition::BottomLeft => margin as i32,
+        OverlayPosition::TopRight | OverlayPosition::BottomRight => {
+            width as i32 - (box_w + margin) as i32
+        }
+    };
+    let box_y = match position {
+        OverlayPosition::TopLeft | OverlayPosition::TopRight => margin as i32,
+        OverlayPosition::BottomLeft | OverlayPosition::BottomRight => {
+            height as i32 - (box_h + margin) as i32
+        }
+    };
+
+    // Translucent backing box, clipped to the frame
+    let x0 = box_x.max(0) as usize;
+    let y0 = box_y.max(0) as usize;
+    let x1 = ((box_x + box_w as i32).max(0) as usize).min(width);
+    let y1 = ((box_y + box_h as i32).max(0) as usize).min(height);
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let idx = (y * width + x) * 4;
+            blend_pixel(&mut data[idx..idx + 4], BOX_COLOR);
+        }
+    }
+
+    draw_text(
+        &mut data,
+        width,
+        height,
+        (box_x + padding as i32, box_y + padding as i32),
+        &label,
+        scale,
+        TEXT_COLOR,
+    );
+
+    Ok(data)
+}
+
+fn format_timecode(seconds: f64) -> String {
+    let total_ms = (seconds.max(0.0) * 1000.0).round() as u64;
+    let ms = total_ms % 1000;
+    let total_secs = total_ms / 1000;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        total_secs / 3600,
+        (total_secs / 60) % 60,
+        total_secs % 60,
+        ms
+    )
+}
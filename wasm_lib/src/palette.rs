@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use wasmfx_core::random::Lcg;
+
+use crate::error::{check_range, check_rgba};
+use crate::pixels::pixels;
+use crate::random;
+
+// ========================================================================
+// PALETTE EXTRACTION
+// Finds the few colours that best represent an image, for theming tools
+// and GIF / pixel-art preparation. Palettes come back as 0xRRGGBB values,
+// most prominent first, ready for `quantize_to_palette` or
+// `recolor_to_palette`. Colour distances are squared RGB, the same metric
+// those use to map pixels, so the palette is the one they'll reproduce
+// best. Fully transparent pixels are ignored.
+// ========================================================================
+
+// Larger images are subsampled to about this many pixels, which already
+// pins the dominant colours down to a level or two per channel
+const MAX_PALETTE_SAMPLES: usize = 1 << 16;
+
+// Opaque-enough pixels of `data` as f32 RGB, every n-th one for big images
+fn palette_samples(data: &[u8]) -> Vec<[f32; 3]> {
+    let pixels = pixels(data);
+    let step = pixels.len().div_ceil(MAX_PALETTE_SAMPLES).max(1);
+    pixels
+        .iter()
+        .step_by(step)
+        .filter(|pixel| pixel[3] > 0)
+        .map(|pixel| [pixel[0] as f32, pixel[1] as f32, pixel[2] as f32])
+        .collect()
+}
+
+fn distance_sq(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    (0..3).map(|c| (a[c] - b[c]).powi(2)).sum()
+}
+
+// Rounds the centres and packs them, largest cluster first
+fn pack_palette(mut clusters: Vec<([f32; 3], usize)>) -> Vec<u32> {
+    clusters.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    clusters
+        .iter()
+        .map(|(color, _)| {
+            let [r, g, b] = color.map(|v| v.round().clamp(0.0, 255.0) as u32);
+            (r << 16) | (g << 8) | b
+        })
+        .collect()
+}
+
+// --- K-Means ---
+// The `k` (1 to 256) colours minimising the total squared distance from
+// every pixel to its nearest one, by Lloyd's algorithm from k-means++
+// seeds (drawn from `random::stream_seed`, so the same image always gives
+// the same palette). Stops after `max_iters` rounds (at least 1) or once
+// no pixel changes cluster. Images with fewer distinct colours than `k`
+// give a shorter palette, and a fully transparent one an empty palette.
+#[wasm_bindgen]
+pub fn extract_palette_kmeans(image_data: &[u8], k: u32, max_iters: u32) -> Result<Vec<u32>, JsValue> {
+    check_rgba(image_data)?;
+    check_range("k", k, 1, 256)?;
+    check_range("max_iters", max_iters, 1, 1000)?;
+    console_log!("Rust (WASM): K-means palette started...");
+    let clusters = kmeans(&palette_samples(image_data), k as usize, max_iters);
+    let palette = pack_palette(clusters);
+    console_log!("Rust (WASM): K-means palette finished ({} colours).", palette.len());
+    Ok(palette)
+}
+
+// Lloyd's algorithm; the (centre, member count) of every non-empty cluster
+fn kmeans(samples: &[[f32; 3]], k: usize, max_iters: u32) -> Vec<([f32; 3], usize)> {
+    let mut centres = kmeans_plus_plus(samples, k);
+    let mut assignment = vec![usize::MAX; samples.len()];
+    let mut counts = vec![0usize; centres.len()];
+    for _ in 0..max_iters {
+        let mut changed = false;
+        for (sample, slot) in samples.iter().zip(assignment.iter_mut()) {
+            let nearest = (0..centres.len())
+                .min_by(|&a, &b| distance_sq(sample, &centres[a]).total_cmp(&distance_sq(sample, &centres[b])))
+                .unwrap_or(0);
+            changed |= *slot != nearest;
+            *slot = nearest;
+        }
+
+        let mut sums = vec![[0.0f64; 3]; centres.len()];
+        counts.iter_mut().for_each(|count| *count = 0);
+        for (sample, &cluster) in samples.iter().zip(&assignment) {
+            for c in 0..3 {
+                sums[cluster][c] += sample[c] as f64;
+            }
+            counts[cluster] += 1;
+        }
+        for ((centre, sum), &count) in centres.iter_mut().zip(&sums).zip(&counts) {
+            if count > 0 {
+                *centre = sum.map(|s| (s / count as f64) as f32);
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    centres.into_iter().zip(counts).filter(|&(_, count)| count > 0).collect()
+}
+
+// Seeds spread over the colours: the first at random, each next one with
+// probability proportional to its squared distance from the nearest seed
+// so far. Stops early once every sample coincides with a seed.
+fn kmeans_plus_plus(samples: &[[f32; 3]], k: usize) -> Vec<[f32; 3]> {
+    let mut rng = Lcg::new(random::stream_seed(0x4b6d_3a11));
+    let Some(&first) = samples.get(rng.next_u32() as usize % samples.len().max(1)) else {
+        return Vec::new();
+    };
+    let mut centres = vec![first];
+    let mut nearest: Vec<f32> = samples.iter().map(|sample| distance_sq(sample, &first)).collect();
+    while centres.len() < k {
+        let total: f64 = nearest.iter().map(|&d| d as f64).sum();
+        if total <= 0.0 {
+            break;
+        }
+        let mut target = rng.next_f64() * total;
+        let mut chosen = samples.len() - 1;
+        for (i, &d) in nearest.iter().enumerate() {
+            target -= d as f64;
+            if target < 0.0 {
+                chosen = i;
+                break;
+            }
+        }
+        let centre = samples[chosen];
+        for (d, sample) in nearest.iter_mut().zip(samples) {
+            *d = d.min(distance_sq(sample, &centre));
+        }
+        centres.push(centre);
+    }
+    centres
+}
+
+// --- Median Cut ---
+// `colors` indices into `palette` (0xRRGGBB values), one byte per pixel in
+// row order: the layout GIF and PNG-8 store. Alpha isn't represented.
+#[wasm_bindgen]
+pub struct IndexedImage {
+    palette: Vec<u32>,
+    indices: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl IndexedImage {
+    #[wasm_bindgen(getter)]
+    pub fn palette(&self) -> Vec<u32> {
+        self.palette.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn indices(&self) -> Vec<u8> {
+        self.indices.clone()
+    }
+}
+
+// Heckbert's median cut: starting from one box around every colour,
+// repeatedly splits the box with the widest channel range at the median
+// of that channel, until there are `colors` (1 to 256) boxes or none can
+// be split; each box's mean becomes a palette entry. Faster than k-means
+// and deterministic, though it spends more entries on sparse corners of
+// colour space. Every pixel (transparent ones too) then gets the index of
+// its nearest entry.
+#[wasm_bindgen]
+pub fn quantize_median_cut(image_data: &[u8], colors: u32) -> Result<IndexedImage, JsValue> {
+    check_rgba(image_data)?;
+    check_range("colors", colors, 1, 256)?;
+    console_log!("Rust (WASM): Median cut started...");
+    let mut samples = palette_samples(image_data);
+    if samples.is_empty() {
+        // Nothing visible to choose colours from
+        samples.push([0.0; 3]);
+    }
+
+    // Boxes are (start, end) ranges of `samples`, which each split
+    // reorders in place
+    let mut boxes = vec![(0, samples.len())];
+    while boxes.len() < colors as usize {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .map(|(i, &(start, end))| (i, widest_channel(&samples[start..end])))
+            .max_by(|a, b| a.1 .1.total_cmp(&b.1 .1));
+        let Some((i, (channel, extent))) = widest else {
+            break;
+        };
+        if extent <= 0.0 {
+            break;
+        }
+        let (start, end) = boxes.swap_remove(i);
+        samples[start..end].sort_by(|a, b| a[channel].total_cmp(&b[channel]));
+        let middle = start + (end - start) / 2;
+        boxes.push((start, middle));
+        boxes.push((middle, end));
+    }
+
+    let clusters = boxes
+        .iter()
+        .map(|&(start, end)| {
+            let members = &samples[start..end];
+            let mut sum = [0.0f64; 3];
+            for sample in members {
+                for c in 0..3 {
+                    sum[c] += sample[c] as f64;
+                }
+            }
+            (sum.map(|s| (s / members.len() as f64) as f32), members.len())
+        })
+        .collect();
+    let palette = pack_palette(clusters);
+
+    let entries: Vec<[f32; 3]> = palette
+        .iter()
+        .map(|&rgb| [(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8].map(|v| v as f32))
+        .collect();
+    // Photos repeat colours a lot, so remember each one's index
+    let mut cache = HashMap::new();
+    let indices = pixels(image_data)
+        .iter()
+        .map(|pixel| {
+            *cache.entry([pixel[0], pixel[1], pixel[2]]).or_insert_with(|| {
+                let value = [pixel[0] as f32, pixel[1] as f32, pixel[2] as f32];
+                (0..entries.len())
+                    .min_by(|&a, &b| distance_sq(&value, &entries[a]).total_cmp(&distance_sq(&value, &entries[b])))
+                    .unwrap_or(0) as u8
+            })
+        })
+        .collect();
+    console_log!("Rust (WASM): Median cut finished ({} colours).", palette.len());
+    Ok(IndexedImage { palette, indices })
+}
+
+// The channel with the largest spread in `samples`, and that spread
+fn widest_channel(samples: &[[f32; 3]]) -> (usize, f32) {
+    (0..3)
+        .map(|c| {
+            let (low, high) = samples
+                .iter()
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), s| (low.min(s[c]), high.max(s[c])));
+            (c, high - low)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((0, 0.0))
+}
+
+// --- Color Analysis ---
+// Swatches `analyze_colors` reports, and the k-means rounds it allows
+const ANALYSIS_SWATCHES: usize = 5;
+const ANALYSIS_ITERATIONS: u32 = 16;
+
+#[derive(Serialize)]
+pub(crate) struct ColorAnalysis {
+    // CSS hex colours; black for an image with nothing visible
+    average: String,
+    dominant: String,
+    swatches: Vec<Swatch>,
+}
+
+#[derive(Serialize)]
+struct Swatch {
+    color: String,
+    // Fraction of the visible pixels closest to this swatch
+    share: f32,
+}
+
+// One call for ambient backgrounds and theming:
+//
+//   { average: "#6b5a4e", dominant: "#2f2620",
+//     swatches: [{ color: "#2f2620", share: 0.41 }, ...] }
+//
+// `average` is the alpha-weighted mean colour, and `swatches` the 5-colour
+// k-means palette of `extract_palette_kmeans`, largest share first, whose
+// first entry is `dominant`. Unlike the average, the dominant colour is
+// one that actually appears in the image.
+#[wasm_bindgen]
+pub fn analyze_colors(image_data: &[u8]) -> Result<JsValue, JsValue> {
+    check_rgba(image_data)?;
+    console_log!("Rust (WASM): Color analysis started...");
+    let analysis = color_analysis(image_data);
+    console_log!("Rust (WASM): Color analysis finished.");
+    serde_json::to_string(&analysis)
+        .ok()
+        .and_then(|json| js_sys::JSON::parse(&json).ok())
+        .ok_or_else(|| JsError::new("couldn't build the color analysis").into())
+}
+
+pub(crate) fn color_analysis(image_data: &[u8]) -> ColorAnalysis {
+    let hex = |[r, g, b]: [f32; 3]| {
+        let [r, g, b] = [r, g, b].map(|v| v.round().clamp(0.0, 255.0) as u8);
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    };
+
+    let mut sum = [0u64; 3];
+    let mut alpha_sum = 0u64;
+    for pixel in pixels(image_data) {
+        for c in 0..3 {
+            sum[c] += pixel[c] as u64 * pixel[3] as u64;
+        }
+        alpha_sum += pixel[3] as u64;
+    }
+    let average = if alpha_sum > 0 { sum.map(|s| s as f32 / alpha_sum as f32) } else { [0.0; 3] };
+
+    let samples = palette_samples(image_data);
+    let mut clusters = kmeans(&samples, ANALYSIS_SWATCHES, ANALYSIS_ITERATIONS);
+    clusters.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    let swatches: Vec<Swatch> = clusters
+        .iter()
+        .map(|&(color, count)| Swatch {
+            color: hex(color),
+            share: count as f32 / samples.len() as f32,
+        })
+        .collect();
+    ColorAnalysis {
+        average: hex(average),
+        dominant: swatches.first().map_or_else(|| hex([0.0; 3]), |swatch| swatch.color.clone()),
+        swatches,
+    }
+}
@@ -0,0 +1,153 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use crate::{power, settings};
+
+// ========================================================================
+// THREADING
+// With the `parallel` feature, row-oriented filters split their rows into
+// bands that run on a rayon pool backed by Web Workers and a
+// SharedArrayBuffer (see README for the nightly build command and the
+// cross-origin isolation headers it needs). Without it everything runs on
+// the calling thread.
+//
+// Jobs are `Interactive` (live previews, video frames) or `Background`
+// (batch exports). `reserve_workers(n)` keeps n workers out of reach of
+// background jobs: all background bands running at once, across every
+// thread that calls filters, share the pool's other workers, so a batch
+// export in another Web Worker can't occupy the whole pool while the
+// preview waits for a free thread.
+// ========================================================================
+
+#[cfg(feature = "parallel")]
+static POOL_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+// Start `num_workers` Web Workers; await the returned promise before
+// calling any filter. The pool can only be created once per page.
+#[cfg(feature = "parallel")]
+#[wasm_bindgen]
+pub fn init_thread_pool(num_workers: usize) -> js_sys::Promise {
+    let workers = num_workers.max(1);
+    POOL_SIZE.store(workers, Ordering::Relaxed);
+    settings::set_thread_count(workers as u32);
+    wasm_bindgen_rayon::init_thread_pool(workers)
+}
+
+// Number of workers in the pool (0 until `init_thread_pool` has run; always
+// 0 in builds without the `parallel` feature)
+pub(crate) fn pool_size() -> usize {
+    #[cfg(feature = "parallel")]
+    return POOL_SIZE.load(Ordering::Relaxed);
+    #[cfg(not(feature = "parallel"))]
+    0
+}
+
+// --- Priorities ---
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobPriority {
+    Interactive,
+    Background,
+}
+
+static RESERVED_WORKERS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static PRIORITY: Cell<JobPriority> = const { Cell::new(JobPriority::Interactive) };
+}
+
+// Priority of the filters called from the current thread (the page's main
+// thread, or the Web Worker that calls them); `Interactive` by default.
+// Each thread keeps its own, so a worker running a batch export sets
+// `Background` once and the main thread's previews are unaffected.
+#[wasm_bindgen]
+pub fn set_job_priority(priority: JobPriority) {
+    PRIORITY.with(|current| current.set(priority));
+}
+
+#[wasm_bindgen]
+pub fn get_job_priority() -> JobPriority {
+    PRIORITY.with(Cell::get)
+}
+
+// Keep `count` pool workers for interactive jobs. Background jobs split
+// their rows across the rest, shared between all of them, and run on the
+// calling thread alone when none are free; interactive jobs may still use
+// the whole pool. Takes effect from the next filter call.
+#[wasm_bindgen]
+pub fn reserve_workers(count: usize) {
+    RESERVED_WORKERS.store(count, Ordering::Relaxed);
+}
+
+#[wasm_bindgen]
+pub fn reserved_workers() -> usize {
+    RESERVED_WORKERS.load(Ordering::Relaxed)
+}
+
+// Workers background jobs may occupy between them, after any power-saving
+// cap from `set_power_state`
+fn background_workers() -> usize {
+    power::thread_cap(pool_size()).saturating_sub(reserved_workers())
+}
+
+// Pool workers running background bands right now
+static BACKGROUND_BANDS: AtomicUsize = AtomicUsize::new(0);
+
+// Up to `wanted` of the background workers no other background job
+// holds, returned to the pool when dropped
+struct BackgroundSlots(usize);
+
+impl BackgroundSlots {
+    fn take(wanted: usize) -> BackgroundSlots {
+        let mut taken = 0;
+        let _ = BACKGROUND_BANDS.fetch_update(Ordering::AcqRel, Ordering::Acquire, |in_use| {
+            taken = wanted.min(background_workers().saturating_sub(in_use));
+            Some(in_use + taken)
+        });
+        BackgroundSlots(taken)
+    }
+}
+
+impl Drop for BackgroundSlots {
+    fn drop(&mut self) {
+        BACKGROUND_BANDS.fetch_sub(self.0, Ordering::AcqRel);
+    }
+}
+
+// Calls `f(first_row, band)` over consecutive bands of whole rows that
+// together cover `data`. Uses one band per configured thread when a pool is
+// running, and otherwise a single band on the current thread. Background
+// jobs get only as many bands as there are background workers free.
+pub(crate) fn for_each_row_band<F>(data: &mut [u8], row_bytes: usize, f: F)
+where
+    F: Fn(usize, &mut [u8]) + Send + Sync,
+{
+    if row_bytes == 0 {
+        return;
+    }
+
+    let rows = data.len() / row_bytes;
+    let wanted = (settings::thread_count() as usize).min(power::thread_cap(pool_size())).min(rows);
+    // Held until the bands have finished
+    let slots = match get_job_priority() {
+        JobPriority::Background if wanted > 1 => Some(BackgroundSlots::take(wanted)),
+        _ => None,
+    };
+    let bands = slots.as_ref().map_or(wanted, |slots| slots.0);
+    if bands > 1 {
+        #[cfg(feature = "parallel")]
+        {
+            let rows_per_band = rows.div_ceil(bands);
+            data.par_chunks_mut(rows_per_band * row_bytes)
+                .enumerate()
+                .for_each(|(i, band)| f(i * rows_per_band, band));
+            return;
+        }
+    }
+
+    f(0, data);
+}
@@ -0,0 +1,328 @@
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+use crate::api::BlurMethod;
+use crate::denoise::{bilateral_pixels, median_pixels, MAX_MEDIAN_RADIUS};
+use crate::error::{check_image, check_positive, check_radius, check_range, check_rgba};
+use crate::memory::{bytes_per_pixel, check_memory};
+use crate::{
+    blur_pixels, box_blur_pixels, edge_detection_pixels, linear_blur_pixels, sharpen_linear, sharpen_pixels, stack_blur_pixels,
+    MAX_SHARPEN_STRENGTH,
+};
+
+// ========================================================================
+// FUSED FILTER PIPELINE
+// Per-pixel filters chained through the individual exports each walk the
+// whole buffer. A pipeline instead records the steps and applies all of
+// them to each pixel in a single pass:
+//
+//   const out = new FilterPipeline()
+//     .add_grayscale()
+//     .add_invert()
+//     .add_brightness(20)
+//     .run(imageData.data);
+//
+// The `add_*` calls consume the pipeline and return it, so build it in one
+// expression (or reassign the variable after each call).
+// ========================================================================
+
+#[derive(Clone, Copy, Debug)]
+enum PixelOp {
+    Grayscale,
+    Invert,
+    Brightness(i32),
+}
+
+impl PixelOp {
+    // Effect on one color channel, for the steps that treat channels
+    // independently
+    fn apply_channel(self, v: u8) -> u8 {
+        match self {
+            PixelOp::Grayscale => v,
+            PixelOp::Invert => 255 - v,
+            PixelOp::Brightness(amount) => (v as i32 + amount).clamp(0, 255) as u8,
+        }
+    }
+}
+
+// What `run` executes: runs of channel-independent steps are folded into
+// one lookup table, so only grayscale (which mixes channels) stays a
+// separate step
+enum FusedStage {
+    Lut(Box<[u8; 256]>),
+    Grayscale,
+}
+
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct FilterPipeline {
+    ops: Vec<PixelOp>,
+}
+
+#[wasm_bindgen]
+impl FilterPipeline {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> FilterPipeline {
+        FilterPipeline::default()
+    }
+
+    pub fn add_grayscale(mut self) -> FilterPipeline {
+        self.ops.push(PixelOp::Grayscale);
+        self
+    }
+
+    pub fn add_invert(mut self) -> FilterPipeline {
+        self.ops.push(PixelOp::Invert);
+        self
+    }
+
+    // Adds `amount` (negative darkens) to R, G and B, clamping to 0..=255
+    pub fn add_brightness(mut self, amount: i32) -> FilterPipeline {
+        self.ops.push(PixelOp::Brightness(amount));
+        self
+    }
+
+    // Number of recorded steps
+    #[wasm_bindgen(getter)]
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    // Apply every step to an RGBA buffer in one pass; alpha is untouched
+    pub fn run(&self, mut image_data: Vec<u8>) -> Result<Vec<u8>, JsValue> {
+        check_rgba(&image_data)?;
+        console_log!("Rust (WASM): Filter pipeline ({} steps) started...", self.ops.len());
+        self.run_pixels(&mut image_data);
+        console_log!("Rust (WASM): Filter pipeline finished.");
+        Ok(image_data)
+    }
+}
+
+impl FilterPipeline {
+    pub(crate) fn run_pixels(&self, image_data: &mut [u8]) {
+        let stages = self.fuse();
+        match stages.as_slice() {
+            [] => {}
+            // A single table is the common case (brightness/invert chains),
+            // so skip the per-pixel stage loop for it
+            [FusedStage::Lut(lut)] => {
+                for pixel in image_data.chunks_exact_mut(4) {
+                    pixel[0] = lut[pixel[0] as usize];
+                    pixel[1] = lut[pixel[1] as usize];
+                    pixel[2] = lut[pixel[2] as usize];
+                }
+            }
+            _ => {
+                for pixel in image_data.chunks_exact_mut(4) {
+                    let mut rgb = [pixel[0], pixel[1], pixel[2]];
+                    for stage in &stages {
+                        match stage {
+                            FusedStage::Lut(lut) => {
+                                for c in rgb.iter_mut() {
+                                    *c = lut[*c as usize];
+                                }
+                            }
+                            // Same integer luminance as `apply_grayscale`
+                            FusedStage::Grayscale => {
+                                let gray = ((rgb[0] as u32 * 299 + rgb[1] as u32 * 587 + rgb[2] as u32 * 114) / 1000) as u8;
+                                rgb = [gray; 3];
+                            }
+                        }
+                    }
+                    pixel[..3].copy_from_slice(&rgb);
+                }
+            }
+        }
+    }
+
+    fn fuse(&self) -> Vec<FusedStage> {
+        let mut stages = Vec::new();
+        let mut lut: Option<Box<[u8; 256]>> = None;
+
+        for &op in &self.ops {
+            if let PixelOp::Grayscale = op {
+                // Two grayscales in a row are the same as one
+                if lut.is_none() && matches!(stages.last(), Some(FusedStage::Grayscale)) {
+                    continue;
+                }
+                if let Some(table) = lut.take() {
+                    stages.push(FusedStage::Lut(table));
+                }
+                stages.push(FusedStage::Grayscale);
+                continue;
+            }
+
+            let table = lut.get_or_insert_with(|| {
+                let mut identity = Box::new([0u8; 256]);
+                for (i, v) in identity.iter_mut().enumerate() {
+                    *v = i as u8;
+                }
+                identity
+            });
+            for v in table.iter_mut() {
+                *v = op.apply_channel(*v);
+            }
+        }
+
+        if let Some(table) = lut {
+            stages.push(FusedStage::Lut(table));
+        }
+        stages
+    }
+}
+
+// ========================================================================
+// JSON PIPELINES
+// Front-ends can describe a chain declaratively instead of needing an
+// export per combination:
+//
+//   apply_pipeline(data, width, height,
+//     '[{"op":"blur","radius":4},{"op":"grayscale"},{"op":"brightness","amount":20}]');
+//
+// Consecutive per-pixel steps (grayscale, invert, brightness) are fused
+// into one pass through `FilterPipeline`. The blurs and "sharpen" take an
+// optional `"linear_light": true`, as their option objects do.
+// ========================================================================
+
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+enum SpecStep {
+    Grayscale,
+    Invert,
+    Brightness { amount: i32 },
+    Blur {
+        radius: u32,
+        #[serde(default)]
+        linear_light: bool,
+    },
+    BoxBlur {
+        radius: u32,
+        #[serde(default)]
+        linear_light: bool,
+    },
+    StackBlur {
+        radius: u32,
+        #[serde(default)]
+        linear_light: bool,
+    },
+    Median { radius: u32 },
+    Bilateral { spatial_sigma: f32, range_sigma: f32 },
+    EdgeDetection,
+    Sharpen {
+        strength: u32,
+        #[serde(default)]
+        linear_light: bool,
+    },
+}
+
+// --- Apply JSON Pipeline ---
+// Throws with serde's message (which includes the position) if the spec
+// doesn't parse
+#[wasm_bindgen]
+pub fn apply_pipeline(mut image_data: Vec<u8>, width: u32, height: u32, spec_json: &str) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    let steps: Vec<SpecStep> = serde_json::from_str(spec_json)
+        .map_err(|err| JsValue::from(JsError::new(&format!("invalid pipeline spec: {}", err))))?;
+    // Same bounds as the standalone exports, and the memory check for the
+    // hungriest step, all before anything runs
+    let mut heaviest = None;
+    for step in &steps {
+        match *step {
+            SpecStep::Blur { radius, .. } | SpecStep::BoxBlur { radius, .. } | SpecStep::StackBlur { radius, .. } => {
+                check_radius(radius)?
+            }
+            SpecStep::Median { radius } => check_range("radius", radius, 1, MAX_MEDIAN_RADIUS)?,
+            SpecStep::Bilateral { spatial_sigma, range_sigma } => {
+                check_positive("spatial_sigma", spatial_sigma)?;
+                check_positive("range_sigma", range_sigma)?;
+            }
+            SpecStep::Sharpen { strength, .. } => check_range("strength", strength, 0, MAX_SHARPEN_STRENGTH)?,
+            _ => {}
+        }
+        let name = match *step {
+            SpecStep::Blur { linear_light: true, .. }
+            | SpecStep::BoxBlur { linear_light: true, .. }
+            | SpecStep::StackBlur { linear_light: true, .. }
+            | SpecStep::Sharpen { linear_light: true, .. } => "linear_convolution",
+            SpecStep::Blur { .. } => "blur",
+            SpecStep::BoxBlur { .. } => "box_blur",
+            SpecStep::StackBlur { .. } => "stack_blur",
+            SpecStep::Median { .. } => "median",
+            SpecStep::Bilateral { .. } => "bilateral",
+            SpecStep::EdgeDetection => "edge_detection",
+            SpecStep::Sharpen { .. } => "sharpen",
+            SpecStep::Grayscale | SpecStep::Invert | SpecStep::Brightness { .. } => "grayscale",
+        };
+        if heaviest.is_none_or(|current| bytes_per_pixel(name) > bytes_per_pixel(current)) {
+            heaviest = Some(name);
+        }
+    }
+    if let Some(name) = heaviest {
+        check_memory(name, width, height)?;
+    }
+    console_log!("Rust (WASM): JSON pipeline ({} steps) started...", steps.len());
+
+    // A neighborhood filter ends the current run of fused steps
+    let mut fused = FilterPipeline::new();
+    for step in steps {
+        match step {
+            SpecStep::Grayscale => fused = fused.add_grayscale(),
+            SpecStep::Invert => fused = fused.add_invert(),
+            SpecStep::Brightness { amount } => fused = fused.add_brightness(amount),
+            SpecStep::Blur { radius, linear_light: false } => {
+                std::mem::take(&mut fused).run_pixels(&mut image_data);
+                blur_pixels(&mut image_data, width, height, radius);
+            }
+            SpecStep::BoxBlur { radius, linear_light: false } => {
+                std::mem::take(&mut fused).run_pixels(&mut image_data);
+                box_blur_pixels(&mut image_data, width as usize, height as usize, radius, None);
+            }
+            SpecStep::StackBlur { radius, linear_light: false } => {
+                std::mem::take(&mut fused).run_pixels(&mut image_data);
+                stack_blur_pixels(&mut image_data, width as usize, height as usize, radius, None);
+            }
+            SpecStep::Blur { radius, linear_light: true }
+            | SpecStep::BoxBlur { radius, linear_light: true }
+            | SpecStep::StackBlur { radius, linear_light: true } => {
+                let method = match step {
+                    SpecStep::BoxBlur { .. } => BlurMethod::Box,
+                    SpecStep::StackBlur { .. } => BlurMethod::Stack,
+                    _ => BlurMethod::Gaussian,
+                };
+                std::mem::take(&mut fused).run_pixels(&mut image_data);
+                let (w, h) = (width as usize, height as usize);
+                image_data = linear_blur_pixels(&image_data, w, h, method, radius, None).unwrap_or(image_data);
+            }
+            SpecStep::Median { radius } => {
+                std::mem::take(&mut fused).run_pixels(&mut image_data);
+                image_data = median_pixels(&image_data, width as usize, height as usize, radius as usize, None);
+            }
+            SpecStep::Bilateral { spatial_sigma, range_sigma } => {
+                std::mem::take(&mut fused).run_pixels(&mut image_data);
+                image_data =
+                    bilateral_pixels(&image_data, width as usize, height as usize, spatial_sigma, range_sigma, None);
+            }
+            SpecStep::EdgeDetection => {
+                std::mem::take(&mut fused).run_pixels(&mut image_data);
+                image_data = edge_detection_pixels(&image_data, width, height);
+            }
+            SpecStep::Sharpen { strength, linear_light } => {
+                std::mem::take(&mut fused).run_pixels(&mut image_data);
+                image_data = if linear_light {
+                    sharpen_linear(&image_data, width as usize, height as usize, strength)
+                } else {
+                    sharpen_pixels(&image_data, width, height, strength)
+                };
+            }
+        }
+    }
+    fused.run_pixels(&mut image_data);
+
+    console_log!("Rust (WASM): JSON pipeline finished.");
+    Ok(image_data)
+}
@@ -0,0 +1,53 @@
+use std::iter::Enumerate;
+use std::slice::ChunksExactMut;
+
+use crate::parallel;
+
+// ========================================================================
+// PIXEL ITERATION
+// Typed views over RGBA buffers for writing filters without hand-rolled
+// `(y * width + x) * 4 + c` arithmetic:
+//
+//   for (y, row) in rows_mut(&mut image_data, width) {
+//       for pixel in row.iter_mut() {
+//           pixel[0] = 255 - pixel[0];
+//       }
+//   }
+//
+// `par_rows_mut` does the same across the worker bands when a thread pool
+// is running. Trailing bytes that don't make up a whole pixel (or a whole
+// row) are left out, so indexing within a row can't run past the image.
+// With the `rust-api` feature this module is public for crates that use
+// wasm_lib as a Rust library.
+// ========================================================================
+
+pub type Pixel = [u8; 4];
+
+// The buffer as pixels
+pub fn pixels(data: &[u8]) -> &[Pixel] {
+    data.as_chunks::<4>().0
+}
+
+pub fn pixels_mut(data: &mut [u8]) -> &mut [Pixel] {
+    data.as_chunks_mut::<4>().0
+}
+
+// `(y, row)` for every whole row of a `width`-pixel-wide image
+pub fn rows_mut(data: &mut [u8], width: usize) -> Enumerate<ChunksExactMut<'_, Pixel>> {
+    let pixels = pixels_mut(data);
+    let len = if width == 0 { 0 } else { pixels.len() };
+    pixels[..len].chunks_exact_mut(width.max(1)).enumerate()
+}
+
+// Calls `f(y, row)` for every row, splitting the rows into bands that run
+// in parallel when a pool is running; `f` can't rely on the row order
+pub fn par_rows_mut<F>(data: &mut [u8], width: usize, f: F)
+where
+    F: Fn(usize, &mut [Pixel]) + Send + Sync,
+{
+    parallel::for_each_row_band(data, width * 4, |first_row, band| {
+        for (y, row) in rows_mut(band, width) {
+            f(first_row + y, row);
+        }
+    });
+}
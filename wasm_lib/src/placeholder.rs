@@ -0,0 +1,631 @@
+use std::f64::consts::PI;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_image, image_len};
+use crate::memory::check_memory;
+use crate::thumbnail::resample_area;
+
+// ========================================================================
+// PLACEHOLDERS
+// Compact encodings of an image's rough look, for showing something
+// before the real image has loaded. Both formats follow their reference
+// implementations, so hashes interoperate with other encoders/decoders:
+//   blurhash  - https://blurha.sh (base83 string, no alpha)
+//   thumbhash - https://evanw.github.io/thumbhash (bytes, keeps alpha and
+//               aspect ratio)
+// ========================================================================
+
+const BASE83: &[u8; 83] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+// ThumbHash is defined on images up to this size; larger ones are
+// area-averaged down first, which doesn't change the result noticeably
+const THUMBHASH_MAX_SIDE: usize = 100;
+
+// ThumbHash decodes to this many pixels along the longer side
+const THUMBHASH_DECODE_SIDE: f64 = 32.0;
+
+fn invalid(message: &str) -> JsValue {
+    JsError::new(message).into()
+}
+
+fn srgb_to_linear(value: u8) -> f64 {
+    let v = value as f64 / 255.0;
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f64) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    if v <= 0.0031308 {
+        (v * 12.92 * 255.0 + 0.5) as u8
+    } else {
+        ((1.055 * v.powf(1.0 / 2.4) - 0.055) * 255.0 + 0.5) as u8
+    }
+}
+
+fn sign_pow(value: f64, exponent: f64) -> f64 {
+    value.abs().powf(exponent).copysign(value)
+}
+
+fn encode_base83(value: u32, digits: u32, out: &mut String) {
+    for i in 1..=digits {
+        let digit = (value / 83u32.pow(digits - i)) % 83;
+        out.push(BASE83[digit as usize] as char);
+    }
+}
+
+fn decode_base83(text: &str) -> Option<u32> {
+    text.bytes().try_fold(0u32, |value, c| {
+        let digit = BASE83.iter().position(|&b| b == c)? as u32;
+        Some(value * 83 + digit)
+    })
+}
+
+// --- Blurhash Encode ---
+// `cx` x `cy` components (each clamped to 1..=9); 4x3 is the usual choice.
+// The cosine sums are separated per row, so large images stay fast.
+#[wasm_bindgen]
+pub fn encode_blurhash(data: &[u8], width: u32, height: u32, cx: u32, cy: u32) -> Result<String, JsValue> {
+    check_image(data, width, height)?;
+    let (w, h) = (width as usize, height as usize);
+    console_log!("Rust (WASM): Blurhash encode started...");
+
+    let nx = cx.clamp(1, 9) as usize;
+    let ny = cy.clamp(1, 9) as usize;
+
+    let cos_x: Vec<f64> = (0..nx)
+        .flat_map(|i| (0..w).map(move |x| (PI * i as f64 * x as f64 / w as f64).cos()))
+        .collect();
+    let linear: Vec<f64> = (0..=255u8).map(srgb_to_linear).collect();
+
+    let mut factors = vec![[0.0f64; 3]; nx * ny];
+    let mut row_sums = vec![[0.0f64; 3]; nx];
+    for y in 0..h {
+        let row = &data[y * w * 4..(y + 1) * w * 4];
+        for (i, sum) in row_sums.iter_mut().enumerate() {
+            *sum = [0.0; 3];
+            for (pixel, &basis) in row.chunks_exact(4).zip(&cos_x[i * w..(i + 1) * w]) {
+                for c in 0..3 {
+                    sum[c] += basis * linear[pixel[c] as usize];
+                }
+            }
+        }
+        for j in 0..ny {
+            let basis_y = (PI * j as f64 * y as f64 / h as f64).cos();
+            for i in 0..nx {
+                let factor = &mut factors[j * nx + i];
+                for c in 0..3 {
+                    factor[c] += basis_y * row_sums[i][c];
+                }
+            }
+        }
+    }
+    for (k, factor) in factors.iter_mut().enumerate() {
+        let normalisation = if k == 0 { 1.0 } else { 2.0 };
+        for value in factor.iter_mut() {
+            *value *= normalisation / (w * h) as f64;
+        }
+    }
+
+    let mut hash = String::with_capacity(4 + 2 * nx * ny);
+    encode_base83(((nx - 1) + (ny - 1) * 9) as u32, 1, &mut hash);
+
+    let dc = factors[0];
+    let ac = &factors[1..];
+    let maximum_value = if ac.is_empty() {
+        encode_base83(0, 1, &mut hash);
+        1.0
+    } else {
+        let actual_max = ac.iter().flatten().fold(0.0f64, |m, v| m.max(v.abs()));
+        let quantised_max = (actual_max * 166.0 - 0.5).floor().clamp(0.0, 82.0) as u32;
+        encode_base83(quantised_max, 1, &mut hash);
+        (quantised_max + 1) as f64 / 166.0
+    };
+
+    let dc_value = ((linear_to_srgb(dc[0]) as u32) << 16) | ((linear_to_srgb(dc[1]) as u32) << 8) | linear_to_srgb(dc[2]) as u32;
+    encode_base83(dc_value, 4, &mut hash);
+
+    for factor in ac {
+        let quant = |v: f64| (sign_pow(v / maximum_value, 0.5) * 9.0 + 9.5).floor().clamp(0.0, 18.0) as u32;
+        encode_base83(quant(factor[0]) * 19 * 19 + quant(factor[1]) * 19 + quant(factor[2]), 2, &mut hash);
+    }
+
+    console_log!("Rust (WASM): Blurhash encode finished.");
+    Ok(hash)
+}
+
+// --- Blurhash Decode ---
+// Renders the hash as an opaque `out_w` x `out_h` RGBA image
+#[wasm_bindgen]
+pub fn decode_blurhash(hash: &str, out_w: u32, out_h: u32) -> Result<Vec<u8>, JsValue> {
+    let len = image_len(out_w, out_h)?;
+    check_memory("decode_blurhash", out_w, out_h)?;
+    if hash.len() < 6 || !hash.is_ascii() {
+        return Err(invalid("blurhash must be at least 6 base83 characters"));
+    }
+    let bad_char = || invalid("blurhash contains a character outside base83");
+
+    let size_flag = decode_base83(&hash[0..1]).ok_or_else(bad_char)? as usize;
+    let nx = size_flag % 9 + 1;
+    let ny = size_flag / 9 + 1;
+    if hash.len() != 4 + 2 * nx * ny {
+        return Err(invalid("blurhash length doesn't match its component count"));
+    }
+
+    let quantised_max = decode_base83(&hash[1..2]).ok_or_else(bad_char)?;
+    let maximum_value = (quantised_max + 1) as f64 / 166.0;
+
+    let mut colors = Vec::with_capacity(nx * ny);
+    let dc = decode_base83(&hash[2..6]).ok_or_else(bad_char)?;
+    colors.push([
+        srgb_to_linear((dc >> 16) as u8),
+        srgb_to_linear((dc >> 8) as u8),
+        srgb_to_linear(dc as u8),
+    ]);
+    for k in 1..nx * ny {
+        let value = decode_base83(&hash[4 + k * 2..6 + k * 2]).ok_or_else(bad_char)?;
+        let unquant = |q: u32| sign_pow((q as f64 - 9.0) / 9.0, 2.0) * maximum_value;
+        colors.push([unquant(value / 361), unquant((value / 19) % 19), unquant(value % 19)]);
+    }
+
+    let (w, h) = (out_w as usize, out_h as usize);
+    let cos_x: Vec<f64> = (0..w)
+        .flat_map(|x| (0..nx).map(move |i| (PI * x as f64 * i as f64 / w as f64).cos()))
+        .collect();
+    let mut result = vec![0u8; len];
+    for y in 0..h {
+        let cos_y: Vec<f64> = (0..ny).map(|j| (PI * y as f64 * j as f64 / h as f64).cos()).collect();
+        for x in 0..w {
+            let mut rgb = [0.0f64; 3];
+            for (j, basis_y) in cos_y.iter().enumerate() {
+                for i in 0..nx {
+                    let basis = cos_x[x * nx + i] * basis_y;
+                    let color = colors[j * nx + i];
+                    for c in 0..3 {
+                        rgb[c] += color[c] * basis;
+                    }
+                }
+            }
+            let idx = (y * w + x) * 4;
+            result[idx] = linear_to_srgb(rgb[0]);
+            result[idx + 1] = linear_to_srgb(rgb[1]);
+            result[idx + 2] = linear_to_srgb(rgb[2]);
+            result[idx + 3] = 255;
+        }
+    }
+
+    Ok(result)
+}
+
+// A decoded ThumbHash: the image size follows the encoded aspect ratio
+#[wasm_bindgen]
+pub struct ThumbHashImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl ThumbHashImage {
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn pixels(&self) -> Vec<u8> {
+        self.pixels.clone()
+    }
+}
+
+// DCT of one channel into its DC term and AC terms normalised to 0..1,
+// plus the scale needed to undo that
+fn thumbhash_encode_channel(channel: &[f64], w: usize, h: usize, nx: usize, ny: usize) -> (f64, Vec<f64>, f64) {
+    let mut dc = 0.0;
+    let mut ac = Vec::new();
+    let mut scale = 0.0f64;
+    let mut fx = vec![0.0; w];
+    for cy in 0..ny {
+        let mut cx = 0;
+        while cx * ny < nx * (ny - cy) {
+            for (x, f) in fx.iter_mut().enumerate() {
+                *f = (PI / w as f64 * cx as f64 * (x as f64 + 0.5)).cos();
+            }
+            let mut f = 0.0;
+            for y in 0..h {
+                let fy = (PI / h as f64 * cy as f64 * (y as f64 + 0.5)).cos();
+                for x in 0..w {
+                    f += channel[x + y * w] * fx[x] * fy;
+                }
+            }
+            f /= (w * h) as f64;
+            if cx > 0 || cy > 0 {
+                ac.push(f);
+                scale = scale.max(f.abs());
+            } else {
+                dc = f;
+            }
+            cx += 1;
+        }
+    }
+    if scale > 0.0 {
+        for value in ac.iter_mut() {
+            *value = 0.5 + 0.5 / scale * *value;
+        }
+    }
+    (dc, ac, scale)
+}
+
+// --- ThumbHash Encode ---
+#[wasm_bindgen]
+pub fn encode_thumbhash(data: &[u8], width: u32, height: u32) -> Result<Vec<u8>, JsValue> {
+    check_image(data, width, height)?;
+    let (mut w, mut h) = (width as usize, height as usize);
+    console_log!("Rust (WASM): ThumbHash encode started...");
+
+    let resized;
+    let rgba = if w > THUMBHASH_MAX_SIDE || h > THUMBHASH_MAX_SIDE {
+        let scale = THUMBHASH_MAX_SIDE as f64 / w.max(h) as f64;
+        let (small_w, small_h) = (
+            ((w as f64 * scale).round() as usize).max(1),
+            ((h as f64 * scale).round() as usize).max(1),
+        );
+        resized = resample_area(data, w, h, (0, 0, w, h), small_w, small_h);
+        (w, h) = (small_w, small_h);
+        &resized[..]
+    } else {
+        data
+    };
+
+    // Average color, weighted by alpha
+    let (mut avg_r, mut avg_g, mut avg_b, mut avg_a) = (0.0, 0.0, 0.0, 0.0);
+    for pixel in rgba.chunks_exact(4) {
+        let alpha = pixel[3] as f64 / 255.0;
+        avg_r += alpha / 255.0 * pixel[0] as f64;
+        avg_g += alpha / 255.0 * pixel[1] as f64;
+        avg_b += alpha / 255.0 * pixel[2] as f64;
+        avg_a += alpha;
+    }
+    if avg_a > 0.0 {
+        avg_r /= avg_a;
+        avg_g /= avg_a;
+        avg_b /= avg_a;
+    }
+
+    let has_alpha = avg_a < (w * h) as f64;
+    // Fewer luminance bits when alpha needs room
+    let l_limit = if has_alpha { 5.0 } else { 7.0 };
+    let longest = w.max(h) as f64;
+    let lx = ((l_limit * w as f64 / longest).round() as usize).max(1);
+    let ly = ((l_limit * h as f64 / longest).round() as usize).max(1);
+
+    // RGBA to LPQA, composited over the average color
+    let mut l = Vec::with_capacity(w * h);
+    let mut p = Vec::with_capacity(w * h);
+    let mut q = Vec::with_capacity(w * h);
+    let mut a = Vec::with_capacity(w * h);
+    for pixel in rgba.chunks_exact(4) {
+        let alpha = pixel[3] as f64 / 255.0;
+        let r = avg_r * (1.0 - alpha) + alpha / 255.0 * pixel[0] as f64;
+        let g = avg_g * (1.0 - alpha) + alpha / 255.0 * pixel[1] as f64;
+        let b = avg_b * (1.0 - alpha) + alpha / 255.0 * pixel[2] as f64;
+        l.push((r + g + b) / 3.0);
+        p.push((r + g) / 2.0 - b);
+        q.push(r - g);
+        a.push(alpha);
+    }
+
+    let (l_dc, l_ac, l_scale) = thumbhash_encode_channel(&l, w, h, lx.max(3), ly.max(3));
+    let (p_dc, p_ac, p_scale) = thumbhash_encode_channel(&p, w, h, 3, 3);
+    let (q_dc, q_ac, q_scale) = thumbhash_encode_channel(&q, w, h, 3, 3);
+    let alpha_channel = has_alpha.then(|| thumbhash_encode_channel(&a, w, h, 5, 5));
+
+    let is_landscape = w > h;
+    let header24 = (63.0 * l_dc).round() as u32
+        | ((31.5 + 31.5 * p_dc).round() as u32) << 6
+        | ((31.5 + 31.5 * q_dc).round() as u32) << 12
+        | ((31.0 * l_scale).round() as u32) << 18
+        | (has_alpha as u32) << 23;
+    let header16 = (if is_landscape { ly } else { lx }) as u32
+        | ((63.0 * p_scale).round() as u32) << 3
+        | ((63.0 * q_scale).round() as u32) << 9
+        | (is_landscape as u32) << 15;
+    let mut hash = vec![
+        (header24 & 255) as u8,
+        ((header24 >> 8) & 255) as u8,
+        (header24 >> 16) as u8,
+        (header16 & 255) as u8,
+        (header16 >> 8) as u8,
+    ];
+    if let Some((a_dc, _, a_scale)) = &alpha_channel {
+        hash.push((15.0 * a_dc).round() as u8 | ((15.0 * a_scale).round() as u8) << 4);
+    }
+
+    // AC terms packed as nibbles, low nibble first
+    let mut channels = vec![l_ac, p_ac, q_ac];
+    if let Some((_, a_ac, _)) = alpha_channel {
+        channels.push(a_ac);
+    }
+    let ac_start = hash.len();
+    for (index, f) in channels.iter().flatten().enumerate() {
+        if index % 2 == 0 {
+            hash.push(0);
+        }
+        hash[ac_start + index / 2] |= ((15.0 * f).round() as u8) << ((index & 1) << 2);
+    }
+
+    console_log!("Rust (WASM): ThumbHash encode finished.");
+    Ok(hash)
+}
+
+// Width / height of the image a ThumbHash was made from
+#[wasm_bindgen]
+pub fn thumbhash_aspect_ratio(hash: &[u8]) -> Result<f32, JsValue> {
+    if hash.len() < 5 {
+        return Err(invalid("thumbhash must be at least 5 bytes"));
+    }
+    let (lx, ly) = thumbhash_luma_size(hash);
+    Ok(lx as f32 / ly as f32)
+}
+
+// Luminance component counts as stored (before the minimum of 3 applies)
+fn thumbhash_luma_size(hash: &[u8]) -> (usize, usize) {
+    let stored = (hash[3] & 7) as usize;
+    let has_alpha = hash[2] & 0x80 != 0;
+    let is_landscape = hash[4] & 0x80 != 0;
+    let long = if has_alpha { 5 } else { 7 };
+    if is_landscape {
+        (long, stored)
+    } else {
+        (stored, long)
+    }
+}
+
+// One channel's AC terms evaluated at a pixel, given the cosines along x
+// and y. Terms are laid out row by row over the triangle
+// `cx * ny < nx * (ny - cy)`, skipping the DC term.
+fn thumbhash_ac_sum(ac: &[f64], fx: &[f64], fy: &[f64], nx: usize, ny: usize) -> f64 {
+    let mut sum = 0.0;
+    let mut terms = ac.iter();
+    for (cy, &fy) in fy.iter().enumerate().take(ny) {
+        let first = if cy > 0 { 0 } else { 1 };
+        let end = (nx * (ny - cy)).div_ceil(ny);
+        for (&fx, &term) in fx[first..end].iter().zip(&mut terms) {
+            sum += term * fx * fy * 2.0;
+        }
+    }
+    sum
+}
+
+// --- ThumbHash Decode ---
+#[wasm_bindgen]
+pub fn decode_thumbhash(hash: &[u8]) -> Result<ThumbHashImage, JsValue> {
+    if hash.len() < 5 {
+        return Err(invalid("thumbhash must be at least 5 bytes"));
+    }
+
+    let header24 = hash[0] as u32 | (hash[1] as u32) << 8 | (hash[2] as u32) << 16;
+    let header16 = hash[3] as u32 | (hash[4] as u32) << 8;
+    let l_dc = (header24 & 63) as f64 / 63.0;
+    let p_dc = ((header24 >> 6) & 63) as f64 / 31.5 - 1.0;
+    let q_dc = ((header24 >> 12) & 63) as f64 / 31.5 - 1.0;
+    let l_scale = ((header24 >> 18) & 31) as f64 / 31.0;
+    let has_alpha = header24 >> 23 != 0;
+    let p_scale = ((header16 >> 3) & 63) as f64 / 63.0;
+    let q_scale = ((header16 >> 9) & 63) as f64 / 63.0;
+    let (stored_lx, stored_ly) = thumbhash_luma_size(hash);
+    let (lx, ly) = (stored_lx.max(3), stored_ly.max(3));
+    if has_alpha && hash.len() < 6 {
+        return Err(invalid("thumbhash is truncated"));
+    }
+    let a_dc = if has_alpha { (hash[5] & 15) as f64 / 15.0 } else { 1.0 };
+    let a_scale = if has_alpha { (hash[5] >> 4) as f64 / 15.0 } else { 0.0 };
+
+    let ac_start = if has_alpha { 6 } else { 5 };
+    let mut ac_index = 0;
+    let mut decode_channel = |nx: usize, ny: usize, scale: f64| -> Result<Vec<f64>, JsValue> {
+        let mut ac = Vec::new();
+        for cy in 0..ny {
+            let mut cx = if cy > 0 { 0 } else { 1 };
+            while cx * ny < nx * (ny - cy) {
+                let byte = *hash
+                    .get(ac_start + (ac_index >> 1))
+                    .ok_or_else(|| invalid("thumbhash is truncated"))?;
+                let nibble = (byte >> ((ac_index & 1) << 2)) & 15;
+                ac.push((nibble as f64 / 7.5 - 1.0) * scale);
+                ac_index += 1;
+                cx += 1;
+            }
+        }
+        Ok(ac)
+    };
+    let l_ac = decode_channel(lx, ly, l_scale)?;
+    // Saturation is boosted to make up for quantization
+    let p_ac = decode_channel(3, 3, p_scale * 1.25)?;
+    let q_ac = decode_channel(3, 3, q_scale * 1.25)?;
+    let a_ac = if has_alpha { decode_channel(5, 5, a_scale)? } else { Vec::new() };
+
+    let ratio = stored_lx as f64 / stored_ly as f64;
+    let w = (if ratio > 1.0 { THUMBHASH_DECODE_SIDE } else { THUMBHASH_DECODE_SIDE * ratio }).round() as usize;
+    let h = (if ratio > 1.0 { THUMBHASH_DECODE_SIDE / ratio } else { THUMBHASH_DECODE_SIDE }).round() as usize;
+
+    let mut pixels = vec![0u8; w * h * 4];
+    let n_x = lx.max(if has_alpha { 5 } else { 3 });
+    let n_y = ly.max(if has_alpha { 5 } else { 3 });
+    let mut fx = vec![0.0; n_x];
+    let mut fy = vec![0.0; n_y];
+    for y in 0..h {
+        for x in 0..w {
+            let (mut l, mut p, mut q, mut a) = (l_dc, p_dc, q_dc, a_dc);
+
+            for (cx, f) in fx.iter_mut().enumerate() {
+                *f = (PI / w as f64 * (x as f64 + 0.5) * cx as f64).cos();
+            }
+            for (cy, f) in fy.iter_mut().enumerate() {
+                *f = (PI / h as f64 * (y as f64 + 0.5) * cy as f64).cos();
+            }
+
+            l += thumbhash_ac_sum(&l_ac, &fx, &fy, lx, ly);
+            p += thumbhash_ac_sum(&p_ac, &fx, &fy, 3, 3);
+            q += thumbhash_ac_sum(&q_ac, &fx, &fy, 3, 3);
+            if has_alpha {
+                a += thumbhash_ac_sum(&a_ac, &fx, &fy, 5, 5);
+            }
+
+            let b = l - 2.0 / 3.0 * p;
+            let r = (3.0 * l - b + q) / 2.0;
+            let g = r - q;
+            let idx = (y * w + x) * 4;
+            for (out, value) in pixels[idx..idx + 4].iter_mut().zip([r, g, b, a]) {
+                *out = (255.0 * value.min(1.0)).max(0.0) as u8;
+            }
+        }
+    }
+
+    Ok(ThumbHashImage {
+        width: w as u32,
+        height: h as u32,
+        pixels,
+    })
+}
+
+// ------------------------------------------------------------------------
+// Color placeholders: cheaper than a hash and usable straight from CSS,
+// no decode step needed
+// ------------------------------------------------------------------------
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaceholderMode {
+    // The most common color (coarse 4-bit-per-channel histogram)
+    DominantColor,
+    // Two stops along whichever axis the image changes most
+    Gradient,
+    // 4x4 grid of area-averaged colors
+    Palette,
+}
+
+// `css` is a ready-to-use `background` value; `colors` holds the same
+// colors packed as 0xRRGGBBAA (row-major for the palette)
+#[wasm_bindgen]
+pub struct Placeholder {
+    css: String,
+    colors: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl Placeholder {
+    #[wasm_bindgen(getter)]
+    pub fn css(&self) -> String {
+        self.css.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn colors(&self) -> Vec<u32> {
+        self.colors.clone()
+    }
+}
+
+const PALETTE_SIDE: usize = 4;
+
+// --- Color Placeholder ---
+#[wasm_bindgen]
+pub fn extract_placeholder(data: &[u8], width: u32, height: u32, mode: PlaceholderMode) -> Result<Placeholder, JsValue> {
+    check_image(data, width, height)?;
+    let (w, h) = (width as usize, height as usize);
+
+    let placeholder = match mode {
+        PlaceholderMode::DominantColor => {
+            let color = dominant_color(data);
+            Placeholder {
+                css: css_hex(color),
+                colors: vec![color],
+            }
+        }
+        PlaceholderMode::Gradient => {
+            let horizontal = resample_area(data, w, h, (0, 0, w, h), 2, 1);
+            let vertical = resample_area(data, w, h, (0, 0, w, h), 1, 2);
+            let spread = |halves: &[u8]| -> u32 { (0..3).map(|c| halves[c].abs_diff(halves[4 + c]) as u32).sum() };
+            let (direction, halves) = if spread(&horizontal) > spread(&vertical) {
+                ("to right", horizontal)
+            } else {
+                ("to bottom", vertical)
+            };
+            let colors = vec![pack_rgba(&halves[0..4]), pack_rgba(&halves[4..8])];
+            Placeholder {
+                css: format!("linear-gradient({}, {}, {})", direction, css_hex(colors[0]), css_hex(colors[1])),
+                colors,
+            }
+        }
+        PlaceholderMode::Palette => {
+            let cells = resample_area(data, w, h, (0, 0, w, h), PALETTE_SIDE, PALETTE_SIDE);
+            let colors: Vec<u32> = cells.chunks_exact(4).map(pack_rgba).collect();
+            Placeholder {
+                css: palette_css(&colors),
+                colors,
+            }
+        }
+    };
+
+    Ok(placeholder)
+}
+
+// Mean color of the fullest bin in a 16x16x16 histogram, alpha-weighted so
+// transparent pixels don't count
+fn dominant_color(data: &[u8]) -> u32 {
+    let mut counts = vec![0u64; 4096];
+    let mut sums = vec![[0u64; 3]; 4096];
+    for pixel in data.chunks_exact(4) {
+        let alpha = pixel[3] as u64;
+        let bin = (pixel[0] as usize >> 4) << 8 | (pixel[1] as usize >> 4) << 4 | pixel[2] as usize >> 4;
+        counts[bin] += alpha;
+        for c in 0..3 {
+            sums[bin][c] += pixel[c] as u64 * alpha;
+        }
+    }
+
+    let (bin, &count) = counts.iter().enumerate().max_by_key(|&(_, count)| *count).unwrap_or((0, &0));
+    if count == 0 {
+        return 0;
+    }
+    let channel = |c: usize| (sums[bin][c] / count) as u32;
+    channel(0) << 24 | channel(1) << 16 | channel(2) << 8 | 0xFF
+}
+
+fn pack_rgba(pixel: &[u8]) -> u32 {
+    u32::from_be_bytes([pixel[0], pixel[1], pixel[2], pixel[3]])
+}
+
+fn css_hex(color: u32) -> String {
+    format!("#{:06x}", color >> 8)
+}
+
+// One hard-stopped gradient per row, each sized to a quarter of the height
+fn palette_css(colors: &[u32]) -> String {
+    let band = 100.0 / PALETTE_SIDE as f64;
+    colors
+        .chunks_exact(PALETTE_SIDE)
+        .enumerate()
+        .map(|(row, cells)| {
+            let stops: Vec<String> = cells
+                .iter()
+                .enumerate()
+                .map(|(i, &color)| format!("{} {}% {}%", css_hex(color), i as f64 * band, (i + 1) as f64 * band))
+                .collect();
+            // background-position percentages line the band's edge up with
+            // the same fraction of the leftover space
+            let y = row as f64 * 100.0 / (PALETTE_SIDE - 1) as f64;
+            format!("linear-gradient(to right, {}) 0 {:.2}% / 100% {}% no-repeat", stops.join(", "), y, band)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
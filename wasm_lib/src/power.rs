@@ -0,0 +1,144 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::settings::{self, QualityTier};
+
+// ========================================================================
+// POWER STATE
+// The module can't see the battery or whether the tab is visible, so the
+// host passes them in whenever they change:
+//
+//   const battery = await navigator.getBattery();
+//   const update = () => set_power_state({
+//       charging: battery.charging,
+//       level: battery.level,
+//       hidden: document.hidden,
+//   });
+//   battery.addEventListener("chargingchange", update);
+//   battery.addEventListener("levelchange", update);
+//   document.addEventListener("visibilitychange", update);
+//
+// On battery, filters use at most half the configured threads and the
+// quality tier is capped at `Balanced`. On a low battery (under 20%) or in
+// a hidden tab they drop to one thread and `Low`, and `background_paused()`
+// turns true so hosts can stop stepping simulations and batch jobs until
+// it clears. Settings chosen with `set_quality` / `init` are kept and come
+// back once the power state allows them again.
+// ========================================================================
+
+// Battery level (0..1) below which a discharging device saves as much as it can
+const LOW_BATTERY: f64 = 0.2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Throttle {
+    None = 0,
+    Saver = 1,
+    Minimal = 2,
+}
+
+static THROTTLE: AtomicU8 = AtomicU8::new(Throttle::None as u8);
+
+fn throttle() -> Throttle {
+    match THROTTLE.load(Ordering::Relaxed) {
+        0 => Throttle::None,
+        1 => Throttle::Saver,
+        _ => Throttle::Minimal,
+    }
+}
+
+// Every field is optional; missing ones count as plugged in, full and
+// visible
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PowerState {
+    charging: Option<bool>,
+    level: Option<f64>,
+    hidden: Option<bool>,
+}
+
+#[derive(Serialize)]
+struct PowerReport {
+    throttle: Throttle,
+    // Most threads filters will now split work across, and the quality
+    // tier they'll run at
+    threads: usize,
+    quality: QualityTier,
+    background_paused: bool,
+}
+
+// --- Set Power State ---
+// Takes the state object described above and returns what it changed to
+#[wasm_bindgen]
+pub fn set_power_state(state: JsValue) -> Result<JsValue, JsValue> {
+    let state: PowerState = if state.is_undefined() || state.is_null() {
+        PowerState::default()
+    } else {
+        let json = js_sys::JSON::stringify(&state)
+            .ok()
+            .and_then(|json| json.as_string())
+            .unwrap_or_default();
+        serde_json::from_str(&json)
+            .map_err(|err| JsValue::from(JsError::new(&format!("invalid power state: {}", err))))?
+    };
+
+    let on_battery = state.charging == Some(false);
+    let low = on_battery && state.level.is_some_and(|level| level < LOW_BATTERY);
+    let throttle = if state.hidden == Some(true) || low {
+        Throttle::Minimal
+    } else if on_battery {
+        Throttle::Saver
+    } else {
+        Throttle::None
+    };
+    THROTTLE.store(throttle as u8, Ordering::Relaxed);
+    console_log!("Rust (WASM): Power throttle {:?}.", throttle);
+
+    let report = PowerReport {
+        throttle,
+        threads: thread_cap(settings::thread_count() as usize),
+        quality: settings::effective_quality(),
+        background_paused: background_paused(),
+    };
+    serde_json::to_string(&report)
+        .ok()
+        .and_then(|json| js_sys::JSON::parse(&json).ok())
+        .ok_or_else(|| JsError::new("couldn't build the power report").into())
+}
+
+// True while the tab is hidden or the battery is low; long-running hosts
+// (simulation loops, batch exports) should hold off until it clears
+#[wasm_bindgen]
+pub fn background_paused() -> bool {
+    throttle() == Throttle::Minimal
+}
+
+// Threads a job may use out of `threads` under the current power state
+pub(crate) fn thread_cap(threads: usize) -> usize {
+    match throttle() {
+        Throttle::None => threads,
+        Throttle::Saver => (threads / 2).max(1),
+        Throttle::Minimal => threads.min(1),
+    }
+}
+
+// `tier` lowered to what the current power state allows
+pub(crate) fn quality_cap(tier: QualityTier) -> QualityTier {
+    match (throttle(), tier) {
+        (Throttle::None, tier) => tier,
+        (Throttle::Saver, QualityTier::High) => QualityTier::Balanced,
+        (Throttle::Saver, tier) => tier,
+        (Throttle::Minimal, _) => QualityTier::Low,
+    }
+}
+
+// Runs `f` as if the device were plugged in and visible, for the self-test
+// whose checksums assume full quality
+pub(crate) fn unthrottled<T>(f: impl FnOnce() -> T) -> T {
+    let saved = THROTTLE.swap(Throttle::None as u8, Ordering::Relaxed);
+    let result = f();
+    THROTTLE.store(saved, Ordering::Relaxed);
+    result
+}
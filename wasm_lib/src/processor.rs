@@ -0,0 +1,100 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_image, check_radius};
+use crate::memory::check_memory;
+use crate::{blur_pixels, grayscale_pixels, invert_pixels};
+
+// ========================================================================
+// IMAGE PROCESSOR
+// Holds one image in WASM memory so a chain of filters runs on the same
+// buffer without re-passing dimensions or copying pixels across the
+// boundary between steps:
+//
+//   const proc = new ImageProcessor();
+//   proc.load(imageData.data, width, height);
+//   proc.grayscale();
+//   proc.blur(4);
+//   imageData.data.set(proc.pixels());
+//
+// The buffer is reused by later `load` calls, so a processor kept around
+// for video frames stops allocating once it has seen the largest frame.
+// ========================================================================
+
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct ImageProcessor {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl ImageProcessor {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> ImageProcessor {
+        ImageProcessor::default()
+    }
+
+    // Copy in a `width x height` RGBA image, replacing the current one.
+    // Throws (and leaves the processor empty) if the size doesn't match.
+    pub fn load(&mut self, data: &[u8], width: u32, height: u32) -> Result<(), JsValue> {
+        self.pixels.clear();
+        if let Err(err) = check_image(data, width, height) {
+            self.width = 0;
+            self.height = 0;
+            return Err(err.into());
+        }
+
+        self.pixels.extend_from_slice(data);
+        self.width = width;
+        self.height = height;
+        Ok(())
+    }
+
+    pub fn grayscale(&mut self) {
+        grayscale_pixels(&mut self.pixels);
+    }
+
+    pub fn invert(&mut self) {
+        invert_pixels(&mut self.pixels);
+    }
+
+    pub fn blur(&mut self, radius: u32) -> Result<(), JsValue> {
+        check_radius(radius)?;
+        check_memory("blur", self.width, self.height)?;
+        blur_pixels(&mut self.pixels, self.width, self.height, radius);
+        Ok(())
+    }
+
+    // Copy of the current pixels
+    pub fn pixels(&self) -> Vec<u8> {
+        self.pixels.clone()
+    }
+
+    // Address of the pixel buffer, for reading it through a view on
+    // `wasm_memory()` without a copy. Invalidated by the next `load`.
+    pub fn pixels_ptr(&self) -> *const u8 {
+        self.pixels.as_ptr()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // Length of the pixel buffer in bytes (width * height * 4)
+    #[wasm_bindgen(getter)]
+    pub fn len(&self) -> usize {
+        self.pixels.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_empty(&self) -> bool {
+        self.pixels.is_empty()
+    }
+}
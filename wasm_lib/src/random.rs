@@ -0,0 +1,31 @@
+use crate::settings;
+
+// ========================================================================
+// RANDOM NUMBERS
+// Every stochastic feature draws from its own `wasmfx_core::random::Lcg`,
+// restarted on each call from `stream_seed(default_seed)`, so a given
+// input always produces the same output. Each feature has a default
+// starting state; `set_seed(seed)` swaps all of them for states
+// derived from `seed`, which lets tests and screenshot suites pick (and
+// vary) the sequence while staying reproducible across runs and browsers.
+//
+// The generator is the same LCG the demo site's JavaScript benchmarks use,
+// so both sides do identical work until a seed is set.
+// ========================================================================
+
+// `default_seed` is used as is until a global seed is set, then mixed
+// with it
+pub(crate) fn stream_seed(default_seed: u32) -> u32 {
+    match settings::seed() {
+        None => default_seed,
+        Some(seed) => mix(((seed as u64) << 32) | default_seed as u64),
+    }
+}
+
+// SplitMix64 finalizer, so nearby seeds give unrelated sequences
+fn mix(value: u64) -> u32 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (z ^ (z >> 31)) as u32
+}
@@ -0,0 +1,244 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::check_image;
+use crate::{blur_pixels, edge_detection_pixels, grayscale_pixels, invert_pixels, sharpen_pixels};
+
+// ========================================================================
+// REAL-TIME SCHEDULER
+// Runs a chain of filters on each video frame within a millisecond budget.
+// Stage costs are learned from previous frames; when the chain wouldn't
+// fit, optional stages are skipped first and then the frame is processed at
+// a lower resolution and scaled back up.
+// ========================================================================
+
+// Smoothing factor for the per-stage cost estimates
+const COST_SMOOTHING: f64 = 0.2;
+// Never go below 1/4 resolution, or below this many pixels per side (the
+// convolution filters need a few pixels of border)
+const MIN_SCALE_DIVISOR: usize = 4;
+const MIN_SIDE: usize = 16;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StageFilter {
+    Grayscale,
+    Invert,
+    Blur,
+    EdgeDetection,
+    Sharpen,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SchedulerStats {
+    // Frames handed to `process`
+    pub frames: u32,
+    // Frames whose processing still overran the budget
+    pub dropped_frames: u32,
+    // Total optional stages skipped to stay within budget
+    pub skipped_stages: u32,
+    // Wall time of the most recent frame
+    pub last_frame_ms: f64,
+    // Resolution divisor used for the most recent frame (1, 2 or 4)
+    pub scale_divisor: u32,
+}
+
+struct Stage {
+    filter: StageFilter,
+    // Radius for blur, strength for sharpen; ignored by the other filters
+    param: u32,
+    optional: bool,
+    // Learned cost in milliseconds per million pixels (0 until measured)
+    ms_per_megapixel: f64,
+}
+
+// --- RealtimeScheduler ---
+#[wasm_bindgen]
+pub struct RealtimeScheduler {
+    width: usize,
+    height: usize,
+    budget_ms: f64,
+    stages: Vec<Stage>,
+    stats: SchedulerStats,
+}
+
+#[wasm_bindgen]
+impl RealtimeScheduler {
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: u32, height: u32, budget_ms: f64) -> RealtimeScheduler {
+        RealtimeScheduler {
+            width: width as usize,
+            height: height as usize,
+            budget_ms: budget_ms.max(0.0),
+            stages: Vec::new(),
+            stats: SchedulerStats {
+                scale_divisor: 1,
+                ..SchedulerStats::default()
+            },
+        }
+    }
+
+    // Append a stage to the chain. Optional stages are the first to go
+    // when the frame budget gets tight.
+    pub fn add_stage(&mut self, filter: StageFilter, param: u32, optional: bool) {
+        self.stages.push(Stage {
+            filter,
+            param,
+            optional,
+            ms_per_megapixel: 0.0,
+        });
+    }
+
+    pub fn clear_stages(&mut self) {
+        self.stages.clear();
+    }
+
+    pub fn set_budget(&mut self, budget_ms: f64) {
+        self.budget_ms = budget_ms.max(0.0);
+    }
+
+    // Run the chain on one RGBA frame; the output is always full size.
+    // Throws if the frame doesn't match the scheduler's dimensions.
+    pub fn process(&mut self, frame: Vec<u8>) -> Result<Vec<u8>, JsValue> {
+        check_image(&frame, self.width as u32, self.height as u32)?;
+        let frame_start = crate::now();
+        let full_megapixels = (self.width * self.height) as f64 / 1_000_000.0;
+
+        // Pick the largest resolution at which the required stages fit
+        let required_cost: f64 = self
+            .stages
+            .iter()
+            .filter(|s| !s.optional)
+            .map(|s| s.ms_per_megapixel)
+            .sum();
+        let mut divisor = 1;
+        while divisor < MIN_SCALE_DIVISOR
+            && required_cost * full_megapixels / (divisor * divisor) as f64 > self.budget_ms
+            && self.width / (divisor * 2) >= MIN_SIDE
+            && self.height / (divisor * 2) >= MIN_SIDE
+        {
+            divisor *= 2;
+        }
+
+        // Spend what's left of the budget on optional stages, in order
+        let megapixels = full_megapixels / (divisor * divisor) as f64;
+        let mut remaining = self.budget_ms - required_cost * megapixels;
+        let mut run_stage = Vec::with_capacity(self.stages.len());
+        for stage in self.stages.iter() {
+            if !stage.optional {
+                run_stage.push(true);
+            } else if stage.ms_per_megapixel * megapixels <= remaining {
+                remaining -= stage.ms_per_megapixel * megapixels;
+                run_stage.push(true);
+            } else {
+                self.stats.skipped_stages += 1;
+                run_stage.push(false);
+            }
+        }
+
+        let (mut data, w, h) = if divisor > 1 {
+            downscale_box(&frame, self.width, self.height, divisor)
+        } else {
+            (frame, self.width, self.height)
+        };
+
+        for (stage, run) in self.stages.iter_mut().zip(run_stage) {
+            if !run {
+                continue;
+            }
+            let start = crate::now();
+            data = run_filter(stage.filter, stage.param, data, w, h, divisor as u32);
+            let cost = (crate::now() - start) / megapixels.max(1e-6);
+
+            stage.ms_per_megapixel = if stage.ms_per_megapixel == 0.0 {
+                cost
+            } else {
+                stage.ms_per_megapixel * (1.0 - COST_SMOOTHING) + cost * COST_SMOOTHING
+            };
+        }
+
+        if divisor > 1 {
+            data = upscale_nearest(&data, w, h, self.width, self.height);
+        }
+
+        let elapsed = crate::now() - frame_start;
+        self.stats.frames += 1;
+        if elapsed > self.budget_ms {
+            self.stats.dropped_frames += 1;
+        }
+        self.stats.last_frame_ms = elapsed;
+        self.stats.scale_divisor = divisor as u32;
+
+        Ok(data)
+    }
+
+    pub fn stats(&self) -> SchedulerStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = SchedulerStats {
+            scale_divisor: self.stats.scale_divisor,
+            ..SchedulerStats::default()
+        };
+    }
+}
+
+// `divisor` is how far the frame was scaled down, which a blur radius has
+// to follow to look the same once the frame is scaled back up
+fn run_filter(filter: StageFilter, param: u32, mut data: Vec<u8>, width: usize, height: usize, divisor: u32) -> Vec<u8> {
+    let (w, h) = (width as u32, height as u32);
+    match filter {
+        StageFilter::Grayscale => grayscale_pixels(&mut data),
+        StageFilter::Invert => invert_pixels(&mut data),
+        // A zero radius leaves the frame as it is
+        StageFilter::Blur if param == 0 => {}
+        StageFilter::Blur => blur_pixels(&mut data, w, h, (param / divisor).max(1)),
+        StageFilter::EdgeDetection => return edge_detection_pixels(&data, w, h),
+        StageFilter::Sharpen => return sharpen_pixels(&data, w, h, param),
+    }
+    data
+}
+
+// Average each `factor` x `factor` block into one pixel
+fn downscale_box(data: &[u8], width: usize, height: usize, factor: usize) -> (Vec<u8>, usize, usize) {
+    let out_w = width / factor;
+    let out_h = height / factor;
+    let mut result = vec![0u8; out_w * out_h * 4];
+    let area = (factor * factor) as u32;
+
+    for y in 0..out_h {
+        for x in 0..out_w {
+            let mut sums = [0u32; 4];
+            for dy in 0..factor {
+                for dx in 0..factor {
+                    let idx = ((y * factor + dy) * width + x * factor + dx) * 4;
+                    for (sum, value) in sums.iter_mut().zip(&data[idx..idx + 4]) {
+                        *sum += *value as u32;
+                    }
+                }
+            }
+            let out_idx = (y * out_w + x) * 4;
+            for (out, sum) in result[out_idx..out_idx + 4].iter_mut().zip(sums) {
+                *out = (sum / area) as u8;
+            }
+        }
+    }
+
+    (result, out_w, out_h)
+}
+
+// Blocky but cheap: this only runs when we're already over budget
+fn upscale_nearest(data: &[u8], width: usize, height: usize, out_w: usize, out_h: usize) -> Vec<u8> {
+    let mut result = vec![0u8; out_w * out_h * 4];
+    for y in 0..out_h {
+        let sy = (y * height / out_h).min(height - 1);
+        for x in 0..out_w {
+            let sx = (x * width / out_w).min(width - 1);
+            let src = (sy * width + sx) * 4;
+            let dst = (y * out_w + x) * 4;
+            result[dst..dst + 4].copy_from_slice(&data[src..src + 4]);
+        }
+    }
+    result
+}
@@ -0,0 +1,983 @@
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "filters-core")]
+use crate::adjust::{
+    apply_curves, apply_duotone, apply_equalize, apply_exposure, apply_gradient_map, apply_posterize, apply_saturation,
+    apply_sepia, apply_temperature_tint, apply_vibrance, tone_map, ToneMapOperator,
+};
+#[cfg(feature = "filters-core")]
+use crate::analysis::{compare_images, compute_histogram, measure_sharpness, perceptual_hash, HashAlgorithm};
+#[cfg(feature = "filters-core")]
+use crate::api::{BlurMethod, BlurOptions, SharpenOptions};
+#[cfg(feature = "filters-core")]
+use crate::binary::{apply_adaptive_threshold, apply_threshold, AdaptiveMethod};
+#[cfg(feature = "filters-core")]
+use crate::blur_effects::{apply_motion_blur, apply_radial_blur, apply_zoom_blur};
+use crate::color::{hsl_to_rgb, rgb_to_hsl, rgb_to_lab};
+#[cfg(feature = "filters-core")]
+use crate::color_transfer::{transfer_color, TransferMethod};
+#[cfg(feature = "filters-core")]
+use crate::composite::{apply_chroma_key, blend_images, render_drop_shadow, BlendMode, LayerStack};
+#[cfg(feature = "filters-core")]
+use crate::denoise::{apply_bilateral, apply_median};
+#[cfg(feature = "filters-core")]
+use crate::dither::{apply_dither_bayer, apply_dither_fs, quantize_to_palette, recolor_to_palette};
+#[cfg(feature = "filters-core")]
+use crate::geometry::Insets;
+#[cfg(feature = "filters-core")]
+use crate::inpaint::inpaint;
+#[cfg(feature = "filters-core")]
+use crate::lut::{apply_lut3d, load_cube_lut};
+#[cfg(feature = "filters-core")]
+use crate::morphology::{morph, KernelShape, MorphOp};
+#[cfg(feature = "dsp")]
+use crate::motion::frame_diff;
+#[cfg(feature = "filters-core")]
+use crate::overlay::{burn_in_overlay, OverlayPosition};
+#[cfg(feature = "filters-core")]
+use crate::palette::{color_analysis, extract_palette_kmeans, quantize_median_cut};
+#[cfg(feature = "filters-core")]
+use crate::pipeline::FilterPipeline;
+#[cfg(feature = "codecs")]
+use crate::placeholder::{encode_blurhash, encode_thumbhash};
+use crate::settings::{self, get_math_mode, get_quality, set_math_mode, set_quality, MathMode, QualityTier};
+#[cfg(feature = "filters-core")]
+use crate::stylize::{
+    apply_cartoon, apply_channel_glitch, apply_chromatic_aberration, apply_grain, apply_halftone, apply_kuwahara,
+    apply_pixel_sort, apply_pixelate, HalftoneMode, SortDirection,
+};
+#[cfg(feature = "filters-core")]
+use crate::thumbnail::smart_thumbnail;
+#[cfg(feature = "filters-core")]
+use crate::scheduler::StageFilter;
+#[cfg(feature = "filters-core")]
+use crate::transform::{
+    build_pyramid, crop, pad, resize, rotate, seam_carve, upscale, Interpolation, ResizeFilter, UpscaleMethod,
+};
+#[cfg(feature = "filters-core")]
+use crate::video::{deinterlace, filter_yuv_luma, i420_to_rgba, rgba_to_i420, DeinterlaceMethod, YuvMatrix};
+#[cfg(feature = "filters-core")]
+use crate::vision::{daltonize, simulate_cvd, Deficiency};
+use crate::{parallel, power, simd};
+#[cfg(feature = "filters-core")]
+use crate::{
+    apply_canny, apply_edge_detection, apply_grayscale, apply_high_pass, apply_invert, apply_unsharp_mask, blur, sharpen,
+};
+#[cfg(feature = "sims")]
+use crate::{generate_mandelbrot, render_fractal_tile, Fractal};
+
+// ========================================================================
+// SELF-TEST
+// Runs each feature on a tiny synthetic image and compares a checksum of
+// the output with the value a reference build produced, so a deployment
+// (including SIMD and threaded builds, whose kernels must match the scalar
+// ones bit for bit) can be checked in the field:
+//
+//   const report = self_test();
+//   if (!report.passed) console.table(report.results);
+//
+// Blur output depends on the quality tier, so the test runs at High and
+// restores the caller's tier afterwards. Any intentional change to a
+// filter's output needs its golden value updated here. Cases for feature
+// groups left out of the build are left out of the test.
+// ========================================================================
+
+// Odd sizes so SIMD remainders and uneven thread bands get exercised
+const TEST_WIDTH: u32 = 23;
+const TEST_HEIGHT: u32 = 17;
+
+struct Case {
+    name: &'static str,
+    expected: u32,
+    run: fn() -> Vec<u8>,
+}
+
+const CASES: &[Case] = &[
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "grayscale",
+        expected: 0xE4E3_3F4E,
+        run: || apply_grayscale(test_image(TEST_WIDTH, TEST_HEIGHT)).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "invert",
+        expected: 0x0B7A_120C,
+        run: || apply_invert(test_image(TEST_WIDTH, TEST_HEIGHT)).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "blur",
+        expected: 0xBA81_F4B4,
+        run: || blur(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, &BlurOptions::new(3)).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "box_blur",
+        expected: 0xD9FD_2622,
+        run: || {
+            let mut options = BlurOptions::new(9);
+            options.set_method(BlurMethod::Box);
+            blur(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, &options).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "stack_blur",
+        expected: 0x430B_5D7C,
+        run: || {
+            let mut options = BlurOptions::new(9);
+            options.set_method(BlurMethod::Stack);
+            blur(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, &options).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "linear_light_blur",
+        expected: 0xC9B8_ACD7,
+        run: || {
+            let mut options = BlurOptions::new(5);
+            options.set_linear_light(true);
+            blur(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, &options).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "linear_light_box_blur",
+        expected: 0x2C80_FAE6,
+        run: || {
+            let mut options = BlurOptions::new(9);
+            options.set_method(BlurMethod::Box);
+            options.set_linear_light(true);
+            blur(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, &options).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "edge_detection",
+        expected: 0x3271_9CC1,
+        run: || apply_edge_detection(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "sharpen",
+        expected: 0x45C5_97B4,
+        run: || {
+            sharpen(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, &SharpenOptions::new(60)).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "linear_light_sharpen",
+        expected: 0xF2F7_DFF4,
+        run: || {
+            let mut options = SharpenOptions::new(60);
+            options.linear_light = true;
+            sharpen(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, &options).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "median",
+        expected: 0x0637_1A0F,
+        run: || apply_median(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, 2, None).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "bilateral",
+        expected: 0xF73E_7A02,
+        run: || {
+            apply_bilateral(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, 2.0, 30.0, None)
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "bilateral_huge_sigma",
+        expected: 0x0A82_DAF5,
+        // The window stops at the image's size instead of growing without
+        // bound
+        run: || {
+            apply_bilateral(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, 1e30, 30.0, None)
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "deinterlace",
+        expected: 0x5F07_7163,
+        run: || {
+            let mut previous = test_image(TEST_WIDTH, TEST_HEIGHT);
+            previous[..TEST_WIDTH as usize * 4 * 8].reverse();
+            let frame = test_image(TEST_WIDTH, TEST_HEIGHT);
+            deinterlace(frame, TEST_WIDTH, TEST_HEIGHT, DeinterlaceMethod::YadifLite, Some(previous)).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "saturation",
+        expected: 0xF117_7673,
+        run: || apply_saturation(test_image(TEST_WIDTH, TEST_HEIGHT), 0.5).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "vibrance",
+        expected: 0x4890_1D4A,
+        run: || apply_vibrance(test_image(TEST_WIDTH, TEST_HEIGHT), 0.8).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "curves",
+        expected: 0x401E_873B,
+        run: || {
+            let points = r#"{"rgb": [[0, 0], [64, 48], [192, 210], [255, 255]], "blue": [[0, 20], [255, 235]]}"#;
+            apply_curves(test_image(TEST_WIDTH, TEST_HEIGHT), points).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "sepia",
+        expected: 0xCC4B_2A63,
+        run: || apply_sepia(test_image(TEST_WIDTH, TEST_HEIGHT), 0.75).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "posterize",
+        expected: 0xCFFE_6EFD,
+        run: || apply_posterize(test_image(TEST_WIDTH, TEST_HEIGHT), 4).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "exposure",
+        expected: 0x2AC6_F401,
+        run: || apply_exposure(test_image(TEST_WIDTH, TEST_HEIGHT), 0.75).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "tone_map_reinhard",
+        expected: 0x6FCA_0711,
+        run: || tone_map(&test_hdr_image(), TEST_WIDTH, TEST_HEIGHT, ToneMapOperator::Reinhard, 0.0).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "tone_map_aces",
+        expected: 0x8780_15EA,
+        run: || tone_map(&test_hdr_image(), TEST_WIDTH, TEST_HEIGHT, ToneMapOperator::Aces, -0.5).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "temperature_tint",
+        expected: 0xA9E4_E7E8,
+        run: || apply_temperature_tint(test_image(TEST_WIDTH, TEST_HEIGHT), 0.6, -0.3).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "duotone",
+        expected: 0x2786_6268,
+        run: || apply_duotone(test_image(TEST_WIDTH, TEST_HEIGHT), 0x1E32_64FF, 0xF037_A5FF).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "gradient_map",
+        expected: 0xDF6E_8B75,
+        run: || {
+            let stops = r##"[{"at": 1, "color": "#fff3b0"}, {"at": 0, "color": "#000000"}, {"at": 0.6, "color": "#d7263d80"}]"##;
+            apply_gradient_map(test_image(TEST_WIDTH, TEST_HEIGHT), stops).unwrap_or_default()
+        },
+    },
+    Case {
+        name: "rgb_to_lab",
+        expected: 0xC42E_E64E,
+        run: || {
+            let lab = rgb_to_lab(&test_image(TEST_WIDTH, TEST_HEIGHT)).unwrap_or_default();
+            lab.iter().flat_map(|v| v.to_le_bytes()).collect()
+        },
+    },
+    Case {
+        name: "hsl_round_trip",
+        expected: 0x4FFE_C241,
+        run: || {
+            let hsl = rgb_to_hsl(&test_image(TEST_WIDTH, TEST_HEIGHT)).unwrap_or_default();
+            hsl_to_rgb(&hsl).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "lut3d",
+        expected: 0xC086_0CDB,
+        run: || {
+            load_cube_lut(&test_cube_lut())
+                .and_then(|lut| apply_lut3d(test_image(TEST_WIDTH, TEST_HEIGHT), &lut))
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "simulate_deuteranopia",
+        expected: 0x84E5_C974,
+        run: || simulate_cvd(test_image(TEST_WIDTH, TEST_HEIGHT), Deficiency::Deuteranopia).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "simulate_tritanopia",
+        expected: 0x682F_1341,
+        run: || simulate_cvd(test_image(TEST_WIDTH, TEST_HEIGHT), Deficiency::Tritanopia).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "daltonize_protanopia",
+        expected: 0xB1BA_FA96,
+        run: || daltonize(test_image(TEST_WIDTH, TEST_HEIGHT), Deficiency::Protanopia).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "equalize",
+        expected: 0xA066_68A3,
+        run: || apply_equalize(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "threshold",
+        expected: 0x27C5_BCC8,
+        run: || apply_threshold(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, 128).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "adaptive_threshold",
+        expected: 0x1EC1_9321,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            apply_adaptive_threshold(image, TEST_WIDTH, TEST_HEIGHT, AdaptiveMethod::Gaussian, 5, 2.0).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "dither_fs",
+        expected: 0x07B0_94FD,
+        run: || apply_dither_fs(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, 1).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "dither_bayer",
+        expected: 0x2452_7B9E,
+        run: || apply_dither_bayer(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, 4).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "recolor_to_palette",
+        expected: 0x3B95_1C00,
+        run: || {
+            let palette = vec![0x0F380F, 0x306230, 0x8BAC0F, 0x9BBC0F];
+            recolor_to_palette(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, palette, true)
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "palette_kmeans",
+        expected: 0x295E_C980,
+        // The palette's packed colours, then the image mapped onto them
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            let palette = extract_palette_kmeans(&image, 5, 20).unwrap_or_default();
+            let mut out: Vec<u8> = palette.iter().flat_map(|rgb| rgb.to_le_bytes()).collect();
+            out.extend(quantize_to_palette(image, palette).unwrap_or_default());
+            out
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "median_cut",
+        expected: 0x4DE6_C286,
+        // The palette's packed colours, then the indices
+        run: || {
+            quantize_median_cut(&test_image(TEST_WIDTH, TEST_HEIGHT), 6)
+                .map(|indexed| {
+                    let mut out: Vec<u8> = indexed.palette().iter().flat_map(|rgb| rgb.to_le_bytes()).collect();
+                    out.extend(indexed.indices());
+                    out
+                })
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "analyze_colors",
+        expected: 0xDBA2_BCCB,
+        run: || serde_json::to_vec(&color_analysis(&test_image(TEST_WIDTH, TEST_HEIGHT))).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "pixelate",
+        expected: 0x621B_DC4B,
+        run: || apply_pixelate(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, 3).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "kuwahara",
+        expected: 0x5B08_EC31,
+        run: || apply_kuwahara(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, 2, None).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "transfer_reinhard",
+        expected: 0xDCCC_BA2A,
+        run: || {
+            let mut source = test_image(TEST_WIDTH, TEST_HEIGHT);
+            source.reverse();
+            let target = test_image(TEST_WIDTH, TEST_HEIGHT);
+            transfer_color(&source, target, TEST_WIDTH, TEST_HEIGHT, TransferMethod::Reinhard).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "transfer_histogram",
+        expected: 0x49DC_24DC,
+        run: || {
+            let mut source = test_image(TEST_WIDTH, TEST_HEIGHT);
+            source.reverse();
+            let target = test_image(TEST_WIDTH, TEST_HEIGHT);
+            transfer_color(&source, target, TEST_WIDTH, TEST_HEIGHT, TransferMethod::Histogram).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "blend_modes",
+        expected: 0x5C93_3AEE,
+        run: || {
+            let mut overlay = test_image(TEST_WIDTH, TEST_HEIGHT);
+            overlay.reverse();
+            let modes = [
+                BlendMode::Normal,
+                BlendMode::Multiply,
+                BlendMode::Screen,
+                BlendMode::Overlay,
+                BlendMode::SoftLight,
+                BlendMode::HardLight,
+                BlendMode::Darken,
+                BlendMode::Lighten,
+                BlendMode::ColorDodge,
+                BlendMode::ColorBurn,
+                BlendMode::Difference,
+                BlendMode::Exclusion,
+            ];
+            modes
+                .iter()
+                .flat_map(|&mode| {
+                    let base = test_image(TEST_WIDTH, TEST_HEIGHT);
+                    blend_images(base, &overlay, TEST_WIDTH, TEST_HEIGHT, mode, 0.75).unwrap_or_default()
+                })
+                .collect()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "layer_stack",
+        expected: 0xD9A3_9356,
+        run: || {
+            let composite = || -> Result<Vec<u8>, JsValue> {
+                let mut stack = LayerStack::new(TEST_WIDTH, TEST_HEIGHT)?;
+                stack.add_layer(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT)?;
+                // A smaller layer hanging off the right edge, masked by a
+                // copy of itself
+                let sticker = test_image(TEST_WIDTH / 2, TEST_HEIGHT / 2);
+                let index = stack.add_layer(sticker.clone(), TEST_WIDTH / 2, TEST_HEIGHT / 2)?;
+                stack.set_position(index, (TEST_WIDTH * 3 / 4) as i32, -2)?;
+                stack.set_blend_mode(index, BlendMode::Screen)?;
+                stack.set_opacity(index, 0.8)?;
+                stack.set_mask(index, Some(sticker))?;
+                stack.composite()
+            };
+            composite().unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "drop_shadow",
+        expected: 0xE233_725F,
+        run: || {
+            // Clear a frame around the image so the shadow shows there
+            let mut data = test_image(TEST_WIDTH, TEST_HEIGHT);
+            let (w, h) = (TEST_WIDTH as usize, TEST_HEIGHT as usize);
+            for (index, pixel) in data.chunks_exact_mut(4).enumerate() {
+                let (x, y) = (index % w, index / w);
+                if x < w / 4 || x >= w * 3 / 4 || y < h / 4 || y >= h * 3 / 4 {
+                    pixel[3] = 0;
+                }
+            }
+            render_drop_shadow(data, TEST_WIDTH, TEST_HEIGHT, 3, 4, 5, 0x1020_40B0).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "chroma_key",
+        expected: 0xAB03_4A01,
+        // The test image turns green towards its bottom-left corner
+        run: || apply_chroma_key(test_image(TEST_WIDTH, TEST_HEIGHT), 0x00B1_40FF, 0.4, 0.3, 0.6).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "motion_blur",
+        expected: 0x80AB_CD49,
+        run: || apply_motion_blur(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, 30.0, 5, None).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "zoom_blur",
+        expected: 0x1924_B6EF,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            apply_zoom_blur(image, TEST_WIDTH, TEST_HEIGHT, 5.0, 3.0, 0.4, None).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "radial_blur",
+        expected: 0xDC0E_AB7E,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            apply_radial_blur(image, TEST_WIDTH, TEST_HEIGHT, 5.0, 3.0, 20.0, None).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "chromatic_aberration",
+        expected: 0xF0B5_0661,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            apply_chromatic_aberration(image, TEST_WIDTH, TEST_HEIGHT, 2.5).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "pixel_sort",
+        expected: 0xE1BF_2FF9,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            apply_pixel_sort(image, TEST_WIDTH, TEST_HEIGHT, 60, SortDirection::Vertical).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "channel_glitch",
+        expected: 0x00DD_EC4D,
+        run: || apply_channel_glitch(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, 4).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "halftone",
+        expected: 0xF111_65AA,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            apply_halftone(image, TEST_WIDTH, TEST_HEIGHT, 4, 45.0, HalftoneMode::Cmyk, None).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "cartoon",
+        expected: 0x647A_06C5,
+        run: || apply_cartoon(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, None).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "grain",
+        expected: 0x4364_AFA5,
+        run: || apply_grain(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, 12.0, 7, false).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "unsharp_mask",
+        expected: 0x8646_CAAC,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            apply_unsharp_mask(image, TEST_WIDTH, TEST_HEIGHT, 3, 1.5, 4, None).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "high_pass",
+        expected: 0x1B01_C9D6,
+        run: || apply_high_pass(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, 3, None).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "canny",
+        expected: 0x6F52_EDD8,
+        run: || apply_canny(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, 30.0, 90.0).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "morph_open_disk",
+        expected: 0x1E92_413C,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            morph(image, TEST_WIDTH, TEST_HEIGHT, MorphOp::Open, KernelShape::Disk, 2, None).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "inpaint",
+        expected: 0x6D1A_2FA1,
+        // Fills a 9x5 block off the centre
+        run: || {
+            let mask: Vec<u8> = (0..TEST_WIDTH * TEST_HEIGHT)
+                .flat_map(|i| {
+                    let (x, y) = (i % TEST_WIDTH, i / TEST_WIDTH);
+                    [if (6..15).contains(&x) && (5..10).contains(&y) { 255 } else { 0 }; 4]
+                })
+                .collect();
+            inpaint(test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, &mask).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "yuv_luma_blur",
+        expected: 0xC4C9_E78B,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            rgba_to_i420(&image, TEST_WIDTH, TEST_HEIGHT, YuvMatrix::Bt709, false)
+                .and_then(|frame| filter_yuv_luma(frame, TEST_WIDTH, TEST_HEIGHT, StageFilter::Blur, 2))
+                .and_then(|frame| i420_to_rgba(&frame, TEST_WIDTH, TEST_HEIGHT, YuvMatrix::Bt709, false))
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "sims")]
+    Case {
+        name: "mandelbrot",
+        expected: 0x0771_69EF,
+        run: || generate_mandelbrot(TEST_WIDTH, TEST_HEIGHT, 64, None),
+    },
+    #[cfg(feature = "sims")]
+    Case {
+        name: "fractal_tile",
+        expected: 0x3BEF_5CF5,
+        run: || render_fractal_tile(Fractal::Julia, 1, 0, 1, 16, None).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "fused_pipeline",
+        expected: 0xF6A9_65F0,
+        run: || {
+            let mut data = test_image(TEST_WIDTH, TEST_HEIGHT);
+            FilterPipeline::new()
+                .add_grayscale()
+                .add_brightness(25)
+                .add_invert()
+                .run_pixels(&mut data);
+            data
+        },
+    },
+    #[cfg(feature = "dsp")]
+    Case {
+        name: "frame_diff",
+        expected: 0x2D59_1D18,
+        run: || {
+            let mut moved = test_image(TEST_WIDTH, TEST_HEIGHT);
+            moved.rotate_left(4 * 3);
+            frame_diff(test_image(TEST_WIDTH, TEST_HEIGHT), moved, 20).map(|diff| diff.mask()).unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "overlay_text",
+        expected: 0x5EB4_7DA9,
+        run: || {
+            burn_in_overlay(test_image(64, 32), 64, 32, "WASMFX", OverlayPosition::TopLeft, Some(3_723_456.0))
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "histogram",
+        expected: 0x33FE_DD6D,
+        run: || {
+            let histogram = compute_histogram(&test_image(TEST_WIDTH, TEST_HEIGHT)).unwrap_or_default();
+            histogram.iter().flat_map(|count| count.to_le_bytes()).collect()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "dhash",
+        expected: 0x4897_5287,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            let hash = perceptual_hash(&image, TEST_WIDTH, TEST_HEIGHT, HashAlgorithm::Difference).unwrap_or_default();
+            hash.to_le_bytes().to_vec()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "phash",
+        expected: 0x490B_180B,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            let hash = perceptual_hash(&image, TEST_WIDTH, TEST_HEIGHT, HashAlgorithm::Perceptual).unwrap_or_default();
+            hash.to_le_bytes().to_vec()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "compare_images",
+        expected: 0xA89F_EEF1,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            let posterized = apply_posterize(image.clone(), 4).unwrap_or_default();
+            compare_images(&image, &posterized, TEST_WIDTH, TEST_HEIGHT)
+                .map(|comparison| [comparison.psnr(), comparison.ssim()].iter().flat_map(|v| v.to_le_bytes()).collect())
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "measure_sharpness",
+        expected: 0x1E69_83DD,
+        run: || {
+            measure_sharpness(&test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT)
+                .map(|sharpness| {
+                    let scores = [sharpness.laplacian_variance(), sharpness.tenengrad()];
+                    scores.iter().flat_map(|v| v.to_le_bytes()).collect()
+                })
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "smart_thumbnail",
+        expected: 0x8DF4_485A,
+        run: || smart_thumbnail(&test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, 8, 8).unwrap_or_default(),
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "laplacian_pyramid",
+        expected: 0xEB23_511A,
+        // Every level's pixels, then every residual rounded to a byte
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            build_pyramid(image, TEST_WIDTH, TEST_HEIGHT, 4, true)
+                .map(|pyramid| {
+                    let levels = (0..pyramid.length()).filter_map(|i| pyramid.level(i)).flat_map(|l| l.pixels());
+                    let residuals = (0..pyramid.length()).filter_map(|i| pyramid.residual(i)).flatten();
+                    levels.chain(residuals.map(|v| v.round() as i8 as u8)).collect()
+                })
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "seam_carve",
+        expected: 0x5F1D_4AFB,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            seam_carve(image, TEST_WIDTH, TEST_HEIGHT, TEST_WIDTH - 6, TEST_HEIGHT + 4, None)
+                .map(|carved| carved.pixels())
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "crop_pad",
+        expected: 0xD2FE_6F8A,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            crop(&image, TEST_WIDTH, TEST_HEIGHT, 3, 5, TEST_WIDTH / 2, TEST_HEIGHT / 3)
+                .and_then(|cropped| {
+                    let insets = Insets::new(2, 0, 5, 3);
+                    pad(&cropped.pixels(), cropped.width(), cropped.height(), insets, 0x3366_99C0)
+                })
+                .map(|padded| padded.pixels())
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "resize_bicubic",
+        expected: 0x7D66_DBC4,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            resize(image, TEST_WIDTH, TEST_HEIGHT, TEST_WIDTH * 3 / 2, TEST_HEIGHT + 5, ResizeFilter::Bicubic)
+                .map(|resized| resized.pixels())
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "resize_lanczos3",
+        expected: 0xC53D_819E,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            resize(image, TEST_WIDTH, TEST_HEIGHT, TEST_WIDTH / 3, TEST_HEIGHT / 2, ResizeFilter::Lanczos3)
+                .map(|resized| resized.pixels())
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "rotate_bicubic",
+        expected: 0x5295_53AD,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            rotate(image, TEST_WIDTH, TEST_HEIGHT, 30.0, Interpolation::Bicubic, 0x2040_60FF)
+                .map(|rotated| rotated.pixels())
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "rotate_quarter",
+        expected: 0x63CD_0D59,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            rotate(image, TEST_WIDTH, TEST_HEIGHT, -90.0, Interpolation::Bilinear, 0)
+                .map(|rotated| rotated.pixels())
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "upscale_edge_directed",
+        expected: 0x6DDD_E5FD,
+        run: || {
+            let image = test_image(TEST_WIDTH, TEST_HEIGHT);
+            upscale(image, TEST_WIDTH, TEST_HEIGHT, 2, UpscaleMethod::EdgeDirected)
+                .map(|upscaled| upscaled.pixels())
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "filters-core")]
+    Case {
+        name: "upscale_pixel_art",
+        expected: 0xBC9F_4DB4,
+        // Posterized first so neighbouring colours actually match
+        run: || {
+            let image = apply_posterize(test_image(TEST_WIDTH, TEST_HEIGHT), 2).unwrap_or_default();
+            upscale(image, TEST_WIDTH, TEST_HEIGHT, 4, UpscaleMethod::PixelArt)
+                .map(|upscaled| upscaled.pixels())
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "codecs")]
+    Case {
+        name: "blurhash",
+        expected: 0xA4DB_9482,
+        run: || {
+            encode_blurhash(&test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT, 4, 3)
+                .map(String::into_bytes)
+                .unwrap_or_default()
+        },
+    },
+    #[cfg(feature = "codecs")]
+    Case {
+        name: "thumbhash",
+        expected: 0x6C68_4104,
+        run: || encode_thumbhash(&test_image(TEST_WIDTH, TEST_HEIGHT), TEST_WIDTH, TEST_HEIGHT).unwrap_or_default(),
+    },
+];
+
+#[derive(Serialize)]
+struct CaseResult {
+    name: &'static str,
+    passed: bool,
+    expected: String,
+    actual: String,
+}
+
+#[derive(Serialize)]
+struct Report {
+    passed: bool,
+    simd: bool,
+    threads: usize,
+    results: Vec<CaseResult>,
+}
+
+// --- Self-Test ---
+// Returns { passed, simd, threads, results: [{ name, passed, expected, actual }] }
+#[wasm_bindgen]
+pub fn self_test() -> JsValue {
+    console_log!("Rust (WASM): Self-test started...");
+
+    let (quality, math_mode) = (get_quality(), get_math_mode());
+    set_quality(QualityTier::High);
+    set_math_mode(MathMode::Exact);
+    let results: Vec<CaseResult> = power::unthrottled(run_cases)
+        .into_iter()
+        .map(|(case, actual)| CaseResult {
+            name: case.name,
+            passed: actual == case.expected,
+            expected: format!("{:08x}", case.expected),
+            actual: format!("{:08x}", actual),
+        })
+        .collect();
+    set_quality(quality);
+    set_math_mode(math_mode);
+
+    let report = Report {
+        passed: results.iter().all(|r| r.passed),
+        simd: simd::simd_build() && settings::simd_enabled(),
+        threads: parallel::pool_size(),
+        results,
+    };
+    console_log!("Rust (WASM): Self-test finished ({}).", if report.passed { "pass" } else { "FAIL" });
+
+    serde_json::to_string(&report)
+        .ok()
+        .and_then(|json| js_sys::JSON::parse(&json).ok())
+        .unwrap_or(JsValue::NULL)
+}
+
+fn run_cases() -> Vec<(&'static Case, u32)> {
+    CASES.iter().map(|case| (case, checksum(&(case.run)()))).collect()
+}
+
+// Smooth gradients plus a hard-edged square and some per-pixel noise, so
+// both the neighborhood filters and the per-pixel ones have work to do
+fn test_image(width: u32, height: u32) -> Vec<u8> {
+    let (w, h) = (width as usize, height as usize);
+    let mut data = vec![0u8; w * h * 4];
+    let mut seed = 0x9E37_79B9u32;
+    for y in 0..h {
+        for x in 0..w {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            let noise = (seed >> 24) as usize & 31;
+            let inside = x > w / 4 && x < w * 3 / 4 && y > h / 4 && y < h * 3 / 4;
+            let idx = (y * w + x) * 4;
+            data[idx] = ((x * 255 / w.max(1)) ^ noise) as u8;
+            data[idx + 1] = ((y * 255 / h.max(1)) + noise) as u8;
+            data[idx + 2] = if inside { 230 } else { 20 + noise as u8 };
+            data[idx + 3] = 255 - (noise as u8 & 7);
+        }
+    }
+    data
+}
+
+// The test image as linear floats reaching 4x diffuse white, for the HDR
+// entry points
+#[cfg(feature = "filters-core")]
+fn test_hdr_image() -> Vec<f32> {
+    test_image(TEST_WIDTH, TEST_HEIGHT)
+        .chunks_exact(4)
+        .flat_map(|p| [p[0] as f32 / 64.0, p[1] as f32 / 64.0, p[2] as f32 / 64.0, p[3] as f32 / 255.0])
+        .collect()
+}
+
+// A 5-point .cube grade: warmer, with an S-curve on the blue channel
+#[cfg(feature = "filters-core")]
+fn test_cube_lut() -> String {
+    let mut text = String::from("TITLE \"selftest\"\nLUT_3D_SIZE 5\n");
+    for b in 0..5 {
+        for g in 0..5 {
+            for r in 0..5 {
+                let [r, g, b] = [r, g, b].map(|v| v as f32 / 4.0);
+                let blue = b * b * (3.0 - 2.0 * b);
+                text += &format!("{:.6} {:.6} {:.6}\n", (r * 1.1).min(1.0), g, blue * 0.9);
+            }
+        }
+    }
+    text
+}
+
+// FNV-1a
+fn checksum(data: &[u8]) -> u32 {
+    data.iter()
+        .fold(0x811C_9DC5u32, |hash, &byte| (hash ^ byte as u32).wrapping_mul(0x0100_0193))
+}
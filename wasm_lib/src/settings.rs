@@ -0,0 +1,207 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering};
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+// ========================================================================
+// GLOBAL SETTINGS
+// Process-wide knobs that filters consult at call time, so hosts can tune
+// behaviour once instead of threading extra parameters through every call.
+// ========================================================================
+
+// --- Quality Tiers ---
+// Lets real-time paths trade accuracy for speed on slow devices. `High` is
+// the default and always gives the full-accuracy result; lower tiers let
+// filters take shortcuts such as skipping blur taps or using smaller
+// tracking templates.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityTier {
+    Low = 0,
+    Balanced = 1,
+    High = 2,
+}
+
+static QUALITY: AtomicU8 = AtomicU8::new(QualityTier::High as u8);
+
+#[wasm_bindgen]
+pub fn set_quality(tier: QualityTier) {
+    QUALITY.store(tier as u8, Ordering::Relaxed);
+}
+
+#[wasm_bindgen]
+pub fn get_quality() -> QualityTier {
+    match QUALITY.load(Ordering::Relaxed) {
+        0 => QualityTier::Low,
+        1 => QualityTier::Balanced,
+        _ => QualityTier::High,
+    }
+}
+
+// The tier filters actually run at: `get_quality()`, lowered while
+// `set_power_state` reports the device on battery or the tab hidden
+pub(crate) fn effective_quality() -> QualityTier {
+    crate::power::quality_cap(get_quality())
+}
+
+// --- Math Mode ---
+// `Exact` (the default) keeps every filter on its reference path: integer
+// arithmetic, or f32 operations in a fixed order that every wasm engine
+// evaluates identically, so the same input gives bit-identical output on
+// any device, thread count or SIMD build (safe for caching and diffing).
+// `Fast` lets filters swap in approximations, lookup tables and reordered
+// sums where those are quicker; results can then differ by a step or so
+// from `Exact`, though they're still repeatable on one binary.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MathMode {
+    Exact = 0,
+    Fast = 1,
+}
+
+static MATH_MODE: AtomicU8 = AtomicU8::new(MathMode::Exact as u8);
+
+#[wasm_bindgen]
+pub fn set_math_mode(mode: MathMode) {
+    MATH_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+#[wasm_bindgen]
+pub fn get_math_mode() -> MathMode {
+    match MATH_MODE.load(Ordering::Relaxed) {
+        1 => MathMode::Fast,
+        _ => MathMode::Exact,
+    }
+}
+
+pub(crate) fn fast_math() -> bool {
+    get_math_mode() == MathMode::Fast
+}
+
+// --- Performance Tuning ---
+// Device-specific values picked by `autotune()` (or restored from a saved
+// tuning blob). Filters read them through the accessors below.
+pub(crate) const DEFAULT_TILE_SIZE: u32 = 64;
+
+static TILE_SIZE: AtomicU32 = AtomicU32::new(DEFAULT_TILE_SIZE);
+static THREAD_COUNT: AtomicU32 = AtomicU32::new(1);
+// SIMD builds start with their vector kernels on
+static SIMD_ENABLED: AtomicBool = AtomicBool::new(cfg!(feature = "simd"));
+static TUNED: AtomicBool = AtomicBool::new(false);
+
+// Width in pixels of the column strips / tiles cache-blocked filters work on
+pub(crate) fn tile_size() -> usize {
+    TILE_SIZE.load(Ordering::Relaxed).max(1) as usize
+}
+
+pub(crate) fn thread_count() -> u32 {
+    THREAD_COUNT.load(Ordering::Relaxed).max(1)
+}
+
+pub(crate) fn set_thread_count(count: u32) {
+    THREAD_COUNT.store(count.max(1), Ordering::Relaxed);
+}
+
+pub(crate) fn simd_enabled() -> bool {
+    SIMD_ENABLED.load(Ordering::Relaxed)
+}
+
+pub(crate) fn set_simd_enabled(enabled: bool) {
+    SIMD_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn is_tuned() -> bool {
+    TUNED.load(Ordering::Relaxed)
+}
+
+pub(crate) fn apply_tuning(tile_size: u32, thread_count: u32, simd: bool) {
+    TILE_SIZE.store(tile_size.max(1), Ordering::Relaxed);
+    THREAD_COUNT.store(thread_count.max(1), Ordering::Relaxed);
+    SIMD_ENABLED.store(simd, Ordering::Relaxed);
+    TUNED.store(true, Ordering::Relaxed);
+}
+
+// --- Logging ---
+// `console_log!` messages are Info; `init()` can turn them down or off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Off = 0,
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+}
+
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+pub(crate) fn log_level() -> LogLevel {
+    match LOG_LEVEL.load(Ordering::Relaxed) {
+        0 => LogLevel::Off,
+        1 => LogLevel::Error,
+        2 => LogLevel::Warn,
+        3 => LogLevel::Info,
+        _ => LogLevel::Debug,
+    }
+}
+
+pub(crate) fn set_log_level(level: LogLevel) {
+    LOG_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub(crate) fn log_enabled(level: LogLevel) -> bool {
+    level <= log_level()
+}
+
+// --- Memory Limit ---
+// Upper bound on a single image buffer, so an oversized canvas fails with
+// an error instead of growing the heap until the tab is killed. 0 means no
+// limit (the default).
+static MAX_IMAGE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+pub(crate) fn max_image_bytes() -> Option<usize> {
+    match MAX_IMAGE_BYTES.load(Ordering::Relaxed) {
+        0 => None,
+        limit => Some(limit),
+    }
+}
+
+pub(crate) fn set_max_image_bytes(limit: Option<usize>) {
+    MAX_IMAGE_BYTES.store(limit.unwrap_or(0), Ordering::Relaxed);
+}
+
+// --- Memory Ceiling ---
+// Most working memory (see `memory.rs`) a heavy filter may need; over it
+// the call throws before allocating. 0 means no ceiling (the default).
+static MEMORY_CEILING: AtomicU64 = AtomicU64::new(0);
+
+pub(crate) fn memory_ceiling() -> Option<u64> {
+    match MEMORY_CEILING.load(Ordering::Relaxed) {
+        0 => None,
+        ceiling => Some(ceiling),
+    }
+}
+
+pub(crate) fn set_memory_ceiling(ceiling: Option<u64>) {
+    MEMORY_CEILING.store(ceiling.unwrap_or(0), Ordering::Relaxed);
+}
+
+// --- Random Seed ---
+// See `random.rs`. `set_seed(undefined)` goes back to the per-feature
+// default sequences.
+static SEED: AtomicU64 = AtomicU64::new(0);
+static SEEDED: AtomicBool = AtomicBool::new(false);
+
+#[wasm_bindgen]
+pub fn set_seed(seed: Option<u32>) {
+    SEED.store(seed.unwrap_or(0) as u64, Ordering::Relaxed);
+    SEEDED.store(seed.is_some(), Ordering::Relaxed);
+}
+
+pub(crate) fn seed() -> Option<u32> {
+    SEEDED
+        .load(Ordering::Relaxed)
+        .then(|| SEED.load(Ordering::Relaxed) as u32)
+}
@@ -0,0 +1,126 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_float_range, check_range, image_len};
+use crate::text::{draw_text, measure_text};
+
+// ========================================================================
+// TEST SIGNALS
+// Synthetic inputs for checking pipelines built on this crate.
+// ========================================================================
+
+const BEEP_HZ: f64 = 1000.0;
+const BEEP_AMPLITUDE: f64 = 0.5;
+const MIN_SAMPLE_RATE: u32 = 8000;
+const MAX_SAMPLE_RATE: u32 = 384_000;
+const MIN_FPS: u32 = 1;
+const MAX_FPS: u32 = 240;
+
+// One frame of the A/V sync test: the RGBA picture plus the mono audio
+// samples that play during it
+#[wasm_bindgen]
+pub struct AvSyncFrame {
+    video: Vec<u8>,
+    audio: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl AvSyncFrame {
+    #[wasm_bindgen(getter)]
+    pub fn video(&self) -> Vec<u8> {
+        self.video.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn audio(&self) -> Vec<f32> {
+        self.audio.clone()
+    }
+}
+
+// --- A/V Sync Test Pattern ---
+// Once per second the picture flashes white for exactly one frame, and a
+// 1 kHz beep lasting one frame starts at the same instant, so any offset
+// between seeing the flash and hearing the beep is pipeline latency. The
+// frame also shows its index and a bar sweeping across the second.
+//
+// Audio is generated from the absolute sample position, so consecutive
+// frames concatenate into a click-free stream. `sample_rate` is in Hz
+// (8000..384000) and `fps` may be fractional (1..240, e.g. 29.97).
+#[wasm_bindgen]
+pub fn generate_av_sync_test(
+    width: u32,
+    height: u32,
+    frame_index: u32,
+    sample_rate: u32,
+    fps: f32,
+) -> Result<AvSyncFrame, JsValue> {
+    image_len(width, height)?;
+    check_range("sample_rate", sample_rate, MIN_SAMPLE_RATE, MAX_SAMPLE_RATE)?;
+    check_float_range("fps", fps, MIN_FPS, MAX_FPS)?;
+    let width = width as usize;
+    let height = height as usize;
+    let fps = fps as f64;
+    let frames_per_second = fps.round().max(1.0) as u32;
+    let frame_in_second = frame_index % frames_per_second;
+
+    // Both the flash and the beep are keyed off the sample clock, so they
+    // stay locked together even when fps doesn't divide the sample rate:
+    // the flash frame is the one during which a new second starts
+    let rate = sample_rate as f64;
+    let samples_per_frame = rate / fps;
+    let first_sample = (frame_index as f64 * samples_per_frame).round() as u64;
+    let next_frame_sample = ((frame_index as f64 + 1.0) * samples_per_frame).round() as u64;
+    let samples_per_second = rate.round() as u64;
+    let beep_samples = samples_per_frame.round() as u64;
+    let next_second_start = first_sample.div_ceil(samples_per_second) * samples_per_second;
+    let is_flash = next_second_start < next_frame_sample;
+
+    // --- Video ---
+    let background = if is_flash { 255 } else { 24 };
+    let mut video = vec![background; width * height * 4];
+    for pixel in video.chunks_exact_mut(4) {
+        pixel[3] = 255;
+    }
+
+    // Progress bar along the bottom, one segment per frame of the second
+    let bar_height = (height / 20).max(2).min(height);
+    let filled = width * (frame_in_second as usize + 1) / frames_per_second as usize;
+    for y in height - bar_height..height {
+        for x in 0..filled.min(width) {
+            let idx = (y * width + x) * 4;
+            video[idx] = 0;
+            video[idx + 1] = 200;
+            video[idx + 2] = 80;
+        }
+    }
+
+    let label = format!("FRAME {}", frame_index);
+    let scale = (height / 120).clamp(1, 8);
+    let (text_w, text_h) = measure_text(&label, scale);
+    let text_color = if is_flash { [0, 0, 0, 255] } else { [255, 255, 255, 255] };
+    draw_text(
+        &mut video,
+        width,
+        height,
+        (
+            (width as i32 - text_w as i32) / 2,
+            (height as i32 - text_h as i32) / 2,
+        ),
+        &label,
+        scale,
+        text_color,
+    );
+
+    // --- Audio ---
+    let audio = (first_sample..next_frame_sample)
+        .map(|sample| {
+            if sample % samples_per_second < beep_samples {
+                let t = sample as f64 / rate;
+                (BEEP_AMPLITUDE * (2.0 * std::f64::consts::PI * BEEP_HZ * t).sin()) as f32
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    Ok(AvSyncFrame { video, audio })
+}
@@ -0,0 +1,89 @@
+use wasm_bindgen::prelude::*;
+
+// ========================================================================
+// SIMD128
+// With the `simd` cargo feature, grayscale and invert process four RGBA
+// pixels per v128 instruction. The SIMD build won't even validate on
+// engines without SIMD support, so hosts ship two builds and call
+// `simd_supported()` from the plain one to decide which to load:
+//
+//   wasm-pack build --target bundler
+//   wasm-pack build --target bundler --out-dir pkg-simd -- --features simd
+// ========================================================================
+
+// Smallest module using a SIMD instruction (i8x16.splat + i8x16.popcnt);
+// it only validates on engines that implement SIMD128
+const SIMD_PROBE_MODULE: [u8; 31] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7b, 0x03,
+    0x02, 0x01, 0x00, 0x0a, 0x0a, 0x01, 0x08, 0x00, 0x41, 0x00, 0xfd, 0x0f, 0xfd, 0x62, 0x0b,
+];
+
+// Whether the running engine can load the SIMD build
+#[wasm_bindgen]
+pub fn simd_supported() -> bool {
+    let probe = js_sys::Uint8Array::from(&SIMD_PROBE_MODULE[..]);
+    js_sys::WebAssembly::validate(&probe).unwrap_or(false)
+}
+
+// Whether this binary was compiled with the SIMD code paths
+#[wasm_bindgen]
+pub fn simd_build() -> bool {
+    cfg!(all(feature = "simd", target_arch = "wasm32"))
+}
+
+#[cfg(all(feature = "simd", feature = "filters-core", target_arch = "wasm32"))]
+mod kernels {
+    use core::arch::wasm32::*;
+
+    // Same integer luminance as the scalar filter. Each u32 lane holds one
+    // little-endian RGBA pixel; the weighted sum is an exact integer below
+    // 2^24, so dividing it in f32 and truncating matches `sum / 1000`
+    // bit for bit.
+    #[target_feature(enable = "simd128")]
+    pub(crate) unsafe fn grayscale(image_data: &mut [u8]) {
+        let byte_mask = u32x4_splat(0xFF);
+        let alpha_mask = u32x4_splat(0xFF00_0000);
+        let r_weight = u32x4_splat(299);
+        let g_weight = u32x4_splat(587);
+        let b_weight = u32x4_splat(114);
+        let divisor = f32x4_splat(1000.0);
+
+        let mut chunks = image_data.chunks_exact_mut(16);
+        for chunk in &mut chunks {
+            let pixels = v128_load(chunk.as_ptr() as *const v128);
+
+            let r = v128_and(pixels, byte_mask);
+            let g = v128_and(u32x4_shr(pixels, 8), byte_mask);
+            let b = v128_and(u32x4_shr(pixels, 16), byte_mask);
+            let sum = i32x4_add(
+                i32x4_add(i32x4_mul(r, r_weight), i32x4_mul(g, g_weight)),
+                i32x4_mul(b, b_weight),
+            );
+            let gray = u32x4_trunc_sat_f32x4(f32x4_div(f32x4_convert_u32x4(sum), divisor));
+
+            let rgb = v128_or(v128_or(gray, u32x4_shl(gray, 8)), u32x4_shl(gray, 16));
+            let result = v128_or(rgb, v128_and(pixels, alpha_mask));
+            v128_store(chunk.as_mut_ptr() as *mut v128, result);
+        }
+
+        // Fewer than four pixels left over
+        crate::grayscale_scalar(chunks.into_remainder());
+    }
+
+    // XOR with 0xFF flips R, G and B (255 - x) and leaves alpha untouched
+    #[target_feature(enable = "simd128")]
+    pub(crate) unsafe fn invert(image_data: &mut [u8]) {
+        let rgb_mask = u32x4_splat(0x00FF_FFFF);
+
+        let mut chunks = image_data.chunks_exact_mut(16);
+        for chunk in &mut chunks {
+            let pixels = v128_load(chunk.as_ptr() as *const v128);
+            v128_store(chunk.as_mut_ptr() as *mut v128, v128_xor(pixels, rgb_mask));
+        }
+
+        crate::invert_scalar(chunks.into_remainder());
+    }
+}
+
+#[cfg(all(feature = "simd", feature = "filters-core", target_arch = "wasm32"))]
+pub(crate) use kernels::{grayscale, invert};
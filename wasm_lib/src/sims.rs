@@ -0,0 +1,91 @@
+use wasm_bindgen::prelude::*;
+use wasmfx_core::fractal::{self, mandelbrot_row};
+
+use crate::api::FractalParams;
+use crate::cancel::{cancelled, CancellationToken};
+use crate::error::{check_range, image_len, Error};
+
+// ========================================================================
+// SIMULATIONS (`sims` feature)
+// Images generated from scratch rather than filtered.
+// ========================================================================
+
+// --- Demo 5: Mandelbrot Set (PURE COMPUTATION - WASM DOMINATES!) ---
+// This generates a fractal image from scratch using pure math
+// No input image needed - we're generating pixel values computationally.
+// The per-pixel work is `wasmfx_core::fractal::mandelbrot_row`.
+// If cancelled, rows not reached yet are left transparent.
+#[wasm_bindgen]
+pub fn generate_mandelbrot(width: u32, height: u32, max_iterations: u32, token: Option<CancellationToken>) -> Vec<u8> {
+    console_log!("Rust (WASM): Mandelbrot generation started...");
+    
+    let width = width as usize;
+    let height = height as usize;
+    let mut result = vec![0u8; width * height * 4];
+    for (y, row) in result.chunks_exact_mut((width * 4).max(1)).enumerate() {
+        if cancelled(token.as_ref()) {
+            break;
+        }
+        mandelbrot_row(row, y, width, height, max_iterations as usize);
+    }
+    
+    console_log!("Rust (WASM): Mandelbrot generation finished.");
+    result
+}
+
+// --- Fractal Map Tiles ---
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fractal {
+    Mandelbrot,
+    Julia,
+}
+
+// Deepest zoom before f64 runs out of precision for 4096-pixel tiles
+const MAX_ZOOM: u32 = 40;
+const MAX_TILE_SIZE: u32 = 4096;
+
+// Renders one slippy-map tile, so fractal explorers can sit on top of a
+// Leaflet / OpenLayers style tile layer and cache tiles on the JS side:
+//
+//   L.GridLayer.extend({ createTile(coords) {
+//     const rgba = render_fractal_tile(Fractal.Mandelbrot, coords.x, coords.y, coords.z, 256, params);
+//     ...draw into a canvas...
+//   }})
+//
+// At `zoom_level` z the world is 2^z tiles across; tile (0, 0) is the top
+// left. `params` sets the iteration count and the Julia constant (the
+// default is plain 256-iteration tiles). A tile always comes out the same,
+// so caching by (fractal, x, y, z, size, params) is safe.
+#[wasm_bindgen]
+pub fn render_fractal_tile(
+    fractal: Fractal,
+    tile_x: u32,
+    tile_y: u32,
+    zoom_level: u32,
+    tile_size: u32,
+    params: Option<FractalParams>,
+) -> Result<Vec<u8>, JsValue> {
+    check_range("zoom_level", zoom_level, 0, MAX_ZOOM)?;
+    let last_tile = (1u32 << zoom_level) - 1;
+    check_range("tile_x", tile_x, 0, last_tile)?;
+    check_range("tile_y", tile_y, 0, last_tile)?;
+    check_range("tile_size", tile_size, 1, MAX_TILE_SIZE)?;
+    image_len(tile_size, tile_size)?;
+    let params = params.unwrap_or_default();
+    if params.max_iterations == 0 {
+        return Err(Error::NotPositive { name: "max_iterations" }.into());
+    }
+    let fractal = match fractal {
+        Fractal::Mandelbrot => fractal::Fractal::Mandelbrot,
+        Fractal::Julia => fractal::Fractal::Julia { c: (params.c_re, params.c_im) },
+    };
+    Ok(fractal::render_tile(
+        fractal,
+        tile_x,
+        tile_y,
+        zoom_level,
+        tile_size as usize,
+        params.max_iterations as usize,
+    ))
+}
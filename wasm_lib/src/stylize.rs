@@ -0,0 +1,511 @@
+use wasm_bindgen::prelude::*;
+use wasmfx_core::filters::sobel_gradients;
+use wasmfx_core::random::Lcg;
+
+use crate::adjust::posterize_lut;
+use crate::binary::luma;
+use crate::blur_effects::sample;
+use crate::cancel::CancellationToken;
+use crate::denoise::{bilateral_pixels, for_each_output_row};
+use crate::error::{check_finite, check_image, check_positive, check_radius, Error};
+use crate::memory::check_memory;
+use crate::pixels::{pixels, pixels_mut, rows_mut, Pixel};
+use crate::random;
+
+// ========================================================================
+// STYLIZE
+// Effects that deliberately throw detail away for a look (or to hide
+// something) rather than to clean the image up.
+// ========================================================================
+
+// --- Pixelate ---
+// Fills each `block_size` x `block_size` block, counted from the top-left
+// corner, with its average colour; blocks cut off by the right and bottom
+// edges average the pixels they have. Colours are weighted by alpha so
+// transparent pixels don't bleed into the block, which is what redaction
+// (blurring out faces, plates) needs.
+#[wasm_bindgen]
+pub fn apply_pixelate(mut image_data: Vec<u8>, width: u32, height: u32, block_size: u32) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    if block_size == 0 {
+        return Err(Error::NotPositive { name: "block_size" }.into());
+    }
+    console_log!("Rust (WASM): Pixelate started...");
+    // A block wider than the image is one block either way, and keeps
+    // the band size from overflowing
+    let block = block_size.min(width.max(height)).max(1) as usize;
+    let w = width as usize;
+    let blocks_across = w.div_ceil(block);
+    for band in image_data.chunks_mut(w * 4 * block) {
+        // Premultiplied colour, alpha and pixel count per block
+        let mut sums = vec![[0u64; 5]; blocks_across];
+        for (_, row) in rows_mut(band, w) {
+            for (x, pixel) in row.iter().enumerate() {
+                let sum = &mut sums[x / block];
+                let alpha = pixel[3] as u64;
+                for c in 0..3 {
+                    sum[c] += pixel[c] as u64 * alpha;
+                }
+                sum[3] += alpha;
+                sum[4] += 1;
+            }
+        }
+
+        let averages: Vec<[u8; 4]> = sums
+            .iter()
+            .map(|&[r, g, b, alpha, count]| {
+                if alpha == 0 {
+                    return [0; 4];
+                }
+                let channel = |sum: u64| ((sum + alpha / 2) / alpha) as u8;
+                [channel(r), channel(g), channel(b), ((alpha + count / 2) / count) as u8]
+            })
+            .collect();
+        for (_, row) in rows_mut(band, w) {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = averages[x / block];
+            }
+        }
+    }
+    console_log!("Rust (WASM): Pixelate finished.");
+    Ok(image_data)
+}
+
+// --- Kuwahara ---
+// The classic painterly filter: each pixel looks at the four
+// (radius + 1) x (radius + 1) quadrants that have it as a corner and takes
+// the mean colour of the one with the lowest variance (summed over R, G
+// and B). Flat regions smear into brush-stroke patches while edges stay
+// crisp, since the quadrant on the far side of an edge is never the
+// calmest. Summed-area tables make every quadrant O(1), so the cost
+// doesn't grow with the radius. Alpha is left as it is; if cancelled,
+// rows not reached yet keep their original pixels.
+#[wasm_bindgen]
+pub fn apply_kuwahara(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    radius: u32,
+    token: Option<CancellationToken>,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_radius(radius)?;
+    check_memory("kuwahara", width, height)?;
+    console_log!("Rust (WASM): Kuwahara filter started...");
+    let (w, h, r) = (width as usize, height as usize, radius as usize);
+    let table = SummedArea::new(pixels(&image_data), w, h);
+    let mut result = image_data.clone();
+    for_each_output_row(&mut result, w, h, token.as_ref(), |y, out_row| {
+        let (top, bottom) = (y.saturating_sub(r), (y + r).min(h - 1));
+        for (x, out) in out_row.iter_mut().enumerate() {
+            let (left, right) = (x.saturating_sub(r), (x + r).min(w - 1));
+            let quadrants = [
+                (left, top, x, y),
+                (x, top, right, y),
+                (left, y, x, bottom),
+                (x, y, right, bottom),
+            ];
+            let mut best = (f64::MAX, [0u8; 3]);
+            for (x0, y0, x1, y1) in quadrants {
+                let sums = table.rect(x0, y0, x1 + 1, y1 + 1);
+                let count = ((x1 + 1 - x0) * (y1 + 1 - y0)) as f64;
+                let mut variance = 0.0;
+                let mut mean = [0u8; 3];
+                for c in 0..3 {
+                    let m = sums[c] as f64 / count;
+                    variance += sums[c + 3] as f64 / count - m * m;
+                    mean[c] = (m + 0.5) as u8;
+                }
+                if variance < best.0 {
+                    best = (variance, mean);
+                }
+            }
+            out[..3].copy_from_slice(&best.1);
+        }
+    });
+    console_log!("Rust (WASM): Kuwahara filter finished.");
+    Ok(result)
+}
+
+// Running sums of R, G, B and of their squares, with a zero row and column
+// in front so any rectangle is four lookups
+struct SummedArea {
+    stride: usize,
+    sums: Vec<[u64; 6]>,
+}
+
+impl SummedArea {
+    fn new(source: &[Pixel], width: usize, height: usize) -> SummedArea {
+        let stride = width + 1;
+        let mut sums = vec![[0u64; 6]; stride * (height + 1)];
+        for y in 0..height {
+            let mut row = [0u64; 6];
+            for x in 0..width {
+                let pixel = &source[y * width + x];
+                for c in 0..3 {
+                    let value = pixel[c] as u64;
+                    row[c] += value;
+                    row[c + 3] += value * value;
+                }
+                let above = sums[y * stride + x + 1];
+                sums[(y + 1) * stride + x + 1] = [0, 1, 2, 3, 4, 5].map(|i| above[i] + row[i]);
+            }
+        }
+        SummedArea { stride, sums }
+    }
+
+    // Sums over columns x0..x1 and rows y0..y1 (exclusive ends)
+    fn rect(&self, x0: usize, y0: usize, x1: usize, y1: usize) -> [u64; 6] {
+        let at = |x: usize, y: usize| self.sums[y * self.stride + x];
+        let (a, b, c, d) = (at(x1, y1), at(x0, y0), at(x1, y0), at(x0, y1));
+        [0, 1, 2, 3, 4, 5].map(|i| a[i] + b[i] - c[i] - d[i])
+    }
+}
+
+// --- Chromatic Aberration ---
+// Imitates a cheap lens that focuses each colour at a slightly different
+// size: red is pushed away from the image centre and blue pulled towards
+// it, by `shift_px` pixels at the corners and proportionally less further
+// in, so the centre stays clean and edges get colour fringes. A negative
+// shift swaps the two. Green and alpha are left as they are.
+#[wasm_bindgen]
+pub fn apply_chromatic_aberration(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    shift_px: f32,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_finite("shift_px", shift_px)?;
+    check_memory("chromatic_aberration", width, height)?;
+    console_log!("Rust (WASM): Chromatic aberration started...");
+    let (w, h) = (width as usize, height as usize);
+    let (cx, cy) = ((w - 1) as f32 / 2.0, (h - 1) as f32 / 2.0);
+    // Scale from distance to shift, so the corners move by `shift_px`
+    let scale = shift_px / cx.hypot(cy).max(1.0);
+
+    let mut result = image_data.clone();
+    let source = pixels(&image_data);
+    for (i, pixel) in pixels_mut(&mut result).iter_mut().enumerate() {
+        let (x, y) = ((i % w) as f32, (i / w) as f32);
+        let (dx, dy) = ((x - cx) * scale, (y - cy) * scale);
+        pixel[0] = (sample(source, w, h, x - dx, y - dy)[0] + 0.5) as u8;
+        pixel[2] = (sample(source, w, h, x + dx, y + dy)[2] + 0.5) as u8;
+    }
+    console_log!("Rust (WASM): Chromatic aberration finished.");
+    Ok(result)
+}
+
+// --- Pixel Sort ---
+// Which way `apply_pixel_sort` runs its spans
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortDirection {
+    Horizontal,
+    Vertical,
+}
+
+// The databending look: every unbroken span of pixels brighter than
+// `threshold` along a row (Horizontal) or column (Vertical) is sorted from
+// dark to bright, dragging the highlights into streaks while everything
+// at or below the threshold stays put. 0 sorts everything but the
+// blackest pixels.
+#[wasm_bindgen]
+pub fn apply_pixel_sort(
+    mut image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    threshold: u8,
+    direction: SortDirection,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    console_log!("Rust (WASM): Pixel sort started...");
+    let (w, h) = (width as usize, height as usize);
+    let image = pixels_mut(&mut image_data);
+    let (lines, length) = match direction {
+        SortDirection::Horizontal => (h, w),
+        SortDirection::Vertical => (w, h),
+    };
+    let index = |line: usize, i: usize| match direction {
+        SortDirection::Horizontal => line * w + i,
+        SortDirection::Vertical => i * w + line,
+    };
+    let mut span: Vec<Pixel> = Vec::with_capacity(length);
+    for line in 0..lines {
+        let mut i = 0;
+        while i < length {
+            if luma(&image[index(line, i)]) <= threshold {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            span.clear();
+            while i < length && luma(&image[index(line, i)]) > threshold {
+                span.push(image[index(line, i)]);
+                i += 1;
+            }
+            span.sort_by_key(luma);
+            for (offset, pixel) in span.iter().enumerate() {
+                image[index(line, start + offset)] = *pixel;
+            }
+        }
+    }
+    console_log!("Rust (WASM): Pixel sort finished.");
+    Ok(image_data)
+}
+
+// --- Channel Glitch ---
+// The other half of the glitch look: the image is cut into horizontal
+// bands of random height, and in each band the red and blue channels slide
+// sideways by random amounts of up to `max_shift` pixels (wrapping around),
+// like a corrupted video signal. Bands and shifts come from
+// `random::stream_seed`, so the same input always glitches the same way.
+#[wasm_bindgen]
+pub fn apply_channel_glitch(
+    mut image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    max_shift: u32,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_memory("channel_glitch", width, height)?;
+    console_log!("Rust (WASM): Channel glitch started...");
+    let (w, h) = (width as usize, height as usize);
+    let mut rng = Lcg::new(random::stream_seed(0x6c17_c4e5));
+    let max_band = (h / 8).max(1);
+    // Shifts wrap around, so a whole width already covers every one
+    let max_shift = max_shift.min(width) as usize;
+    let span = 2 * max_shift + 1;
+    let source = image_data.clone();
+    let source = pixels(&source);
+    let image = pixels_mut(&mut image_data);
+    let mut y = 0;
+    while y < h {
+        let band = 1 + (rng.next_u32() >> 8) as usize % max_band;
+        // Shift in -max_shift..=max_shift, kept as an offset in 0..w
+        let mut offset = || ((rng.next_u32() >> 8) as usize % span + w - max_shift) % w;
+        let (red, blue) = (offset(), offset());
+        for row in y..(y + band).min(h) {
+            for x in 0..w {
+                let pixel = &mut image[row * w + x];
+                pixel[0] = source[row * w + (x + red) % w][0];
+                pixel[2] = source[row * w + (x + blue) % w][2];
+            }
+        }
+        y += band;
+    }
+    console_log!("Rust (WASM): Channel glitch finished.");
+    Ok(image_data)
+}
+
+// --- Halftone ---
+// Which inks `apply_halftone` prints with
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HalftoneMode {
+    // Black dots on white
+    Grayscale,
+    // Cyan, magenta, yellow and black dots, each on its own screen angle
+    Cmyk,
+}
+
+// Screen angles of the C, M, Y and K inks relative to K, the classic
+// 15 / 75 / 0 / 45 degree set that keeps the screens from forming moire
+const CMYK_SCREENS: [f32; 4] = [-30.0, 30.0, -45.0, 0.0];
+
+// Reprints the image the way a press does: a grid of `dot_size` pixel
+// cells rotated by `angle_degrees` (45 is traditional), with one dot per
+// cell per ink whose size follows the ink the image needs there. Dot edges
+// are antialiased; alpha is kept.
+#[wasm_bindgen]
+pub fn apply_halftone(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    dot_size: u32,
+    angle_degrees: f32,
+    mode: HalftoneMode,
+    token: Option<CancellationToken>,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    if dot_size == 0 {
+        return Err(Error::NotPositive { name: "dot_size" }.into());
+    }
+    check_finite("angle_degrees", angle_degrees)?;
+    check_memory("halftone", width, height)?;
+    console_log!("Rust (WASM): Halftone started...");
+    let (w, h) = (width as usize, height as usize);
+    let screen = |offset: f32| Screen::new(dot_size as f32, angle_degrees + offset);
+    let [cyan, magenta, yellow, black] = CMYK_SCREENS.map(screen);
+
+    let source = pixels(&image_data);
+    let mut result = image_data.clone();
+    for_each_output_row(&mut result, w, h, token.as_ref(), |y, out_row| {
+        for (x, out) in out_row.iter_mut().enumerate() {
+            let (x, y) = (x as f32, y as f32);
+            let paper = match mode {
+                HalftoneMode::Grayscale => {
+                    let k = black.coverage(source, w, h, x, y, |rgb| 1.0 - gray(rgb));
+                    [1.0 - k; 3]
+                }
+                HalftoneMode::Cmyk => {
+                    let k = black.coverage(source, w, h, x, y, |rgb| cmyk(rgb)[3]);
+                    let c = cyan.coverage(source, w, h, x, y, |rgb| cmyk(rgb)[0]);
+                    let m = magenta.coverage(source, w, h, x, y, |rgb| cmyk(rgb)[1]);
+                    let ye = yellow.coverage(source, w, h, x, y, |rgb| cmyk(rgb)[2]);
+                    [(1.0 - c) * (1.0 - k), (1.0 - m) * (1.0 - k), (1.0 - ye) * (1.0 - k)]
+                }
+            };
+            for (channel, value) in out.iter_mut().zip(paper) {
+                *channel = (value * 255.0 + 0.5) as u8;
+            }
+        }
+    });
+    console_log!("Rust (WASM): Halftone finished.");
+    Ok(result)
+}
+
+// Luminance in 0..1 of a sampled colour, with `apply_grayscale`'s weights
+fn gray([r, g, b]: [f32; 3]) -> f32 {
+    (r * 0.299 + g * 0.587 + b * 0.114) / 255.0
+}
+
+// Naive RGB -> CMYK: all the grey goes to K, the rest to C, M and Y
+fn cmyk(rgb: [f32; 3]) -> [f32; 4] {
+    let [r, g, b] = rgb.map(|v| v / 255.0);
+    let k = 1.0 - r.max(g).max(b);
+    if k >= 1.0 {
+        return [0.0, 0.0, 0.0, 1.0];
+    }
+    [(1.0 - r - k) / (1.0 - k), (1.0 - g - k) / (1.0 - k), (1.0 - b - k) / (1.0 - k), k]
+}
+
+// One ink's rotated grid of cells
+struct Screen {
+    cell: f32,
+    sin: f32,
+    cos: f32,
+}
+
+impl Screen {
+    fn new(cell: f32, angle_degrees: f32) -> Screen {
+        let (sin, cos) = angle_degrees.to_radians().sin_cos();
+        Screen { cell, sin, cos }
+    }
+
+    // How much of pixel (x, y) this screen's dots cover, 0..1. Each dot
+    // sits in the middle of its cell with its ink amount (`ink` of the
+    // colour there) setting its size; full ink reaches the cell corners so
+    // neighbouring dots merge into solid colour. Dots bigger than half a
+    // cell spill into the cells around them, so those are checked too.
+    fn coverage(
+        &self,
+        source: &[Pixel],
+        width: usize,
+        height: usize,
+        x: f32,
+        y: f32,
+        ink: impl Fn([f32; 3]) -> f32,
+    ) -> f32 {
+        // Into grid space, where cells are axis-aligned squares
+        let u = x * self.cos + y * self.sin;
+        let v = y * self.cos - x * self.sin;
+        let (cell_u, cell_v) = ((u / self.cell).floor(), (v / self.cell).floor());
+        let mut coverage = 0.0f32;
+        for du in -1..=1 {
+            for dv in -1..=1 {
+                let centre_u = (cell_u + du as f32 + 0.5) * self.cell;
+                let centre_v = (cell_v + dv as f32 + 0.5) * self.cell;
+                let centre_x = centre_u * self.cos - centre_v * self.sin;
+                let centre_y = centre_u * self.sin + centre_v * self.cos;
+                let amount = ink(sample(source, width, height, centre_x, centre_y)).clamp(0.0, 1.0);
+                let radius = self.cell * (amount / 2.0).sqrt();
+                let distance = (u - centre_u).hypot(v - centre_v);
+                // Antialiased edge; dots under a pixel across fade out
+                // instead of leaving a speck at every empty cell
+                let edge = (radius - distance + 0.5).clamp(0.0, 1.0);
+                coverage = coverage.max(edge * (radius * 2.0).min(1.0));
+            }
+        }
+        coverage
+    }
+}
+
+// --- Cartoon ---
+// Bilateral smoothing strength, levels per channel and the Sobel magnitude
+// above which a pixel is drawn as outline
+const CARTOON_SPATIAL_SIGMA: f32 = 3.0;
+const CARTOON_RANGE_SIGMA: f32 = 30.0;
+const CARTOON_LEVELS: u32 = 6;
+const CARTOON_EDGE_THRESHOLD: f32 = 96.0;
+
+// Toon shading in one call: a bilateral filter flattens texture while
+// keeping edges, the colours are posterized into a few flat bands, and
+// black outlines are drawn wherever the smoothed image has a strong Sobel
+// gradient (the same operator as `apply_edge_detection`). Outlines come
+// from the smoothed image so skin and fabric texture don't sprout lines.
+// If cancelled during smoothing the result is only partly smoothed.
+#[wasm_bindgen]
+pub fn apply_cartoon(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    token: Option<CancellationToken>,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_memory("cartoon", width, height)?;
+    console_log!("Rust (WASM): Cartoon started...");
+    let (w, h) = (width as usize, height as usize);
+    let mut result = bilateral_pixels(&image_data, w, h, CARTOON_SPATIAL_SIGMA, CARTOON_RANGE_SIGMA, token.as_ref());
+    let gradients = sobel_gradients(&result, w, h);
+    let lut = posterize_lut(CARTOON_LEVELS);
+    for (pixel, (gx, gy)) in pixels_mut(&mut result).iter_mut().zip(gradients) {
+        if gx.hypot(gy) > CARTOON_EDGE_THRESHOLD {
+            pixel[..3].fill(0);
+        } else {
+            for channel in &mut pixel[..3] {
+                *channel = lut[*channel as usize];
+            }
+        }
+    }
+    console_log!("Rust (WASM): Cartoon finished.");
+    Ok(result)
+}
+
+// --- Film Grain ---
+// Adds noise with a standard deviation of `amount` 8-bit levels (about 5
+// is subtle, 20 heavy): one value per pixel shared by R, G and B when
+// `monochrome` (silver-halide grain), otherwise independent per channel
+// (colour sensor noise). The grain depends only on `seed` and the image
+// size, not on `set_seed` or the pixels, so keeping the seed fixed gives
+// every frame of a video the same grain and changing it per frame
+// animates it.
+#[wasm_bindgen]
+pub fn apply_grain(
+    mut image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    amount: f32,
+    seed: u32,
+    monochrome: bool,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_positive("amount", amount)?;
+    console_log!("Rust (WASM): Grain started...");
+    let mut rng = Lcg::new(seed);
+    // Sum of four uniforms: close enough to Gaussian for grain, with unit
+    // variance after scaling by sqrt(3)
+    let mut gaussian = || {
+        let sum: f32 = (0..4).map(|_| (rng.next_u32() >> 8) as f32 / (1 << 24) as f32).sum();
+        (sum - 2.0) * 3.0f32.sqrt()
+    };
+    for pixel in pixels_mut(&mut image_data) {
+        let shared = if monochrome { gaussian() * amount } else { 0.0 };
+        for channel in &mut pixel[..3] {
+            let noise = if monochrome { shared } else { gaussian() * amount };
+            *channel = (*channel as f32 + noise).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+    console_log!("Rust (WASM): Grain finished.");
+    Ok(image_data)
+}
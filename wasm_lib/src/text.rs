@@ -0,0 +1,145 @@
+// ========================================================================
+// TEXT RASTERIZER
+// A tiny 5x7 bitmap font for stamping readable labels straight into RGBA
+// buffers. Lowercase letters are drawn as uppercase and anything without a
+// glyph is drawn as '?'.
+// ========================================================================
+
+pub const GLYPH_WIDTH: usize = 5;
+pub const GLYPH_HEIGHT: usize = 7;
+// Blank columns between characters and blank rows between lines
+const CHAR_SPACING: usize = 1;
+const LINE_SPACING: usize = 2;
+
+// Size in pixels of `text` (which may contain newlines) at the given scale
+pub fn measure_text(text: &str, scale: usize) -> (usize, usize) {
+    let lines: Vec<&str> = text.lines().collect();
+    let longest = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    if longest == 0 {
+        return (0, 0);
+    }
+
+    let width = longest * (GLYPH_WIDTH + CHAR_SPACING) - CHAR_SPACING;
+    let height = lines.len() * (GLYPH_HEIGHT + LINE_SPACING) - LINE_SPACING;
+    (width * scale, height * scale)
+}
+
+// Draws `text` with its top-left corner at `origin`, alpha-blending `color`
+// (RGBA) over the existing pixels. Glyphs falling outside the buffer are
+// clipped.
+pub fn draw_text(
+    data: &mut [u8],
+    width: usize,
+    height: usize,
+    origin: (i32, i32),
+    text: &str,
+    scale: usize,
+    color: [u8; 4],
+) {
+    let (x, y) = origin;
+    let scale = scale.max(1);
+    let advance = ((GLYPH_WIDTH + CHAR_SPACING) * scale) as i32;
+    let line_height = ((GLYPH_HEIGHT + LINE_SPACING) * scale) as i32;
+
+    for (line_idx, line) in text.lines().enumerate() {
+        let line_y = y + line_idx as i32 * line_height;
+        for (char_idx, c) in line.chars().enumerate() {
+            let glyph_x = x + char_idx as i32 * advance;
+            let rows = glyph(c);
+
+            for (gy, bits) in rows.iter().enumerate() {
+                for gx in 0..GLYPH_WIDTH {
+                    if bits & (0x10 >> gx) == 0 {
+                        continue;
+                    }
+                    // Each font pixel becomes a scale x scale block
+                    for sy in 0..scale {
+                        for sx in 0..scale {
+                            let px = glyph_x + (gx * scale + sx) as i32;
+                            let py = line_y + (gy * scale + sy) as i32;
+                            if px < 0 || py < 0 || px as usize >= width || py as usize >= height {
+                                continue;
+                            }
+                            let idx = (py as usize * width + px as usize) * 4;
+                            blend_pixel(&mut data[idx..idx + 4], color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Source-over blend of an RGBA color onto one pixel
+pub fn blend_pixel(pixel: &mut [u8], color: [u8; 4]) {
+    let alpha = color[3] as u32;
+    for c in 0..3 {
+        pixel[c] = ((color[c] as u32 * alpha + pixel[c] as u32 * (255 - alpha)) / 255) as u8;
+    }
+    pixel[3] = pixel[3].max(color[3]);
+}
+
+// Rows of a glyph, top to bottom; bit 4 is the leftmost column
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    match c.to_ascii_uppercase() {
+        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        ';' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        '=' => [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00],
+        '*' => [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '%' => [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03],
+        '#' => [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A],
+        '!' => [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        '[' => [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E],
+        ']' => [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E],
+        '<' => [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02],
+        '>' => [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08],
+        '|' => [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        '\'' => [0x04, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04], // '?'
+    }
+}
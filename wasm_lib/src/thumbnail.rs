@@ -0,0 +1,126 @@
+use wasm_bindgen::prelude::*;
+
+use crate::analysis::{best_window, saliency_map};
+use crate::error::{check_image, image_len};
+
+// ========================================================================
+// SMART THUMBNAILS
+// Instead of a center crop, the crop window is placed over the most
+// salient part of the image (see `suggest_crop_saliency`), optionally
+// pulled towards face boxes the host already knows about (e.g. from the
+// Shape Detection API), then area-averaged down to the thumbnail size.
+// ========================================================================
+
+// --- Smart Thumbnail ---
+#[wasm_bindgen]
+pub fn smart_thumbnail(data: &[u8], width: u32, height: u32, out_w: u32, out_h: u32) -> Result<Vec<u8>, JsValue> {
+    smart_thumbnail_with_faces(data, width, height, out_w, out_h, &[])
+}
+
+// Same, with face hints as a flat [x0, y0, w0, h0, x1, y1, w1, h1, ...]
+// list of boxes in source pixels. Faces count as the most salient content
+// in the image, so the crop keeps them whenever it can.
+#[wasm_bindgen]
+pub fn smart_thumbnail_with_faces(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    out_w: u32,
+    out_h: u32,
+    faces: &[i32],
+) -> Result<Vec<u8>, JsValue> {
+    check_image(data, width, height)?;
+    image_len(out_w, out_h)?;
+    console_log!("Rust (WASM): Smart thumbnail started...");
+
+    let (w, h) = (width as usize, height as usize);
+    let (out_w, out_h) = (out_w as usize, out_h as usize);
+
+    // Largest window with the thumbnail's aspect ratio
+    let aspect = out_w as f64 / out_h as f64;
+    let (crop_w, crop_h) = if (w as f64 / h as f64) > aspect {
+        (((h as f64 * aspect).round() as usize).clamp(1, w), h)
+    } else {
+        (w, ((w as f64 / aspect).round() as usize).clamp(1, h))
+    };
+
+    let mut map = saliency_map(data, w, h);
+    let peak = map.values.iter().cloned().fold(1.0f32, f32::max);
+    for face in faces.chunks_exact(4) {
+        let step = map.step as i32;
+        let x0 = (face[0] / step).clamp(0, map.grid_width as i32) as usize;
+        let y0 = (face[1] / step).clamp(0, map.grid_height as i32) as usize;
+        let x1 = ((face[0] + face[2]) / step + 1).clamp(0, map.grid_width as i32) as usize;
+        let y1 = ((face[1] + face[3]) / step + 1).clamp(0, map.grid_height as i32) as usize;
+        for gy in y0..y1 {
+            for gx in x0..x1 {
+                map.values[gy * map.grid_width + gx] += peak;
+            }
+        }
+    }
+    let (crop_x, crop_y) = best_window(&map, crop_w, crop_h);
+
+    let result = resample_area(data, w, h, (crop_x, crop_y, crop_w, crop_h), out_w, out_h);
+
+    console_log!("Rust (WASM): Smart thumbnail finished.");
+    Ok(result)
+}
+
+// Area-average the `crop` rectangle (x, y, width, height) of a `width`
+// pixels wide image to `out_w x out_h`; each output pixel covers a
+// fractional rectangle of source pixels
+pub(crate) fn resample_area(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    crop: (usize, usize, usize, usize),
+    out_w: usize,
+    out_h: usize,
+) -> Vec<u8> {
+    let (crop_x, crop_y, crop_w, crop_h) = crop;
+    let mut result = vec![0u8; out_w * out_h * 4];
+    let scale_x = crop_w as f64 / out_w as f64;
+    let scale_y = crop_h as f64 / out_h as f64;
+    let x_spans: Vec<Vec<(usize, f64)>> = (0..out_w)
+        .map(|ox| coverage(ox as f64 * scale_x, (ox + 1) as f64 * scale_x, crop_x, width))
+        .collect();
+
+    for oy in 0..out_h {
+        let y_span = coverage(oy as f64 * scale_y, (oy + 1) as f64 * scale_y, crop_y, height);
+        for (ox, x_span) in x_spans.iter().enumerate() {
+            let mut sums = [0.0f64; 4];
+            let mut total = 0.0;
+            for &(sy, wy) in &y_span {
+                for &(sx, wx) in x_span {
+                    let weight = wx * wy;
+                    let idx = (sy * width + sx) * 4;
+                    for (sum, value) in sums.iter_mut().zip(&data[idx..idx + 4]) {
+                        *sum += *value as f64 * weight;
+                    }
+                    total += weight;
+                }
+            }
+            let out_idx = (oy * out_w + ox) * 4;
+            for (out, sum) in result[out_idx..out_idx + 4].iter_mut().zip(sums) {
+                *out = (sum / total).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+
+    result
+}
+
+// Source pixels overlapping [start, end) (relative to `offset`) with how
+// much of each is covered. Upscaling gives spans narrower than a pixel,
+// which still land on the one pixel underneath.
+fn coverage(start: f64, end: f64, offset: usize, limit: usize) -> Vec<(usize, f64)> {
+    let first = start.floor() as usize;
+    let last = (end.ceil() as usize).max(first + 1);
+    (first..last)
+        .filter_map(|i| {
+            let weight = (end.min((i + 1) as f64) - start.max(i as f64)).max(1e-6);
+            let src = offset + i;
+            (src < limit).then_some((src, weight))
+        })
+        .collect()
+}
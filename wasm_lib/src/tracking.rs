@@ -0,0 +1,341 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::check_image;
+use crate::fft::{fft2d, Complex};
+use crate::geometry::Rect;
+use crate::settings::{self, QualityTier};
+
+// ========================================================================
+// OBJECT TRACKING
+// MOSSE (Minimum Output Sum of Squared Error) correlation tracker: learns a
+// filter in the frequency domain whose response to the target patch is a
+// sharp Gaussian peak, then follows the peak from frame to frame.
+// ========================================================================
+
+// Width of the desired Gaussian response, in template pixels
+const TARGET_SIGMA: f32 = 2.0;
+// Keeps the filter stable at frequencies where the patch has no energy
+const REGULARIZATION: f32 = 0.01;
+// Peak-to-sidelobe ratio below which the target is considered lost and the
+// model is left untouched, so occlusions don't poison the filter
+const LOST_PSR: f32 = 7.0;
+// Half-size of the window around the peak excluded from the sidelobe stats
+const PEAK_EXCLUSION: i32 = 5;
+// Template sides are powers of two within this range; lower quality tiers
+// cap the template smaller to make each FFT cheaper
+const MIN_TEMPLATE: usize = 16;
+const MAX_TEMPLATE: usize = 128;
+
+// --- Tracker ---
+#[wasm_bindgen]
+pub struct Tracker {
+    frame_width: usize,
+    frame_height: usize,
+    // Current target, stored as a sub-pixel center plus size
+    center_x: f32,
+    center_y: f32,
+    rect_width: f32,
+    rect_height: f32,
+    // Template (FFT) dimensions
+    template_width: usize,
+    template_height: usize,
+    window: Vec<f32>,
+    target: Vec<Complex>,
+    numerator: Vec<Complex>,
+    denominator: Vec<f32>,
+    learning_rate: f32,
+    psr: f32,
+    initialized: bool,
+}
+
+#[wasm_bindgen]
+impl Tracker {
+    // `learning_rate` controls how quickly the model adapts to appearance
+    // changes; the MOSSE paper uses 0.125.
+    #[wasm_bindgen(constructor)]
+    pub fn new(learning_rate: f32) -> Tracker {
+        Tracker {
+            frame_width: 0,
+            frame_height: 0,
+            center_x: 0.0,
+            center_y: 0.0,
+            rect_width: 0.0,
+            rect_height: 0.0,
+            template_width: 0,
+            template_height: 0,
+            window: Vec::new(),
+            target: Vec::new(),
+            numerator: Vec::new(),
+            denominator: Vec::new(),
+            learning_rate: learning_rate.clamp(0.0, 1.0),
+            psr: 0.0,
+            initialized: false,
+        }
+    }
+
+    // Start tracking the object inside `rect` on an RGBA frame
+    pub fn init(&mut self, frame: Vec<u8>, width: u32, height: u32, rect: Rect) -> Result<(), JsValue> {
+        check_image(&frame, width, height)?;
+        console_log!("Rust (WASM): Tracker init started...");
+
+        self.frame_width = width as usize;
+        self.frame_height = height as usize;
+        self.rect_width = rect.width.max(1) as f32;
+        self.rect_height = rect.height.max(1) as f32;
+        self.center_x = rect.x as f32 + self.rect_width / 2.0;
+        self.center_y = rect.y as f32 + self.rect_height / 2.0;
+
+        self.template_width = template_side(rect.width);
+        self.template_height = template_side(rect.height);
+        let (tw, th) = (self.template_width, self.template_height);
+
+        self.window = hann_window(tw, th);
+        self.target = gaussian_target(tw, th);
+
+        // Train on the patch plus a few small rotations and scalings of it,
+        // so the very first filter already tolerates some deformation
+        let gray = to_gray(&frame);
+        self.numerator = vec![Complex::ZERO; tw * th];
+        self.denominator = vec![0.0; tw * th];
+
+        let perturbations = [
+            (0.0, 1.0),
+            (-0.1, 1.0),
+            (0.1, 1.0),
+            (0.0, 0.95),
+            (0.0, 1.05),
+            (-0.05, 0.97),
+            (0.05, 1.03),
+            (0.15, 1.0),
+        ];
+        for &(angle, scale) in perturbations.iter() {
+            let spectrum = self.patch_spectrum(&gray, angle, scale);
+            for ((a, b), (f, g)) in self
+                .numerator
+                .iter_mut()
+                .zip(self.denominator.iter_mut())
+                .zip(spectrum.iter().zip(self.target.iter()))
+            {
+                *a = *a + *g * f.conj();
+                *b += f.norm_sqr();
+            }
+        }
+
+        self.psr = 0.0;
+        self.initialized = true;
+        console_log!("Rust (WASM): Tracker init finished.");
+        Ok(())
+    }
+
+    // Locate the target in the next frame, which must be the size `init`
+    // was given, and return its new rectangle. Before `init` this just
+    // returns an empty rect.
+    pub fn update(&mut self, frame: Vec<u8>) -> Result<Rect, JsValue> {
+        if !self.initialized {
+            return Ok(Rect::default());
+        }
+        check_image(&frame, self.frame_width as u32, self.frame_height as u32)?;
+
+        let (tw, th) = (self.template_width, self.template_height);
+        let gray = to_gray(&frame);
+
+        // Correlate: response = IFFT(H* . F) where H* = A / B
+        let mut response = self.patch_spectrum(&gray, 0.0, 1.0);
+        for ((r, a), b) in response
+            .iter_mut()
+            .zip(self.numerator.iter())
+            .zip(self.denominator.iter())
+        {
+            *r = *r * a.scale(1.0 / (b + REGULARIZATION));
+        }
+        fft2d(&mut response, tw, th, true);
+
+        let (peak_x, peak_y, psr) = find_peak(&response, tw, th);
+        self.psr = psr;
+
+        if psr >= LOST_PSR {
+            // Shift from template coordinates back to frame coordinates
+            let dx = (peak_x as f32 - (tw / 2) as f32) * self.rect_width / tw as f32;
+            let dy = (peak_y as f32 - (th / 2) as f32) * self.rect_height / th as f32;
+            self.center_x = (self.center_x + dx).clamp(0.0, self.frame_width as f32);
+            self.center_y = (self.center_y + dy).clamp(0.0, self.frame_height as f32);
+
+            // Blend the patch at the new location into the running model
+            let spectrum = self.patch_spectrum(&gray, 0.0, 1.0);
+            let eta = self.learning_rate;
+            for ((a, b), (f, g)) in self
+                .numerator
+                .iter_mut()
+                .zip(self.denominator.iter_mut())
+                .zip(spectrum.iter().zip(self.target.iter()))
+            {
+                *a = (*g * f.conj()).scale(eta) + a.scale(1.0 - eta);
+                *b = eta * f.norm_sqr() + (1.0 - eta) * *b;
+            }
+        }
+
+        Ok(self.rect())
+    }
+
+    // The current target rectangle
+    pub fn rect(&self) -> Rect {
+        Rect {
+            x: (self.center_x - self.rect_width / 2.0).round() as i32,
+            y: (self.center_y - self.rect_height / 2.0).round() as i32,
+            width: self.rect_width as u32,
+            height: self.rect_height as u32,
+        }
+    }
+
+    // Peak-to-sidelobe ratio of the last update; values under ~7 mean the
+    // target was lost (occluded or out of frame)
+    #[wasm_bindgen(getter)]
+    pub fn psr(&self) -> f32 {
+        self.psr
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_lost(&self) -> bool {
+        self.initialized && self.psr < LOST_PSR
+    }
+}
+
+impl Tracker {
+    // Sample the target patch (optionally rotated/scaled about its center),
+    // preprocess it as in the MOSSE paper and return its spectrum
+    fn patch_spectrum(&self, gray: &[f32], angle: f32, scale: f32) -> Vec<Complex> {
+        let (tw, th) = (self.template_width, self.template_height);
+        let (sin, cos) = angle.sin_cos();
+        let step_x = self.rect_width / tw as f32 * scale;
+        let step_y = self.rect_height / th as f32 * scale;
+
+        let mut patch = Vec::with_capacity(tw * th);
+        for j in 0..th {
+            for i in 0..tw {
+                let u = (i as f32 + 0.5 - tw as f32 / 2.0) * step_x;
+                let v = (j as f32 + 0.5 - th as f32 / 2.0) * step_y;
+                let sx = self.center_x + u * cos - v * sin;
+                let sy = self.center_y + u * sin + v * cos;
+                let value = sample_bilinear(gray, self.frame_width, self.frame_height, sx, sy);
+                // Log transform reduces the effect of lighting changes
+                patch.push((value + 1.0).ln());
+            }
+        }
+
+        // Normalize to zero mean / unit variance, then taper the edges
+        let n = patch.len() as f32;
+        let mean = patch.iter().sum::<f32>() / n;
+        let variance = patch.iter().map(|p| (p - mean) * (p - mean)).sum::<f32>() / n;
+        let inv_std = 1.0 / (variance.sqrt() + 1e-5);
+
+        let mut spectrum: Vec<Complex> = patch
+            .iter()
+            .zip(self.window.iter())
+            .map(|(p, w)| Complex::new((p - mean) * inv_std * w, 0.0))
+            .collect();
+        fft2d(&mut spectrum, tw, th, false);
+        spectrum
+    }
+}
+
+// Smallest power of two covering the rect side, kept in a sane FFT range
+fn template_side(side: u32) -> usize {
+    let max_side = match settings::effective_quality() {
+        QualityTier::High => MAX_TEMPLATE,
+        QualityTier::Balanced => MAX_TEMPLATE / 2,
+        QualityTier::Low => MAX_TEMPLATE / 4,
+    };
+    (side as usize).next_power_of_two().clamp(MIN_TEMPLATE, max_side)
+}
+
+fn hann_window(width: usize, height: usize) -> Vec<f32> {
+    let hann = |i: usize, n: usize| {
+        0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / (n - 1) as f32).cos()
+    };
+    let mut window = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            window.push(hann(x, width) * hann(y, height));
+        }
+    }
+    window
+}
+
+// Spectrum of a Gaussian peak centred in the template
+fn gaussian_target(width: usize, height: usize) -> Vec<Complex> {
+    let two_sigma_sq = 2.0 * TARGET_SIGMA * TARGET_SIGMA;
+    let (cx, cy) = ((width / 2) as f32, (height / 2) as f32);
+
+    let mut target = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let dx = x as f32 - cx;
+            let dy = y as f32 - cy;
+            target.push(Complex::new((-(dx * dx + dy * dy) / two_sigma_sq).exp(), 0.0));
+        }
+    }
+    fft2d(&mut target, width, height, false);
+    target
+}
+
+// Returns the peak location and its peak-to-sidelobe ratio
+fn find_peak(response: &[Complex], width: usize, height: usize) -> (usize, usize, f32) {
+    let mut peak_idx = 0;
+    let mut peak = f32::MIN;
+    for (i, value) in response.iter().enumerate() {
+        if value.re > peak {
+            peak = value.re;
+            peak_idx = i;
+        }
+    }
+    let (px, py) = (peak_idx % width, peak_idx / width);
+
+    let mut sum = 0.0;
+    let mut sum_sq = 0.0;
+    let mut count = 0.0;
+    for y in 0..height {
+        for x in 0..width {
+            let near_peak = (x as i32 - px as i32).abs() <= PEAK_EXCLUSION
+                && (y as i32 - py as i32).abs() <= PEAK_EXCLUSION;
+            if !near_peak {
+                let v = response[y * width + x].re;
+                sum += v;
+                sum_sq += v * v;
+                count += 1.0;
+            }
+        }
+    }
+
+    if count == 0.0 {
+        return (px, py, 0.0);
+    }
+    let mean = sum / count;
+    let std = (sum_sq / count - mean * mean).max(0.0).sqrt();
+    (px, py, (peak - mean) / (std + 1e-5))
+}
+
+fn to_gray(frame: &[u8]) -> Vec<f32> {
+    frame
+        .chunks_exact(4)
+        .map(|p| p[0] as f32 * 0.299 + p[1] as f32 * 0.587 + p[2] as f32 * 0.114)
+        .collect()
+}
+
+// Bilinear lookup with clamp-to-edge addressing
+fn sample_bilinear(gray: &[f32], width: usize, height: usize, x: f32, y: f32) -> f32 {
+    if width == 0 || height == 0 || gray.len() < width * height {
+        return 0.0;
+    }
+    let x = x.clamp(0.0, (width - 1) as f32);
+    let y = y.clamp(0.0, (height - 1) as f32);
+    let x0 = x.floor() as usize;
+    let y0 = y.floor() as usize;
+    let x1 = (x0 + 1).min(width - 1);
+    let y1 = (y0 + 1).min(height - 1);
+    let fx = x - x0 as f32;
+    let fy = y - y0 as f32;
+
+    let top = gray[y0 * width + x0] * (1.0 - fx) + gray[y0 * width + x1] * fx;
+    let bottom = gray[y1 * width + x0] * (1.0 - fx) + gray[y1 * width + x1] * fx;
+    top * (1.0 - fy) + bottom * fy
+}
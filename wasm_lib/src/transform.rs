@@ -0,0 +1,999 @@
+use wasm_bindgen::prelude::*;
+use wasmfx_core::filters::sobel_gradients;
+
+use crate::adjust::SrgbEncoder;
+use crate::cancel::{cancelled, CancellationToken};
+use crate::color::srgb_to_linear_table;
+use crate::error::{
+    check_finite, check_float_range, check_image, check_one_of, check_positive, check_range, image_len, Error,
+};
+use crate::geometry::Insets;
+use crate::memory::check_memory;
+use crate::pixels::pixels;
+
+// ========================================================================
+// TRANSFORMS
+// Operations that change an image's dimensions, from plain crops and
+// padding to resampling and seam carving. Resampling happens in linear
+// light with premultiplied alpha, so downscaled edges don't darken and
+// transparent pixels don't bleed their (invisible) colour into opaque
+// neighbours.
+// ========================================================================
+
+// An RGBA image together with its dimensions, for exports whose output
+// size differs from their input's
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct RgbaImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl RgbaImage {
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn pixels(&self) -> Vec<u8> {
+        self.pixels.clone()
+    }
+}
+
+// --- Crop / Pad ---
+// The `crop_width x crop_height` region whose top-left pixel is (`x`, `y`),
+// which must lie within the image
+#[wasm_bindgen]
+pub fn crop(
+    image_data: &[u8],
+    width: u32,
+    height: u32,
+    x: u32,
+    y: u32,
+    crop_width: u32,
+    crop_height: u32,
+) -> Result<RgbaImage, JsValue> {
+    check_image(image_data, width, height)?;
+    image_len(crop_width, crop_height)?;
+    check_memory("crop", crop_width, crop_height)?;
+    if x.checked_add(crop_width).is_none_or(|right| right > width)
+        || y.checked_add(crop_height).is_none_or(|bottom| bottom > height)
+    {
+        return Err(Error::OutsideImage {
+            x,
+            y,
+            width: crop_width,
+            height: crop_height,
+            image_width: width,
+            image_height: height,
+        }
+        .into());
+    }
+    console_log!("Rust (WASM): Crop started...");
+    let (w, cw) = (width as usize, crop_width as usize);
+    let mut pixels = Vec::with_capacity(cw * crop_height as usize * 4);
+    for row in image_data.chunks_exact(w * 4).skip(y as usize).take(crop_height as usize) {
+        pixels.extend_from_slice(&row[x as usize * 4..][..cw * 4]);
+    }
+    console_log!("Rust (WASM): Crop finished.");
+    Ok(RgbaImage {
+        width: crop_width,
+        height: crop_height,
+        pixels,
+    })
+}
+
+// Grows the canvas by `insets` on each side, filling the new border with
+// `fill_rgba` (0xRRGGBBAA; 0 for transparent)
+#[wasm_bindgen]
+pub fn pad(image_data: &[u8], width: u32, height: u32, insets: Insets, fill_rgba: u32) -> Result<RgbaImage, JsValue> {
+    check_image(image_data, width, height)?;
+    let out_width = width.saturating_add(insets.left).saturating_add(insets.right);
+    let out_height = height.saturating_add(insets.top).saturating_add(insets.bottom);
+    let len = image_len(out_width, out_height)?;
+    check_memory("pad", out_width, out_height)?;
+    console_log!("Rust (WASM): Pad started...");
+    let fill = fill_rgba.to_be_bytes();
+    let mut pixels: Vec<u8> = fill.iter().copied().cycle().take(len).collect();
+    let (w, out_w) = (width as usize * 4, out_width as usize * 4);
+    let left = insets.left as usize * 4;
+    for (y, row) in image_data.chunks_exact(w).enumerate() {
+        let start = (y + insets.top as usize) * out_w + left;
+        pixels[start..][..w].copy_from_slice(row);
+    }
+    console_log!("Rust (WASM): Pad finished.");
+    Ok(RgbaImage {
+        width: out_width,
+        height: out_height,
+        pixels,
+    })
+}
+
+// --- Resize ---
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResizeFilter {
+    // Tent over one pixel (or, shrinking, one output pixel's footprint);
+    // smooth, slightly soft
+    Bilinear,
+    // Catmull-Rom over two; sharper, with faint halos at hard edges
+    Bicubic,
+    // Windowed sinc over three; the sharpest and the usual choice for
+    // thumbnails, with a little ringing next to hard edges
+    Lanczos3,
+}
+
+impl ResizeFilter {
+    // Taps either side of the centre, in source pixels when enlarging
+    fn support(self) -> f32 {
+        match self {
+            ResizeFilter::Bilinear => 1.0,
+            ResizeFilter::Bicubic => 2.0,
+            ResizeFilter::Lanczos3 => 3.0,
+        }
+    }
+
+    // Unnormalized weight of a tap `x` pixels from the centre
+    fn weight(self, x: f32) -> f32 {
+        let x = x.abs();
+        match self {
+            ResizeFilter::Bilinear => 1.0 - x,
+            ResizeFilter::Bicubic if x < 1.0 => (1.5 * x - 2.5) * x * x + 1.0,
+            ResizeFilter::Bicubic if x < 2.0 => ((-0.5 * x + 2.5) * x - 4.0) * x + 2.0,
+            ResizeFilter::Bicubic => 0.0,
+            ResizeFilter::Lanczos3 if x == 0.0 => 1.0,
+            ResizeFilter::Lanczos3 if x < 3.0 => {
+                let pi_x = std::f32::consts::PI * x;
+                3.0 * pi_x.sin() * (pi_x / 3.0).sin() / (pi_x * pi_x)
+            }
+            ResizeFilter::Lanczos3 => 0.0,
+        }
+    }
+}
+
+// Resamples to `new_width x new_height` (the aspect ratio is the caller's
+// to keep). Each filter is stretched over the area an output pixel covers
+// when shrinking, so downscales don't alias the way canvas `drawImage`
+// does.
+#[wasm_bindgen]
+pub fn resize(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    new_width: u32,
+    new_height: u32,
+    filter: ResizeFilter,
+) -> Result<RgbaImage, JsValue> {
+    check_image(&image_data, width, height)?;
+    image_len(new_width, new_height)?;
+    check_memory("resize", width.max(new_width), height.max(new_height))?;
+    console_log!("Rust (WASM): Resize started...");
+    let pixels = resample_linear(
+        &image_data,
+        width as usize,
+        height as usize,
+        new_width as usize,
+        new_height as usize,
+        filter,
+    );
+    console_log!("Rust (WASM): Resize finished.");
+    Ok(RgbaImage {
+        width: new_width,
+        height: new_height,
+        pixels,
+    })
+}
+
+// --- Resize With Pixel Aspect Ratio ---
+// Widest pixel aspect ratio accepted; real formats stay within 0.5..3
+const MAX_PAR: u32 = 16;
+
+// Anamorphic video stores pixels that are displayed wider or narrower than
+// they are tall: a 720x576 PAL frame with a pixel aspect ratio (`par`) of
+// 16/15 is shown as 768x576. This resamples to square pixels at
+// `out_height` rows (0 keeps the source height), with the width following
+// from the display aspect ratio.
+#[wasm_bindgen]
+pub fn resize_with_par(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    par: f32,
+    out_height: u32,
+) -> Result<RgbaImage, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_positive("par", par)?;
+    check_float_range("par", par, 0, MAX_PAR)?;
+    let out_height = if out_height == 0 { height } else { out_height };
+    let display_width = width as f64 * par as f64 * out_height as f64 / height as f64;
+    let out_width = display_width.round().clamp(1.0, u32::MAX as f64) as u32;
+    image_len(out_width, out_height)?;
+    check_memory("resize_with_par", width.max(out_width), height.max(out_height))?;
+
+    console_log!("Rust (WASM): Pixel aspect resize started...");
+    let pixels = resample_linear(
+        &image_data,
+        width as usize,
+        height as usize,
+        out_width as usize,
+        out_height as usize,
+        ResizeFilter::Bilinear,
+    );
+    console_log!("Rust (WASM): Pixel aspect resize finished.");
+    Ok(RgbaImage {
+        width: out_width,
+        height: out_height,
+        pixels,
+    })
+}
+
+// --- Rotate ---
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    // The nearest source pixel; keeps pixel art's hard edges
+    Nearest,
+    Bilinear,
+    // Catmull-Rom; sharper, with faint halos at hard edges
+    Bicubic,
+}
+
+impl Interpolation {
+    // The colour at (`x`, `y`), in pixel-centre coordinates, from the
+    // pixels `at` returns
+    fn sample<'a>(self, x: f32, y: f32, at: impl Fn(isize, isize) -> &'a [f32; 4]) -> [f32; 4] {
+        let (base_x, base_y) = (x.floor(), y.floor());
+        let (fx, fy) = (x - base_x, y - base_y);
+        let (bx, by) = (base_x as isize, base_y as isize);
+        match self {
+            Interpolation::Nearest => *at(x.round() as isize, y.round() as isize),
+            Interpolation::Bilinear => {
+                let taps = [(0, 0, (1.0 - fx) * (1.0 - fy)), (1, 0, fx * (1.0 - fy)), (0, 1, (1.0 - fx) * fy), (1, 1, fx * fy)];
+                weighted_sum(taps.into_iter().map(|(dx, dy, weight)| (at(bx + dx, by + dy), weight)))
+            }
+            Interpolation::Bicubic => {
+                let (wx, wy) = (catmull_rom(fx), catmull_rom(fy));
+                weighted_sum((0..16).map(|k| (at(bx + k % 4 - 1, by + k / 4 - 1), wx[k as usize % 4] * wy[k as usize / 4])))
+            }
+        }
+    }
+}
+
+// Turns the image `degrees` clockwise about its centre. The canvas grows
+// to the rotated image's bounding box, with the corners it uncovers filled
+// with `background_rgba` (0xRRGGBBAA; 0 for transparent) and the image's
+// edges antialiased against it. Multiples of 90 degrees are exact pixel
+// copies, whatever the interpolation.
+#[wasm_bindgen]
+pub fn rotate(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    degrees: f32,
+    interpolation: Interpolation,
+    background_rgba: u32,
+) -> Result<RgbaImage, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_finite("degrees", degrees)?;
+    console_log!("Rust (WASM): Rotate started...");
+    let turned = degrees.rem_euclid(360.0);
+    if turned % 90.0 == 0.0 {
+        let result = rotate_quarters(&image_data, width, height, (turned / 90.0) as usize);
+        console_log!("Rust (WASM): Rotate finished.");
+        return Ok(result);
+    }
+
+    let (sin, cos) = (degrees as f64).to_radians().sin_cos();
+    // Corners overhanging by under a hundredth of a pixel don't get a row
+    // or column of their own
+    let extent = |along: u32, across: u32| {
+        (along as f64 * cos.abs() + across as f64 * sin.abs() - 0.01).ceil().max(1.0) as u32
+    };
+    let (out_width, out_height) = (extent(width, height), extent(height, width));
+    image_len(out_width, out_height)?;
+    check_memory("rotate", width.max(out_width), height.max(out_height))?;
+
+    let (w, h) = (width as usize, height as usize);
+    let source = to_premultiplied_linear(&image_data);
+    let background = to_premultiplied_linear(&background_rgba.to_be_bytes())[0];
+    let (source, background) = (&source, &background);
+    // Taps beyond the image read the background, which blends the edges
+    let at = move |x: isize, y: isize| -> &[f32; 4] {
+        if (0..w as isize).contains(&x) && (0..h as isize).contains(&y) {
+            &source[y as usize * w + x as usize]
+        } else {
+            background
+        }
+    };
+    let (sin, cos) = (sin as f32, cos as f32);
+    let (center_x, center_y) = (w as f32 / 2.0, h as f32 / 2.0);
+    let (out_center_x, out_center_y) = (out_width as f32 / 2.0, out_height as f32 / 2.0);
+    let mut result = Vec::with_capacity(out_width as usize * out_height as usize);
+    for oy in 0..out_height {
+        let dy = oy as f32 + 0.5 - out_center_y;
+        for ox in 0..out_width {
+            let dx = ox as f32 + 0.5 - out_center_x;
+            // Inverse of the clockwise rotation (y points down)
+            let x = dx * cos + dy * sin + center_x - 0.5;
+            let y = -dx * sin + dy * cos + center_y - 0.5;
+            result.push(interpolation.sample(x, y, at));
+        }
+    }
+    console_log!("Rust (WASM): Rotate finished.");
+    Ok(RgbaImage {
+        width: out_width,
+        height: out_height,
+        pixels: from_premultiplied_linear(&result),
+    })
+}
+
+// `quarters` (0..4) turns of 90 degrees clockwise
+fn rotate_quarters(data: &[u8], width: u32, height: u32, quarters: usize) -> RgbaImage {
+    let (w, h) = (width as usize, height as usize);
+    let (out_width, out_height) = if quarters % 2 == 1 { (height, width) } else { (width, height) };
+    let source = pixels(data);
+    let mut result = Vec::with_capacity(data.len());
+    for oy in 0..out_height as usize {
+        for ox in 0..out_width as usize {
+            let (x, y) = match quarters {
+                1 => (oy, h - 1 - ox),
+                2 => (w - 1 - ox, h - 1 - oy),
+                3 => (w - 1 - oy, ox),
+                _ => (ox, oy),
+            };
+            result.extend_from_slice(&source[y * w + x]);
+        }
+    }
+    RgbaImage {
+        width: out_width,
+        height: out_height,
+        pixels: result,
+    }
+}
+
+// --- Image Pyramids ---
+// Most levels `build_pyramid` makes; a 16-level pyramid of the largest
+// accepted image is already down to a few pixels
+const MAX_PYRAMID_LEVELS: u32 = 16;
+
+// Gaussian levels from full size down, each blurred with Burt and
+// Adelson's 5-tap [1 4 6 4 1] / 16 kernel and halved (rounding up), plus
+// optionally the Laplacian residuals between them
+#[wasm_bindgen]
+pub struct Pyramid {
+    levels: Vec<RgbaImage>,
+    residuals: Vec<Vec<f32>>,
+}
+
+#[wasm_bindgen]
+impl Pyramid {
+    // Gaussian levels; may be fewer than asked for if the image reached
+    // 1x1 first
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> u32 {
+        self.levels.len() as u32
+    }
+
+    // Level 0 is the input itself
+    pub fn level(&self, index: u32) -> Option<RgbaImage> {
+        self.levels.get(index as usize).cloned()
+    }
+
+    // Level `index` minus level `index + 1` expanded back to its size, as
+    // signed RGBA floats at level `index`'s dimensions. Adding the residual
+    // to the expanded coarser level restores the finer one exactly, so
+    // blends done per level collapse back without drift. There is one
+    // fewer residual than levels (the coarsest level is its own top), and
+    // none unless `build_pyramid` was asked for them.
+    pub fn residual(&self, index: u32) -> Option<Vec<f32>> {
+        self.residuals.get(index as usize).cloned()
+    }
+}
+
+// Works on the stored sRGB values of all four channels, unlike the
+// resamplers here, so the residuals stay in the space callers blend in
+#[wasm_bindgen]
+pub fn build_pyramid(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    levels: u32,
+    laplacian: bool,
+) -> Result<Pyramid, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_range("levels", levels, 1, MAX_PYRAMID_LEVELS)?;
+    check_memory("pyramid", width, height)?;
+    console_log!("Rust (WASM): Pyramid build started...");
+    let mut pyramid = Pyramid {
+        levels: vec![RgbaImage { width, height, pixels: image_data }],
+        residuals: Vec::new(),
+    };
+    while pyramid.levels.len() < levels as usize {
+        let finer = &pyramid.levels[pyramid.levels.len() - 1];
+        if finer.width == 1 && finer.height == 1 {
+            break;
+        }
+        let coarser = pyramid_reduce(finer);
+        if laplacian {
+            let expanded = pyramid_expand(&coarser, finer.width as usize, finer.height as usize);
+            let residual = finer.pixels.iter().zip(&expanded).map(|(&v, e)| v as f32 - e).collect();
+            pyramid.residuals.push(residual);
+        }
+        pyramid.levels.push(coarser);
+    }
+    console_log!("Rust (WASM): Pyramid build finished ({} levels).", pyramid.levels.len());
+    Ok(pyramid)
+}
+
+const PYRAMID_KERNEL: [u32; 5] = [1, 4, 6, 4, 1];
+
+// Blur and keep every other pixel in each direction, clamping at the edges
+fn pyramid_reduce(image: &RgbaImage) -> RgbaImage {
+    let (w, h) = (image.width as usize, image.height as usize);
+    let (out_w, out_h) = (w.div_ceil(2), h.div_ceil(2));
+    let tap = |center: usize, k: usize, len: usize| (center + k).saturating_sub(2).min(len - 1);
+
+    // Horizontal sums (x16) at the even columns
+    let mut temp = vec![0u32; out_w * h * 4];
+    for y in 0..h {
+        let row = &image.pixels[y * w * 4..][..w * 4];
+        for ox in 0..out_w {
+            let out = &mut temp[(y * out_w + ox) * 4..][..4];
+            for (k, weight) in PYRAMID_KERNEL.iter().enumerate() {
+                let x = tap(ox * 2, k, w);
+                for c in 0..4 {
+                    out[c] += weight * row[x * 4 + c] as u32;
+                }
+            }
+        }
+    }
+
+    let mut pixels = vec![0u8; out_w * out_h * 4];
+    for oy in 0..out_h {
+        for (ox, out) in pixels[oy * out_w * 4..][..out_w * 4].chunks_exact_mut(4).enumerate() {
+            let mut sum = [0u32; 4];
+            for (k, weight) in PYRAMID_KERNEL.iter().enumerate() {
+                let y = tap(oy * 2, k, h);
+                for c in 0..4 {
+                    sum[c] += weight * temp[(y * out_w + ox) * 4 + c];
+                }
+            }
+            for c in 0..4 {
+                out[c] = ((sum[c] + 128) / 256) as u8;
+            }
+        }
+    }
+    RgbaImage { width: out_w as u32, height: out_h as u32, pixels }
+}
+
+// Upsamples a reduced level to `width` x `height` by interpolating with
+// the same kernel: even positions take (1 6 1) / 8 of their neighbours,
+// odd ones the mean of the two either side
+fn pyramid_expand(image: &RgbaImage, width: usize, height: usize) -> Vec<f32> {
+    let (w, h) = (image.width as usize, image.height as usize);
+    // (coarse index, weight) taps for each fine index
+    let taps = |len: usize, coarse_len: usize| -> Vec<Vec<(usize, f32)>> {
+        (0..len)
+            .map(|i| {
+                let c = i / 2;
+                let next = (c + 1).min(coarse_len - 1);
+                if i % 2 == 0 {
+                    vec![(c.saturating_sub(1), 0.125), (c, 0.75), (next, 0.125)]
+                } else {
+                    vec![(c, 0.5), (next, 0.5)]
+                }
+            })
+            .collect()
+    };
+    let (columns, rows) = (taps(width, w), taps(height, h));
+
+    let mut temp = vec![0.0f32; width * h * 4];
+    for y in 0..h {
+        let row = &image.pixels[y * w * 4..][..w * 4];
+        for (out, column) in temp[y * width * 4..][..width * 4].chunks_exact_mut(4).zip(&columns) {
+            for &(x, weight) in column {
+                for c in 0..4 {
+                    out[c] += weight * row[x * 4 + c] as f32;
+                }
+            }
+        }
+    }
+
+    let mut result = vec![0.0f32; width * height * 4];
+    for (out_row, row_taps) in result.chunks_exact_mut(width * 4).zip(&rows) {
+        for &(y, weight) in row_taps {
+            let src = &temp[y * width * 4..][..width * 4];
+            for (out, &v) in out_row.iter_mut().zip(src) {
+                *out += weight * v;
+            }
+        }
+    }
+    result
+}
+
+// --- Seam Carving ---
+// Content-aware resize to `target_width` x `target_height`: rather than
+// scaling everything, removes (or, to enlarge, duplicates) the connected
+// top-to-bottom or left-to-right paths of pixels with the least detail,
+// found by dynamic programming over the Sobel gradient magnitude. Widths
+// change first, then heights. Each seam costs a pass over the image, so
+// this is for interactive resizes by a few hundred pixels, not big
+// rescales. If cancelled, returns the image as carved so far; check its
+// `width` and `height`.
+#[wasm_bindgen]
+pub fn seam_carve(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    target_width: u32,
+    target_height: u32,
+    token: Option<CancellationToken>,
+) -> Result<RgbaImage, JsValue> {
+    check_image(&image_data, width, height)?;
+    image_len(target_width, target_height)?;
+    check_memory("seam_carve", width.max(target_width), height.max(target_height))?;
+    console_log!("Rust (WASM): Seam carving started...");
+    let token = token.as_ref();
+    let (w, h) = (width as usize, height as usize);
+    let (data, w) = carve_columns(image_data, w, h, target_width as usize, token);
+    let (data, h) = carve_columns(transpose(&data, w, h), h, w, target_height as usize, token);
+    console_log!("Rust (WASM): Seam carving finished.");
+    Ok(RgbaImage {
+        width: w as u32,
+        height: h as u32,
+        pixels: transpose(&data, h, w),
+    })
+}
+
+// Removes or inserts vertical seams until the image is `target` wide;
+// returns the new pixels and width
+fn carve_columns(
+    mut data: Vec<u8>,
+    mut width: usize,
+    height: usize,
+    target: usize,
+    token: Option<&CancellationToken>,
+) -> (Vec<u8>, usize) {
+    while width > target && !cancelled(token) {
+        let seam = find_seam(&data, width, height);
+        data = remove_seam(&data, width, &seam);
+        width -= 1;
+    }
+    // Inserting the same cheapest seam over and over would just smear it,
+    // so each batch finds the `count` cheapest distinct seams (removing
+    // them one after another from a scratch copy) and duplicates them all
+    while width < target && !cancelled(token) {
+        let count = (target - width).min(width);
+        let mut scratch = data.clone();
+        let mut columns: Vec<usize> = (0..height).flat_map(|_| 0..width).collect();
+        let mut duplicate = vec![false; width * height];
+        for remaining in (width - count + 1..=width).rev() {
+            if cancelled(token) {
+                break;
+            }
+            let seam = find_seam(&scratch, remaining, height);
+            for (y, &x) in seam.iter().enumerate() {
+                duplicate[y * width + columns[y * remaining + x]] = true;
+            }
+            scratch = remove_seam(&scratch, remaining, &seam);
+            columns = remove_seam_from(&columns, remaining, &seam);
+        }
+        let inserted = duplicate.iter().filter(|&&d| d).count() / height;
+        let mut grown = Vec::with_capacity((width + inserted) * height * 4);
+        for (row, marks) in data.chunks_exact(width * 4).zip(duplicate.chunks_exact(width)) {
+            for x in 0..width {
+                let pixel = &row[x * 4..][..4];
+                grown.extend_from_slice(pixel);
+                if marks[x] {
+                    // Halfway between the seam and its right neighbour
+                    let next = &row[(x + 1).min(width - 1) * 4..][..4];
+                    grown.extend(pixel.iter().zip(next).map(|(&a, &b)| (a as u16 + b as u16).div_ceil(2) as u8));
+                }
+            }
+        }
+        data = grown;
+        width += inserted;
+    }
+    (data, width)
+}
+
+// Column of the least-energy 8-connected vertical path in each row
+fn find_seam(data: &[u8], width: usize, height: usize) -> Vec<usize> {
+    let mut cost: Vec<f32> = sobel_gradients(data, width, height)
+        .iter()
+        .map(|&(gx, gy)| gx.abs() + gy.abs())
+        .collect();
+    for y in 1..height {
+        let (above, row) = cost[(y - 1) * width..][..width * 2].split_at_mut(width);
+        for (x, c) in row.iter_mut().enumerate() {
+            let lo = x.saturating_sub(1);
+            *c += above[lo..(x + 2).min(width)].iter().copied().fold(f32::INFINITY, f32::min);
+        }
+    }
+
+    let mut seam = vec![0; height];
+    let last = &cost[(height - 1) * width..];
+    seam[height - 1] = (0..width).min_by(|&a, &b| last[a].total_cmp(&last[b])).unwrap_or(0);
+    for y in (0..height - 1).rev() {
+        let x = seam[y + 1];
+        let row = &cost[y * width..][..width];
+        seam[y] = (x.saturating_sub(1)..(x + 2).min(width)).min_by(|&a, &b| row[a].total_cmp(&row[b])).unwrap_or(x);
+    }
+    seam
+}
+
+fn remove_seam(data: &[u8], width: usize, seam: &[usize]) -> Vec<u8> {
+    remove_seam_from(pixels(data), width, seam).concat()
+}
+
+fn remove_seam_from<T: Copy>(plane: &[T], width: usize, seam: &[usize]) -> Vec<T> {
+    let mut result = Vec::with_capacity(plane.len() - seam.len());
+    for (row, &x) in plane.chunks_exact(width).zip(seam) {
+        result.extend_from_slice(&row[..x]);
+        result.extend_from_slice(&row[x + 1..]);
+    }
+    result
+}
+
+fn transpose(data: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut result = vec![0u8; data.len()];
+    for y in 0..height {
+        for x in 0..width {
+            result[(x * height + y) * 4..][..4].copy_from_slice(&data[(y * width + x) * 4..][..4]);
+        }
+    }
+    result
+}
+
+// --- Upscale ---
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpscaleMethod {
+    // Catmull-Rom; sharp, with slight halos on hard edges
+    Bicubic,
+    // Directional cubic convolution (Zhou, Shen and Xu, 2012): interpolates
+    // along edges rather than across them, so diagonals stay clean instead
+    // of stair-stepping. For photos.
+    EdgeDirected,
+    // Scale2x (EPX), twice over for 4x: copies pixels, rounding off
+    // diagonal staircases where exactly matching colours meet. For pixel
+    // art, where any blending would smear the palette.
+    PixelArt,
+}
+
+// Enlarges `factor` times (2 or 4) in each direction. Bicubic and
+// EdgeDirected work in linear light with premultiplied alpha like the
+// other resamplers here.
+#[wasm_bindgen]
+pub fn upscale(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    factor: u32,
+    method: UpscaleMethod,
+) -> Result<RgbaImage, JsValue> {
+    check_image(&image_data, width, height)?;
+    check_one_of("factor", factor, &[2, 4])?;
+    let (out_width, out_height) = (width.saturating_mul(factor), height.saturating_mul(factor));
+    image_len(out_width, out_height)?;
+    check_memory("upscale", out_width, out_height)?;
+    console_log!("Rust (WASM): Upscale started...");
+    let (w, h) = (width as usize, height as usize);
+    let pixels = match method {
+        UpscaleMethod::Bicubic => {
+            let linear = to_premultiplied_linear(&image_data);
+            from_premultiplied_linear(&bicubic(&linear, w, h, factor as usize))
+        }
+        UpscaleMethod::EdgeDirected => {
+            let mut linear = to_premultiplied_linear(&image_data);
+            let (mut w, mut h) = (w, h);
+            for _ in 0..factor / 2 {
+                linear = edge_directed_2x(&linear, w, h);
+                (w, h) = (w * 2, h * 2);
+            }
+            from_premultiplied_linear(&linear)
+        }
+        UpscaleMethod::PixelArt => {
+            let mut data = image_data;
+            let (mut w, mut h) = (w, h);
+            for _ in 0..factor / 2 {
+                data = scale2x(&data, w, h);
+                (w, h) = (w * 2, h * 2);
+            }
+            data
+        }
+    };
+    console_log!("Rust (WASM): Upscale finished.");
+    Ok(RgbaImage {
+        width: out_width,
+        height: out_height,
+        pixels,
+    })
+}
+
+// Catmull-Rom weights for the four taps around a sample `t` (0..1) past
+// the second one
+fn catmull_rom(t: f32) -> [f32; 4] {
+    let (t2, t3) = (t * t, t * t * t);
+    [
+        (-t3 + 2.0 * t2 - t) / 2.0,
+        (3.0 * t3 - 5.0 * t2 + 2.0) / 2.0,
+        (-3.0 * t3 + 4.0 * t2 + t) / 2.0,
+        (t3 - t2) / 2.0,
+    ]
+}
+
+fn bicubic(source: &[[f32; 4]], width: usize, height: usize, factor: usize) -> Vec<[f32; 4]> {
+    // (first tap, weights) per output index; taps past the ends clamp
+    let taps = |len: usize| -> Vec<([usize; 4], [f32; 4])> {
+        (0..len * factor)
+            .map(|o| {
+                let position = (o as f32 + 0.5) / factor as f32 - 0.5;
+                let base = position.floor();
+                let index = |k: isize| (base as isize + k).clamp(0, len as isize - 1) as usize;
+                ([index(-1), index(0), index(1), index(2)], catmull_rom(position - base))
+            })
+            .collect()
+    };
+    let (columns, rows) = (taps(width), taps(height));
+    let out_w = width * factor;
+
+    let mut temp = vec![[0.0f32; 4]; out_w * height];
+    for y in 0..height {
+        let src_row = &source[y * width..][..width];
+        for (out, (xs, weights)) in temp[y * out_w..][..out_w].iter_mut().zip(&columns) {
+            *out = weighted_sum(xs.iter().zip(weights).map(|(&x, &weight)| (&src_row[x], weight)));
+        }
+    }
+    let mut result = Vec::with_capacity(out_w * height * factor);
+    for (ys, weights) in &rows {
+        for x in 0..out_w {
+            result.push(weighted_sum(ys.iter().zip(weights).map(|(&y, &weight)| (&temp[y * out_w + x], weight))));
+        }
+    }
+    result
+}
+
+// Ratio of gradient sums above which one direction counts as an edge and
+// the other is used alone; below it both are blended
+const DCCI_EDGE_RATIO: f32 = 1.15;
+// Exponent sharpening the blend towards the smoother direction
+const DCCI_WEIGHT_POWER: i32 = 5;
+
+// One doubling. Source pixels land on even (x, y); the odd / odd centres
+// are interpolated first along whichever diagonal is smoother, then the
+// rest along whichever of horizontal and vertical is, using the centres.
+fn edge_directed_2x(source: &[[f32; 4]], width: usize, height: usize) -> Vec<[f32; 4]> {
+    let (out_w, out_h) = (width * 2, height * 2);
+    let mut grid = vec![[0.0f32; 4]; out_w * out_h];
+    for y in 0..height {
+        for x in 0..width {
+            grid[y * 2 * out_w + x * 2] = source[y * width + x];
+        }
+    }
+
+    // Positions past an edge reflect back onto the nearest position with
+    // the same parity, which has already been filled
+    let clamp = |v: isize, len: usize| {
+        if v < 0 {
+            v.rem_euclid(2) as usize
+        } else if v >= len as isize {
+            len - 2 + (v as usize % 2)
+        } else {
+            v as usize
+        }
+    };
+    let at = |grid: &[[f32; 4]], x: isize, y: isize| grid[clamp(y, out_h) * out_w + clamp(x, out_w)];
+    // Luminance on a 0-255 scale, which the thresholds above assume
+    let level = |p: [f32; 4]| (0.2126 * p[0] + 0.7152 * p[1] + 0.0722 * p[2]) * 255.0;
+
+    // Summed luminance differences between the pixels at `pairs` (offsets
+    // from (x, y)) and those `step` further on
+    let gradient = |grid: &[[f32; 4]], x: isize, y: isize, pairs: &[(isize, isize)], step: (isize, isize)| -> f32 {
+        pairs
+            .iter()
+            .map(|&(dx, dy)| (level(at(grid, x + dx, y + dy)) - level(at(grid, x + dx + step.0, y + dy + step.1))).abs())
+            .sum()
+    };
+    // (-1, 9, 9, -1) / 16 through the points at +-1 and +-3 steps along `u`
+    let cubic = |grid: &[[f32; 4]], x: isize, y: isize, u: (isize, isize)| -> [f32; 4] {
+        let mut out = [0.0f32; 4];
+        for (k, weight) in [(-3, -1.0), (-1, 9.0), (1, 9.0), (3, -1.0)] {
+            let p = at(grid, x + u.0 * k, y + u.1 * k);
+            for c in 0..4 {
+                out[c] += p[c] * weight / 16.0;
+            }
+        }
+        out
+    };
+    let blend = |grid: &[[f32; 4]], x: isize, y: isize, d1: f32, d2: f32, u1: (isize, isize), u2: (isize, isize)| {
+        // The direction with the larger gradient crosses the edge
+        if 1.0 + d1 > DCCI_EDGE_RATIO * (1.0 + d2) {
+            return cubic(grid, x, y, u2);
+        }
+        if 1.0 + d2 > DCCI_EDGE_RATIO * (1.0 + d1) {
+            return cubic(grid, x, y, u1);
+        }
+        let (w1, w2) = (1.0 / (1.0 + d1.powi(DCCI_WEIGHT_POWER)), 1.0 / (1.0 + d2.powi(DCCI_WEIGHT_POWER)));
+        let (p1, p2) = (cubic(grid, x, y, u1), cubic(grid, x, y, u2));
+        let mut out = [0.0f32; 4];
+        for c in 0..4 {
+            out[c] = (w1 * p1[c] + w2 * p2[c]) / (w1 + w2);
+        }
+        out
+    };
+
+    // Diagonal differences across the 4x4 source pixels around the centre:
+    // d1 along up-right, d2 along down-right
+    let mut diagonal_pairs = Vec::new();
+    for b in [-3, -1, 1] {
+        for a in [-3, -1, 1] {
+            diagonal_pairs.push((a, b));
+        }
+    }
+    let up_right: Vec<_> = diagonal_pairs.iter().map(|&(a, b)| (a, b + 2)).collect();
+    for y in (1..out_h as isize).step_by(2) {
+        for x in (1..out_w as isize).step_by(2) {
+            let d1 = gradient(&grid, x, y, &up_right, (2, -2));
+            let d2 = gradient(&grid, x, y, &diagonal_pairs, (2, 2));
+            grid[y as usize * out_w + x as usize] = blend(&grid, x, y, d1, d2, (1, -1), (1, 1));
+        }
+    }
+
+    // Horizontal differences in the diamond of known pixels around an edge
+    // midpoint, and their transpose for vertical
+    const HORIZONTAL_PAIRS: [(isize, isize); 5] = [(-1, 0), (-2, -1), (0, -1), (-2, 1), (0, 1)];
+    const VERTICAL_PAIRS: [(isize, isize); 5] = [(0, -1), (-1, -2), (-1, 0), (1, -2), (1, 0)];
+    for y in 0..out_h as isize {
+        for x in ((1 - y % 2)..out_w as isize).step_by(2) {
+            let d1 = gradient(&grid, x, y, &HORIZONTAL_PAIRS, (2, 0));
+            let d2 = gradient(&grid, x, y, &VERTICAL_PAIRS, (0, 2));
+            grid[y as usize * out_w + x as usize] = blend(&grid, x, y, d1, d2, (1, 0), (0, 1));
+        }
+    }
+    grid
+}
+
+// Each pixel becomes 2x2; a corner takes the colour of the two neighbours
+// meeting there when they match each other but not the opposite pair
+fn scale2x(data: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let source = pixels(data);
+    let out_w = width * 2;
+    let mut result = vec![0u8; data.len() * 4];
+    for y in 0..height {
+        for x in 0..width {
+            let p = source[y * width + x];
+            let up = source[y.saturating_sub(1) * width + x];
+            let down = source[(y + 1).min(height - 1) * width + x];
+            let left = source[y * width + x.saturating_sub(1)];
+            let right = source[y * width + (x + 1).min(width - 1)];
+            let corners = [
+                if left == up && left != down && up != right { up } else { p },
+                if up == right && up != left && right != down { right } else { p },
+                if down == left && down != right && left != up { left } else { p },
+                if right == down && right != up && down != left { down } else { p },
+            ];
+            for (i, corner) in corners.iter().enumerate() {
+                let (ox, oy) = (x * 2 + i % 2, y * 2 + i / 2);
+                result[(oy * out_w + ox) * 4..][..4].copy_from_slice(corner);
+            }
+        }
+    }
+    result
+}
+
+// Separable resample in linear, premultiplied light. The filter widens
+// with the scale factor when shrinking, so every source pixel contributes
+// (no aliasing), and is its plain self when enlarging.
+pub(crate) fn resample_linear(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    out_w: usize,
+    out_h: usize,
+    filter: ResizeFilter,
+) -> Vec<u8> {
+    let premultiplied = to_premultiplied_linear(data);
+
+    // Horizontal: width x height -> out_w x height
+    let columns = filter_weights(width, out_w, filter);
+    let mut temp = vec![[0.0f32; 4]; out_w * height];
+    for y in 0..height {
+        let src_row = &premultiplied[y * width..][..width];
+        for (out, taps) in temp[y * out_w..][..out_w].iter_mut().zip(&columns) {
+            *out = weighted_sum(taps.iter().map(|&(x, weight)| (&src_row[x], weight)));
+        }
+    }
+
+    // Vertical: out_w x height -> out_w x out_h
+    let rows = filter_weights(height, out_h, filter);
+    let mut result = Vec::with_capacity(out_w * out_h);
+    for taps in &rows {
+        for ox in 0..out_w {
+            result.push(weighted_sum(taps.iter().map(|&(y, weight)| (&temp[y * out_w + ox], weight))));
+        }
+    }
+    from_premultiplied_linear(&result)
+}
+
+pub(crate) fn to_premultiplied_linear(data: &[u8]) -> Vec<[f32; 4]> {
+    let to_linear = srgb_to_linear_table();
+    data.chunks_exact(4)
+        .map(|p| {
+            let alpha = p[3] as f32 / 255.0;
+            [
+                to_linear[p[0] as usize] * alpha,
+                to_linear[p[1] as usize] * alpha,
+                to_linear[p[2] as usize] * alpha,
+                alpha,
+            ]
+        })
+        .collect()
+}
+
+// Back to straight sRGB bytes, clamping whatever the filter overshot
+pub(crate) fn from_premultiplied_linear(pixels: &[[f32; 4]]) -> Vec<u8> {
+    let encoder = SrgbEncoder::new();
+    let mut result = vec![0u8; pixels.len() * 4];
+    for (out, pixel) in result.chunks_exact_mut(4).zip(pixels) {
+        let alpha = pixel[3].clamp(0.0, 1.0);
+        if alpha > 0.0 {
+            for c in 0..3 {
+                out[c] = encoder.encode(pixel[c] / alpha);
+            }
+        }
+        out[3] = (alpha * 255.0 + 0.5) as u8;
+    }
+    result
+}
+
+pub(crate) fn weighted_sum<'a>(taps: impl Iterator<Item = (&'a [f32; 4], f32)>) -> [f32; 4] {
+    let mut sum = [0.0f32; 4];
+    for (pixel, weight) in taps {
+        for (s, v) in sum.iter_mut().zip(pixel) {
+            *s += v * weight;
+        }
+    }
+    sum
+}
+
+// For each output index, the (source index, weight) pairs it reads, with
+// weights summing to 1. Taps past either end clamp to the edge pixel.
+fn filter_weights(in_len: usize, out_len: usize, filter: ResizeFilter) -> Vec<Vec<(usize, f32)>> {
+    let scale = in_len as f32 / out_len as f32;
+    let stretch = scale.max(1.0);
+    let support = filter.support() * stretch;
+    (0..out_len)
+        .map(|o| {
+            let center = (o as f32 + 0.5) * scale;
+            let first = (center - support).floor() as isize;
+            let last = (center + support).ceil() as isize;
+            let mut taps: Vec<(usize, f32)> = Vec::new();
+            for i in first..=last {
+                let distance = (i as f32 + 0.5 - center).abs() / stretch;
+                if distance >= filter.support() {
+                    continue;
+                }
+                let weight = filter.weight(distance);
+                let src = i.clamp(0, in_len as isize - 1) as usize;
+                match taps.last_mut() {
+                    Some(last) if last.0 == src => last.1 += weight,
+                    _ => taps.push((src, weight)),
+                }
+            }
+            let total: f32 = taps.iter().map(|&(_, w)| w).sum();
+            for tap in taps.iter_mut() {
+                tap.1 /= total;
+            }
+            taps
+        })
+        .collect()
+}
@@ -0,0 +1,343 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_image, check_radius, check_range, check_yuv420};
+use crate::memory::check_memory;
+use crate::scheduler::StageFilter;
+use crate::{blur_pixels, edge_detection_pixels, sharpen_pixels, MAX_SHARPEN_STRENGTH};
+
+// ========================================================================
+// VIDEO FRAMES
+// Per-frame fixes for captured video. Interlaced sources store two fields
+// (the even and the odd rows) captured at different instants, which shows
+// up as combing on anything that moves.
+// ========================================================================
+
+// Below this much change (summed over R, G and B) between frames a pixel
+// counts as static, and yadif-lite weaves it instead of interpolating
+const STATIC_THRESHOLD: u32 = 24;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeinterlaceMethod {
+    // Rebuild the odd rows by averaging the even rows around them: no
+    // combing, but half the vertical detail
+    Bob,
+    // Keep both fields as they are; right for static scenes and for
+    // progressive material that's flagged as interlaced
+    Weave,
+    // Weave where nothing moved since `previous`, and fill moving parts
+    // with an edge-directed interpolation of the even rows, so static
+    // detail stays sharp and diagonals don't turn into stairs
+    YadifLite,
+}
+
+// --- Deinterlace ---
+// Keeps the top field (even rows) and rebuilds the bottom one. `previous`
+// is the last input frame, used by yadif-lite to tell moving pixels from
+// static ones; without it every pixel is treated as moving.
+#[wasm_bindgen]
+pub fn deinterlace(
+    image_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    method: DeinterlaceMethod,
+    previous: Option<Vec<u8>>,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(&image_data, width, height)?;
+    if let Some(previous) = &previous {
+        check_image(previous, width, height)?;
+    }
+    check_memory("deinterlace", width, height)?;
+    if method == DeinterlaceMethod::Weave {
+        return Ok(image_data);
+    }
+
+    console_log!("Rust (WASM): Deinterlace started...");
+    let result = deinterlace_pixels(&image_data, width as usize, height as usize, method, previous.as_deref());
+    console_log!("Rust (WASM): Deinterlace finished.");
+    Ok(result)
+}
+
+pub(crate) fn deinterlace_pixels(
+    image_data: &[u8],
+    width: usize,
+    height: usize,
+    method: DeinterlaceMethod,
+    previous: Option<&[u8]>,
+) -> Vec<u8> {
+    let row_bytes = width * 4;
+    let mut result = image_data.to_vec();
+    for y in (1..height).step_by(2) {
+        let above = &image_data[(y - 1) * row_bytes..][..row_bytes];
+        // The last row of an even-height frame only has a neighbour above
+        let below_row = if y + 1 < height { y + 1 } else { y - 1 };
+        let below = &image_data[below_row * row_bytes..][..row_bytes];
+        let out = &mut result[y * row_bytes..][..row_bytes];
+
+        match method {
+            DeinterlaceMethod::Weave => {}
+            DeinterlaceMethod::Bob => {
+                for ((out, a), b) in out.chunks_exact_mut(4).zip(above.chunks_exact(4)).zip(below.chunks_exact(4)) {
+                    for c in 0..3 {
+                        out[c] = (a[c] as u16 + b[c] as u16).div_ceil(2) as u8;
+                    }
+                }
+            }
+            DeinterlaceMethod::YadifLite => {
+                for x in 0..width {
+                    let idx = x * 4;
+                    if let Some(previous) = previous {
+                        let prev_row = |row: usize| &previous[row * row_bytes + idx..][..3];
+                        let motion = channel_diff(&above[idx..idx + 3], prev_row(y - 1))
+                            + channel_diff(&below[idx..idx + 3], prev_row(below_row))
+                            + channel_diff(&image_data[y * row_bytes + idx..][..3], prev_row(y));
+                        if motion < STATIC_THRESHOLD {
+                            continue;
+                        }
+                    }
+                    let (a, b) = edge_directed_pair(above, below, width, x);
+                    for c in 0..3 {
+                        out[idx + c] = (above[a + c] as u16 + below[b + c] as u16).div_ceil(2) as u8;
+                    }
+                }
+            }
+        }
+    }
+    result
+}
+
+fn channel_diff(a: &[u8], b: &[u8]) -> u32 {
+    a.iter().zip(b).map(|(&a, &b)| a.abs_diff(b) as u32).sum()
+}
+
+// Edge-based line averaging: of the three lines through (x, y) joining the
+// rows above and below (left diagonal, vertical, right diagonal), pick the
+// one whose ends match best and return the byte offsets of those ends
+fn edge_directed_pair(above: &[u8], below: &[u8], width: usize, x: usize) -> (usize, usize) {
+    let mut best = (x * 4, x * 4);
+    let mut best_diff = channel_diff(&above[x * 4..x * 4 + 3], &below[x * 4..x * 4 + 3]);
+    for d in [-1isize, 1] {
+        let (xa, xb) = (x as isize + d, x as isize - d);
+        if xa < 0 || xb < 0 || xa >= width as isize || xb >= width as isize {
+            continue;
+        }
+        let (a, b) = (xa as usize * 4, xb as usize * 4);
+        let diff = channel_diff(&above[a..a + 3], &below[b..b + 3]);
+        if diff < best_diff {
+            best = (a, b);
+            best_diff = diff;
+        }
+    }
+    best
+}
+
+// ========================================================================
+// YUV 4:2:0
+// WebCodecs frames usually arrive as I420 (Y plane, then U, then V) or
+// NV12 (Y plane, then interleaved U/V), with one chroma sample per 2x2
+// block of pixels. Converting here avoids a slow per-pixel pass in JS, and
+// `filter_yuv_luma` skips the conversion entirely for filters that only
+// need brightness.
+// ========================================================================
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum YuvMatrix {
+    // Standard-definition video and most webcams
+    Bt601,
+    // HD video
+    Bt709,
+}
+
+impl YuvMatrix {
+    // Luma weights of red and blue
+    fn weights(self) -> (f32, f32) {
+        match self {
+            YuvMatrix::Bt601 => (0.299, 0.114),
+            YuvMatrix::Bt709 => (0.2126, 0.0722),
+        }
+    }
+}
+
+// Limited ("video") range puts black at Y = 16 and white at 235, with
+// chroma in 16..240; full range uses all of 0..255
+struct YuvRange {
+    luma_offset: f32,
+    luma_scale: f32,
+    chroma_scale: f32,
+}
+
+impl YuvRange {
+    fn new(full_range: bool) -> YuvRange {
+        if full_range {
+            YuvRange { luma_offset: 0.0, luma_scale: 1.0, chroma_scale: 1.0 }
+        } else {
+            YuvRange { luma_offset: 16.0, luma_scale: 219.0 / 255.0, chroma_scale: 224.0 / 255.0 }
+        }
+    }
+}
+
+// --- YUV -> RGBA ---
+#[wasm_bindgen]
+pub fn i420_to_rgba(
+    frame: &[u8],
+    width: u32,
+    height: u32,
+    matrix: YuvMatrix,
+    full_range: bool,
+) -> Result<Vec<u8>, JsValue> {
+    check_yuv420(frame, width, height)?;
+    let (w, h) = (width as usize, height as usize);
+    let chroma = w.div_ceil(2) * h.div_ceil(2);
+    let (u_plane, v_plane) = frame[w * h..].split_at(chroma);
+    Ok(yuv420_to_rgba(&frame[..w * h], w, h, matrix, full_range, |i| (u_plane[i], v_plane[i])))
+}
+
+#[wasm_bindgen]
+pub fn nv12_to_rgba(
+    frame: &[u8],
+    width: u32,
+    height: u32,
+    matrix: YuvMatrix,
+    full_range: bool,
+) -> Result<Vec<u8>, JsValue> {
+    check_yuv420(frame, width, height)?;
+    let (w, h) = (width as usize, height as usize);
+    let uv_plane = &frame[w * h..];
+    Ok(yuv420_to_rgba(&frame[..w * h], w, h, matrix, full_range, |i| (uv_plane[2 * i], uv_plane[2 * i + 1])))
+}
+
+// `chroma_at(i)` returns the (U, V) pair of chroma block `i`
+fn yuv420_to_rgba(
+    y_plane: &[u8],
+    width: usize,
+    height: usize,
+    matrix: YuvMatrix,
+    full_range: bool,
+    chroma_at: impl Fn(usize) -> (u8, u8),
+) -> Vec<u8> {
+    let (kr, kb) = matrix.weights();
+    let kg = 1.0 - kr - kb;
+    let range = YuvRange::new(full_range);
+    let chroma_width = width.div_ceil(2);
+    let mut rgba = vec![0u8; width * height * 4];
+
+    for y in 0..height {
+        for x in 0..width {
+            let luma = (y_plane[y * width + x] as f32 - range.luma_offset) / range.luma_scale;
+            let (u, v) = chroma_at((y / 2) * chroma_width + x / 2);
+            let cb = (u as f32 - 128.0) / range.chroma_scale;
+            let cr = (v as f32 - 128.0) / range.chroma_scale;
+
+            let r = luma + 2.0 * (1.0 - kr) * cr;
+            let b = luma + 2.0 * (1.0 - kb) * cb;
+            let g = (luma - kr * r - kb * b) / kg;
+            let out = &mut rgba[(y * width + x) * 4..][..4];
+            out[0] = (r + 0.5).clamp(0.0, 255.0) as u8;
+            out[1] = (g + 0.5).clamp(0.0, 255.0) as u8;
+            out[2] = (b + 0.5).clamp(0.0, 255.0) as u8;
+            out[3] = 255;
+        }
+    }
+    rgba
+}
+
+// --- RGBA -> I420 ---
+// Alpha is dropped; each chroma sample averages its 2x2 block
+#[wasm_bindgen]
+pub fn rgba_to_i420(
+    image_data: &[u8],
+    width: u32,
+    height: u32,
+    matrix: YuvMatrix,
+    full_range: bool,
+) -> Result<Vec<u8>, JsValue> {
+    check_image(image_data, width, height)?;
+    let (w, h) = (width as usize, height as usize);
+    let (kr, kb) = matrix.weights();
+    let kg = 1.0 - kr - kb;
+    let range = YuvRange::new(full_range);
+    let (chroma_w, chroma_h) = (w.div_ceil(2), h.div_ceil(2));
+    let mut frame = vec![0u8; w * h + 2 * chroma_w * chroma_h];
+    let (y_plane, chroma) = frame.split_at_mut(w * h);
+    let (u_plane, v_plane) = chroma.split_at_mut(chroma_w * chroma_h);
+
+    for cy in 0..chroma_h {
+        for cx in 0..chroma_w {
+            let (mut cb_sum, mut cr_sum, mut count) = (0.0f32, 0.0f32, 0.0f32);
+            for y in cy * 2..(cy * 2 + 2).min(h) {
+                for x in cx * 2..(cx * 2 + 2).min(w) {
+                    let p = &image_data[(y * w + x) * 4..][..3];
+                    let (r, g, b) = (p[0] as f32, p[1] as f32, p[2] as f32);
+                    let luma = kr * r + kg * g + kb * b;
+                    y_plane[y * w + x] = (luma * range.luma_scale + range.luma_offset + 0.5).clamp(0.0, 255.0) as u8;
+                    cb_sum += (b - luma) / (2.0 * (1.0 - kb));
+                    cr_sum += (r - luma) / (2.0 * (1.0 - kr));
+                    count += 1.0;
+                }
+            }
+            let chroma_idx = cy * chroma_w + cx;
+            u_plane[chroma_idx] = (cb_sum / count * range.chroma_scale + 128.5).clamp(0.0, 255.0) as u8;
+            v_plane[chroma_idx] = (cr_sum / count * range.chroma_scale + 128.5).clamp(0.0, 255.0) as u8;
+        }
+    }
+    Ok(frame)
+}
+
+// --- Luma-Only Filters ---
+// Runs a filter on the Y plane of an I420 or NV12 frame (both start with
+// it) and returns the frame with the chroma untouched, except that
+// grayscale also neutralises it. The Y plane goes through the same kernels
+// as RGBA images, so results match filtering a gray image. `param` is the
+// blur radius (up to 1000; 0 leaves the frame as it is) or sharpen
+// strength (up to 10000), as in `RealtimeScheduler.add_stage`.
+#[wasm_bindgen]
+pub fn filter_yuv_luma(
+    mut frame: Vec<u8>,
+    width: u32,
+    height: u32,
+    filter: StageFilter,
+    param: u32,
+) -> Result<Vec<u8>, JsValue> {
+    check_yuv420(&frame, width, height)?;
+    // The Y plane is widened to RGBA for the kernels, so the RGBA filter's
+    // estimate holds
+    match filter {
+        StageFilter::Blur if param == 0 => {}
+        StageFilter::Blur => {
+            check_radius(param)?;
+            check_memory("blur", width, height)?;
+        }
+        StageFilter::EdgeDetection => check_memory("edge_detection", width, height)?,
+        StageFilter::Sharpen => {
+            check_range("strength", param, 0, MAX_SHARPEN_STRENGTH)?;
+            check_memory("sharpen", width, height)?;
+        }
+        StageFilter::Grayscale | StageFilter::Invert => {}
+    }
+    let luma_len = width as usize * height as usize;
+    let (y_plane, chroma) = frame.split_at_mut(luma_len);
+
+    match filter {
+        StageFilter::Grayscale => chroma.fill(128),
+        StageFilter::Invert => y_plane.iter_mut().for_each(|y| *y = 255 - *y),
+        StageFilter::Blur if param == 0 => {}
+        StageFilter::Blur | StageFilter::EdgeDetection | StageFilter::Sharpen => {
+            console_log!("Rust (WASM): Luma filter started...");
+            let mut gray: Vec<u8> = y_plane.iter().flat_map(|&y| [y, y, y, 255]).collect();
+            let gray = match filter {
+                StageFilter::Blur => {
+                    blur_pixels(&mut gray, width, height, param);
+                    gray
+                }
+                StageFilter::EdgeDetection => edge_detection_pixels(&gray, width, height),
+                _ => sharpen_pixels(&gray, width, height, param),
+            };
+            for (y, pixel) in y_plane.iter_mut().zip(gray.chunks_exact(4)) {
+                *y = pixel[0];
+            }
+            console_log!("Rust (WASM): Luma filter finished.");
+        }
+    }
+    Ok(frame)
+}
@@ -0,0 +1,116 @@
+use wasm_bindgen::prelude::*;
+
+use crate::adjust::SrgbEncoder;
+use crate::color::srgb_to_linear_table;
+use crate::error::check_rgba;
+use crate::pixels::pixels_mut;
+
+// ========================================================================
+// COLOUR VISION DEFICIENCY
+// `simulate_cvd` shows an image as someone missing one of the three cone
+// types would see it, for checking that charts, maps and UI stay readable;
+// `daltonize` recolours it so the differences such a viewer would lose
+// come back as ones they can see. Both work in linear light: each colour
+// is taken to LMS cone responses, the missing cone's response is rebuilt
+// from the other two (Viénot, Brettel and Mollon, "Digital Video
+// Colourmaps for Checking the Legibility of Displays by Dichromats",
+// 1999), and the result taken back to RGB. Alpha is kept.
+// ========================================================================
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Deficiency {
+    // No long-wavelength (red) cones: reds look dark and close to greens
+    Protanopia,
+    // No medium-wavelength (green) cones, the most common form: reds and
+    // greens merge, without the darkening
+    Deuteranopia,
+    // No short-wavelength (blue) cones, rare: blues and greens merge, as do
+    // yellows and pinks
+    Tritanopia,
+}
+
+// Linear RGB to LMS, and back
+const RGB_TO_LMS: [[f32; 3]; 3] = [
+    [17.882_4, 43.516_1, 4.119_35],
+    [3.455_65, 27.155_4, 3.867_14],
+    [0.029_956_6, 0.184_309, 1.467_09],
+];
+const LMS_TO_RGB: [[f32; 3]; 3] = [
+    [0.080_944_45, -0.130_504_41, 0.116_721_07],
+    [-0.010_248_53, 0.054_019_33, -0.113_614_71],
+    [-0.000_365_297, -0.004_121_615, 0.693_511_4],
+];
+
+fn multiply(matrix: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    matrix.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+impl Deficiency {
+    // The colour a dichromat confuses `rgb` with, both linear
+    fn simulate(self, rgb: [f32; 3]) -> [f32; 3] {
+        let [l, m, s] = multiply(&RGB_TO_LMS, rgb);
+        let lms = match self {
+            Deficiency::Protanopia => [2.023_44 * m - 2.525_81 * s, m, s],
+            Deficiency::Deuteranopia => [l, 0.494_207 * l + 1.248_27 * s, s],
+            Deficiency::Tritanopia => [l, m, -0.395_913 * l + 0.801_109 * m],
+        };
+        multiply(&LMS_TO_RGB, lms)
+    }
+
+    // Where `daltonize` moves the lost difference: into the channels the
+    // viewer still tells apart
+    fn correction(self) -> [[f32; 3]; 3] {
+        match self {
+            Deficiency::Protanopia | Deficiency::Deuteranopia => [[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]],
+            Deficiency::Tritanopia => [[1.0, 0.0, 0.7], [0.0, 1.0, 0.7], [0.0, 0.0, 0.0]],
+        }
+    }
+}
+
+// --- Simulate CVD ---
+#[wasm_bindgen]
+pub fn simulate_cvd(image_data: Vec<u8>, deficiency: Deficiency) -> Result<Vec<u8>, JsValue> {
+    check_rgba(&image_data)?;
+    console_log!("Rust (WASM): CVD simulation started...");
+    let result = map_linear(image_data, |rgb| deficiency.simulate(rgb));
+    console_log!("Rust (WASM): CVD simulation finished.");
+    Ok(result)
+}
+
+// --- Daltonize ---
+// Adds back what `simulate_cvd` would take away, shifted into colours the
+// viewer can see (Fidaner, Lin and Ozguven's method). Colours that survive
+// the simulation unchanged, such as grays, are left alone.
+#[wasm_bindgen]
+pub fn daltonize(image_data: Vec<u8>, deficiency: Deficiency) -> Result<Vec<u8>, JsValue> {
+    check_rgba(&image_data)?;
+    console_log!("Rust (WASM): Daltonization started...");
+    let correction = deficiency.correction();
+    let result = map_linear(image_data, |rgb| {
+        let simulated = deficiency.simulate(rgb);
+        let error = [rgb[0] - simulated[0], rgb[1] - simulated[1], rgb[2] - simulated[2]];
+        let shift = multiply(&correction, error);
+        [rgb[0] + shift[0], rgb[1] + shift[1], rgb[2] + shift[2]]
+    });
+    console_log!("Rust (WASM): Daltonization finished.");
+    Ok(result)
+}
+
+// Applies `f` to every pixel's linear RGB; results out of gamut are clipped
+// by the encoder
+fn map_linear(mut image_data: Vec<u8>, f: impl Fn([f32; 3]) -> [f32; 3]) -> Vec<u8> {
+    let to_linear = srgb_to_linear_table();
+    let encoder = SrgbEncoder::new();
+    for pixel in pixels_mut(&mut image_data) {
+        let rgb = f([
+            to_linear[pixel[0] as usize],
+            to_linear[pixel[1] as usize],
+            to_linear[pixel[2] as usize],
+        ]);
+        for (out, value) in pixel.iter_mut().zip(rgb) {
+            *out = encoder.encode(value);
+        }
+    }
+    image_data
+}
@@ -0,0 +1,322 @@
+// Golden image snapshots: every filter runs on the fixture images in
+// tests/fixtures and its output is compared with the PNG stored in
+// tests/goldens, allowing each channel to be off by up to `TOLERANCE`
+// (SIMD, threading and libm differences round slightly differently).
+//
+//   cargo test --target x86_64-unknown-linux-gnu --test snapshots
+//   wasm-pack test --node -- --test snapshots
+//
+// After an intentional change to a filter's output, regenerate its goldens
+// natively and review the new images before committing them:
+//
+//   WASMFX_UPDATE_GOLDENS=1 cargo test --target x86_64-unknown-linux-gnu --test snapshots
+//
+// A new filter gets a line in `snapshots!` below; its goldens are written
+// by the first run with the variable set.
+
+use wasm_lib::adjust::{
+    apply_curves, apply_duotone, apply_equalize, apply_exposure, apply_gradient_map, apply_posterize, apply_saturation,
+    apply_sepia, apply_temperature_tint, apply_vibrance,
+};
+use wasm_lib::api::{BlurMethod, BlurOptions, SharpenOptions};
+use wasm_lib::binary::{apply_adaptive_threshold, apply_threshold, AdaptiveMethod};
+use wasm_lib::blur_effects::{apply_motion_blur, apply_radial_blur, apply_zoom_blur};
+use wasm_lib::color_transfer::{transfer_color, TransferMethod};
+use wasm_lib::composite::{apply_chroma_key, blend_images, render_drop_shadow, BlendMode};
+use wasm_lib::denoise::{apply_bilateral, apply_median};
+use wasm_lib::dither::{apply_dither_bayer, apply_dither_fs, quantize_to_palette, recolor_to_palette};
+use wasm_lib::inpaint::inpaint;
+use wasm_lib::morphology::{morph, KernelShape, MorphOp};
+use wasm_lib::palette::{extract_palette_kmeans, quantize_median_cut};
+use wasm_lib::stylize::{
+    apply_cartoon, apply_channel_glitch, apply_chromatic_aberration, apply_grain, apply_halftone, apply_kuwahara,
+    apply_pixel_sort, apply_pixelate, HalftoneMode, SortDirection,
+};
+use wasm_lib::vision::{daltonize, simulate_cvd, Deficiency};
+use wasm_lib::{
+    apply_canny, apply_edge_detection, apply_grayscale, apply_high_pass, apply_invert, apply_unsharp_mask, blur, sharpen,
+};
+
+const TOLERANCE: u8 = 2;
+
+type Filter = fn(Vec<u8>, u32, u32) -> Vec<u8>;
+
+struct Snapshot {
+    name: &'static str,
+    filter: Filter,
+    // The golden for each of `FIXTURES`, compiled in for wasm where the
+    // test can't read files (always `None` natively)
+    embedded: [Option<&'static [u8]>; 2],
+}
+
+const FIXTURES: [&str; 2] = ["shapes", "texture"];
+
+#[cfg(target_arch = "wasm32")]
+macro_rules! embed {
+    ($path:expr) => {
+        Some(include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/", $path)) as &[u8])
+    };
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+macro_rules! embed {
+    ($path:expr) => {
+        None
+    };
+}
+
+macro_rules! snapshots {
+    ($($name:ident => $filter:expr,)*) => {
+        const SNAPSHOTS: &[Snapshot] = &[$(
+            Snapshot {
+                name: stringify!($name),
+                filter: $filter,
+                embedded: [
+                    embed!(concat!("goldens/shapes-", stringify!($name), ".png")),
+                    embed!(concat!("goldens/texture-", stringify!($name), ".png")),
+                ],
+            },
+        )*];
+    };
+}
+
+// A second reference image for the two-image filters: the fixture with
+// its pixel order and channels reversed
+fn reversed(data: &[u8]) -> Vec<u8> {
+    data.iter().rev().copied().collect()
+}
+
+// White over the middle third of the image, for the masked filters
+fn centre_mask(width: u32, height: u32) -> Vec<u8> {
+    let (w, h) = (width as usize, height as usize);
+    let mut mask = vec![0u8; w * h * 4];
+    for y in h / 3..h * 2 / 3 {
+        mask[(y * w + w / 3) * 4..(y * w + w * 2 / 3) * 4].fill(255);
+    }
+    mask
+}
+
+fn blur_with(method: BlurMethod, radius: u32) -> BlurOptions {
+    let mut options = BlurOptions::new(radius);
+    options.set_method(method);
+    options
+}
+
+snapshots! {
+    grayscale => |data, _, _| apply_grayscale(data).unwrap(),
+    invert => |data, _, _| apply_invert(data).unwrap(),
+    blur => |data, w, h| blur(data, w, h, &blur_with(BlurMethod::Gaussian, 4)).unwrap(),
+    box_blur => |data, w, h| blur(data, w, h, &blur_with(BlurMethod::Box, 4)).unwrap(),
+    stack_blur => |data, w, h| blur(data, w, h, &blur_with(BlurMethod::Stack, 4)).unwrap(),
+    linear_light_blur => |data, w, h| {
+        let mut options = blur_with(BlurMethod::Gaussian, 4);
+        options.set_linear_light(true);
+        blur(data, w, h, &options).unwrap()
+    },
+    edge_detection => |data, w, h| apply_edge_detection(data, w, h).unwrap(),
+    canny => |data, w, h| apply_canny(data, w, h, 20.0, 60.0).unwrap(),
+    sharpen => |data, w, h| sharpen(data, w, h, &SharpenOptions::new(60)).unwrap(),
+    linear_light_sharpen => |data, w, h| {
+        let mut options = SharpenOptions::new(60);
+        options.linear_light = true;
+        sharpen(data, w, h, &options).unwrap()
+    },
+    unsharp_mask => |data, w, h| apply_unsharp_mask(data, w, h, 4, 1.5, 3, None).unwrap(),
+    high_pass => |data, w, h| apply_high_pass(data, w, h, 4, None).unwrap(),
+    median => |data, w, h| apply_median(data, w, h, 2, None).unwrap(),
+    bilateral => |data, w, h| apply_bilateral(data, w, h, 2.0, 30.0, None).unwrap(),
+    saturation => |data, _, _| apply_saturation(data, 0.5).unwrap(),
+    vibrance => |data, _, _| apply_vibrance(data, 0.6).unwrap(),
+    curves => |data, _, _| apply_curves(data, r#"{"rgb": [[0, 0], [64, 40], [192, 215], [255, 255]]}"#).unwrap(),
+    sepia => |data, _, _| apply_sepia(data, 0.8).unwrap(),
+    posterize => |data, _, _| apply_posterize(data, 4).unwrap(),
+    equalize => |data, w, h| apply_equalize(data, w, h).unwrap(),
+    exposure => |data, _, _| apply_exposure(data, -1.0).unwrap(),
+    temperature_tint => |data, _, _| apply_temperature_tint(data, 0.8, 0.3).unwrap(),
+    duotone => |data, _, _| apply_duotone(data, 0x1E32_64FF, 0xF037_A5FF).unwrap(),
+    gradient_map => |data, _, _| {
+        let stops = r##"[{"at": 0, "color": "#000000"}, {"at": 0.6, "color": "#d7263d"}, {"at": 1, "color": "#fff3b0"}]"##;
+        apply_gradient_map(data, stops).unwrap()
+    },
+    simulate_protanopia => |data, _, _| simulate_cvd(data, Deficiency::Protanopia).unwrap(),
+    daltonize_deuteranopia => |data, _, _| daltonize(data, Deficiency::Deuteranopia).unwrap(),
+    dither_fs => |data, w, h| apply_dither_fs(data, w, h, 1).unwrap(),
+    dither_bayer => |data, w, h| apply_dither_bayer(data, w, h, 8).unwrap(),
+    recolor_to_palette => |data, w, h| {
+        recolor_to_palette(data, w, h, vec![0x0F380F, 0x306230, 0x8BAC0F, 0x9BBC0F], true).unwrap()
+    },
+    palette_kmeans => |data, _, _| {
+        let palette = extract_palette_kmeans(&data, 6, 20).unwrap();
+        quantize_to_palette(data, palette).unwrap()
+    },
+    median_cut => |mut data, _, _| {
+        let indexed = quantize_median_cut(&data, 6).unwrap();
+        let palette = indexed.palette();
+        for (pixel, &index) in data.chunks_exact_mut(4).zip(&indexed.indices()) {
+            let rgb = palette[index as usize];
+            pixel[..3].copy_from_slice(&[(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8]);
+        }
+        data
+    },
+    pixelate => |data, w, h| apply_pixelate(data, w, h, 5).unwrap(),
+    kuwahara => |data, w, h| apply_kuwahara(data, w, h, 3, None).unwrap(),
+    motion_blur => |data, w, h| apply_motion_blur(data, w, h, 30.0, 9, None).unwrap(),
+    zoom_blur => |data, w, h| apply_zoom_blur(data, w, h, w as f32 / 2.0, h as f32 / 2.0, 0.3, None).unwrap(),
+    radial_blur => |data, w, h| apply_radial_blur(data, w, h, w as f32 / 2.0, h as f32 / 2.0, 15.0, None).unwrap(),
+    chromatic_aberration => |data, w, h| apply_chromatic_aberration(data, w, h, 4.0).unwrap(),
+    pixel_sort => |data, w, h| apply_pixel_sort(data, w, h, 100, SortDirection::Horizontal).unwrap(),
+    channel_glitch => |data, w, h| apply_channel_glitch(data, w, h, 6).unwrap(),
+    halftone_gray => |data, w, h| apply_halftone(data, w, h, 4, 45.0, HalftoneMode::Grayscale, None).unwrap(),
+    halftone_cmyk => |data, w, h| apply_halftone(data, w, h, 4, 45.0, HalftoneMode::Cmyk, None).unwrap(),
+    cartoon => |data, w, h| apply_cartoon(data, w, h, None).unwrap(),
+    grain_mono => |data, w, h| apply_grain(data, w, h, 16.0, 1, true).unwrap(),
+    grain_color => |data, w, h| apply_grain(data, w, h, 16.0, 1, false).unwrap(),
+    transfer_reinhard => |data, w, h| transfer_color(&reversed(&data), data, w, h, TransferMethod::Reinhard).unwrap(),
+    transfer_histogram => |data, w, h| transfer_color(&reversed(&data), data, w, h, TransferMethod::Histogram).unwrap(),
+    blend_multiply => |data, w, h| blend_images(data.clone(), &reversed(&data), w, h, BlendMode::Multiply, 1.0).unwrap(),
+    blend_soft_light => |data, w, h| blend_images(data.clone(), &reversed(&data), w, h, BlendMode::SoftLight, 0.8).unwrap(),
+    drop_shadow => |mut data, w, h| {
+        // Cut the image down to its centre so the shadow falls around it
+        for (pixel, mask) in data.chunks_exact_mut(4).zip(centre_mask(w, h).chunks_exact(4)) {
+            pixel[3] = mask[0];
+        }
+        render_drop_shadow(data, w, h, 4, 6, 6, 0x000000A0).unwrap()
+    },
+    chroma_key => |data, _, _| apply_chroma_key(data, 0x00B1_40FF, 0.5, 0.3, 0.6).unwrap(),
+    threshold => |data, w, h| apply_threshold(data, w, h, 128).unwrap(),
+    adaptive_threshold => |data, w, h| apply_adaptive_threshold(data, w, h, AdaptiveMethod::Mean, 4, 3.0).unwrap(),
+    erode_square => |data, w, h| morph(data, w, h, MorphOp::Erode, KernelShape::Square, 2, None).unwrap(),
+    dilate_cross => |data, w, h| morph(data, w, h, MorphOp::Dilate, KernelShape::Cross, 3, None).unwrap(),
+    close_disk => |data, w, h| morph(data, w, h, MorphOp::Close, KernelShape::Disk, 3, None).unwrap(),
+    inpaint => |data, w, h| inpaint(data, w, h, &centre_mask(w, h)).unwrap(),
+}
+
+struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+fn decode(bytes: &[u8]) -> Image {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info().expect("not a PNG");
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).expect("corrupt PNG");
+    assert_eq!(info.color_type, png::ColorType::Rgba, "snapshot images must be RGBA");
+    pixels.truncate(info.buffer_size());
+    Image { width: info.width, height: info.height, pixels }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn encode(image: &Image) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, image.width, image.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&image.pixels).unwrap();
+    writer.finish().unwrap();
+    bytes
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn path(name: &str) -> String {
+    format!("{}/tests/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn fixture(name: &str) -> Vec<u8> {
+    let bytes: &[u8] = match name {
+        "shapes" => include_bytes!("fixtures/shapes.png"),
+        "texture" => include_bytes!("fixtures/texture.png"),
+        _ => panic!("unknown fixture {}", name),
+    };
+    bytes.to_vec()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn fixture(name: &str) -> Vec<u8> {
+    read(&format!("fixtures/{}.png", name)).expect("missing fixture")
+}
+
+// Files under tests/; wasm builds have everything compiled in instead
+#[cfg(not(target_arch = "wasm32"))]
+fn read(name: &str) -> Option<Vec<u8>> {
+    std::fs::read(path(name)).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read(_name: &str) -> Option<Vec<u8>> {
+    None
+}
+
+// Describes how `actual` differs from `expected` beyond the tolerance
+fn compare(actual: &Image, expected: &Image) -> Result<(), String> {
+    if (actual.width, actual.height) != (expected.width, expected.height) {
+        return Err(format!(
+            "size {}x{}, golden is {}x{}",
+            actual.width, actual.height, expected.width, expected.height
+        ));
+    }
+    let mut worst = (0u8, 0usize);
+    let mut over = 0usize;
+    for (i, (&a, &e)) in actual.pixels.iter().zip(&expected.pixels).enumerate() {
+        let diff = a.abs_diff(e);
+        if diff > TOLERANCE {
+            over += 1;
+        }
+        if diff > worst.0 {
+            worst = (diff, i);
+        }
+    }
+    if over == 0 {
+        return Ok(());
+    }
+    let pixel = worst.1 / 4;
+    Err(format!(
+        "{} channels differ by more than {}, worst by {} at ({}, {}) channel {}",
+        over,
+        TOLERANCE,
+        worst.0,
+        pixel as u32 % actual.width,
+        pixel as u32 / actual.width,
+        worst.1 % 4
+    ))
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test::wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn filters_match_goldens() {
+    #[cfg(not(target_arch = "wasm32"))]
+    let update = std::env::var_os("WASMFX_UPDATE_GOLDENS").is_some();
+    let mut failures = Vec::new();
+
+    for (index, fixture_name) in FIXTURES.iter().enumerate() {
+        let source = decode(&fixture(fixture_name));
+        for snapshot in SNAPSHOTS {
+            let result = Image {
+                width: source.width,
+                height: source.height,
+                pixels: (snapshot.filter)(source.pixels.clone(), source.width, source.height),
+            };
+            let golden_name = format!("goldens/{}-{}.png", fixture_name, snapshot.name);
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if update {
+                std::fs::write(path(&golden_name), encode(&result)).expect("couldn't write golden");
+                continue;
+            }
+            let Some(golden) = snapshot.embedded[index].map(<[u8]>::to_vec).or_else(|| read(&golden_name)) else {
+                failures.push(format!("{}: missing, run with WASMFX_UPDATE_GOLDENS=1", golden_name));
+                continue;
+            };
+
+            if let Err(reason) = compare(&result, &decode(&golden)) {
+                failures.push(format!("{}: {}", golden_name, reason));
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "snapshot mismatches:\n  {}", failures.join("\n  "));
+}
@@ -0,0 +1,103 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use wasmfx_core::fft::{fft2d, Complex};
+use wasmfx_core::{filters, fractal};
+
+// Native timings for the image kernels on a 512x512 test pattern:
+//
+//   cargo bench -p wasmfx-core --target x86_64-unknown-linux-gnu
+//
+// (the target has to be given explicitly because wasm_lib's cargo config
+// builds for wasm32 by default). Criterion compares each run against the
+// last one and flags regressions.
+
+const WIDTH: usize = 512;
+const HEIGHT: usize = 512;
+
+// A smooth gradient with some hard edges, so neither the blurs nor the
+// edge filters degenerate
+fn test_image() -> Vec<u8> {
+    let mut data = vec![0u8; WIDTH * HEIGHT * 4];
+    for (i, pixel) in data.chunks_exact_mut(4).enumerate() {
+        let (x, y) = (i % WIDTH, i / WIDTH);
+        pixel[0] = (x * 255 / WIDTH) as u8;
+        pixel[1] = (y * 255 / HEIGHT) as u8;
+        pixel[2] = if (x / 32 + y / 32) % 2 == 0 { 40 } else { 220 };
+        pixel[3] = 255;
+    }
+    data
+}
+
+fn point_filters(c: &mut Criterion) {
+    let image = test_image();
+    let mut group = c.benchmark_group("point");
+    group.throughput(Throughput::Bytes(image.len() as u64));
+    group.bench_function("grayscale", |b| {
+        b.iter_batched_ref(|| image.clone(), |data| filters::grayscale(data), criterion::BatchSize::LargeInput)
+    });
+    group.bench_function("invert", |b| {
+        b.iter_batched_ref(|| image.clone(), |data| filters::invert(data), criterion::BatchSize::LargeInput)
+    });
+    group.finish();
+}
+
+fn blurs(c: &mut Criterion) {
+    let image = test_image();
+    let mut group = c.benchmark_group("blur");
+    group.throughput(Throughput::Bytes(image.len() as u64));
+    for radius in [3u32, 15] {
+        group.bench_with_input(BenchmarkId::new("gaussian", radius), &radius, |b, &radius| {
+            b.iter_batched_ref(
+                || image.clone(),
+                |data| filters::gaussian_blur(data, WIDTH, HEIGHT, radius),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("box", radius), &radius, |b, &radius| {
+            b.iter_batched_ref(
+                || image.clone(),
+                |data| filters::box_blur(data, WIDTH, HEIGHT, radius),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("stack", radius), &radius, |b, &radius| {
+            b.iter_batched_ref(
+                || image.clone(),
+                |data| filters::stack_blur(data, WIDTH, HEIGHT, radius),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn convolutions(c: &mut Criterion) {
+    let image = test_image();
+    let mut group = c.benchmark_group("convolution");
+    group.throughput(Throughput::Bytes(image.len() as u64));
+    group.bench_function("edge_detection", |b| {
+        b.iter(|| filters::edge_detection(black_box(&image), WIDTH, HEIGHT))
+    });
+    group.bench_function("sharpen", |b| b.iter(|| filters::sharpen(black_box(&image), WIDTH, HEIGHT, 50)));
+    group.bench_function("canny", |b| {
+        b.iter(|| filters::canny(black_box(&image), WIDTH, HEIGHT, 40.0, 120.0))
+    });
+    group.finish();
+}
+
+fn generated(c: &mut Criterion) {
+    c.bench_function("mandelbrot", |b| b.iter(|| fractal::mandelbrot(WIDTH, HEIGHT, black_box(64))));
+
+    let signal: Vec<Complex> = (0..256 * 256).map(|i| Complex::new((i % 17) as f32, 0.0)).collect();
+    c.bench_function("fft2d_256", |b| {
+        b.iter_batched_ref(
+            || signal.clone(),
+            |data| fft2d(data, 256, 256, false),
+            criterion::BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(benches, point_filters, blurs, convolutions, generated);
+criterion_main!(benches);
@@ -0,0 +1,23 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use wasmfx_core::bench;
+
+// Native timings for the computational benchmarks, at a tenth or so of the
+// sizes the demo site races against JavaScript so a full run stays short
+// (and Fibonacci stays within u64). Run with
+//
+//   cargo bench -p wasmfx-core --target x86_64-unknown-linux-gnu --bench kernels
+
+fn kernels(c: &mut Criterion) {
+    c.bench_function("calculate_primes", |b| b.iter(|| bench::calculate_primes(black_box(100_000), || false)));
+    c.bench_function("matrix_multiply", |b| b.iter(|| bench::matrix_multiply(black_box(128), || false)));
+    c.bench_function("fibonacci_sequence", |b| b.iter(|| bench::fibonacci_sequence(black_box(90), || false)));
+    c.bench_function("compute_hashes", |b| b.iter(|| bench::compute_hashes(black_box(1_000_000), || false)));
+    c.bench_function("estimate_pi", |b| b.iter(|| bench::estimate_pi(black_box(1_000_000), 123456789, || false)));
+    c.bench_function("sort_array", |b| b.iter(|| bench::sort_array(black_box(100_000), 42, || false)));
+    c.bench_function("process_text", |b| b.iter(|| bench::process_text(black_box(1_000), || false)));
+}
+
+criterion_group!(benches, kernels);
+criterion_main!(benches);
@@ -0,0 +1,184 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::math;
+use crate::random::Lcg;
+
+// ========================================================================
+// COMPUTATIONAL BENCHMARKS
+// The pure workloads the demo site races against JavaScript. Each calls
+// `stop()` every `STOP_CHECK_INTERVAL` iterations (matrix multiplication:
+// once per result row) and returns what it has so far once it says yes;
+// pass `|| false` to always run to the end.
+// ========================================================================
+
+pub const STOP_CHECK_INTERVAL: u32 = 4096;
+
+// --- Prime Number Generation (CPU Intensive) ---
+pub fn calculate_primes(limit: u32, mut stop: impl FnMut() -> bool) -> Vec<u32> {
+    let mut primes = Vec::new();
+
+    for num in 2..=limit {
+        if num.is_multiple_of(STOP_CHECK_INTERVAL) && stop() {
+            break;
+        }
+        let mut is_prime = true;
+        let sqrt_num = math::sqrt_f64(num as f64) as u32;
+
+        for i in 2..=sqrt_num {
+            if num % i == 0 {
+                is_prime = false;
+                break;
+            }
+        }
+
+        if is_prime {
+            primes.push(num);
+        }
+    }
+
+    primes
+}
+
+// --- Matrix Multiplication (Linear Algebra) ---
+// Unfinished rows stay zero
+pub fn matrix_multiply(size: usize, mut stop: impl FnMut() -> bool) -> Vec<f64> {
+    // Create two matrices with random-ish values
+    let mut matrix_a = vec![0.0; size * size];
+    let mut matrix_b = vec![0.0; size * size];
+    let mut result = vec![0.0; size * size];
+
+    for i in 0..size {
+        for j in 0..size {
+            matrix_a[i * size + j] = ((i + j) % 10) as f64;
+            matrix_b[i * size + j] = ((i * j) % 10) as f64;
+        }
+    }
+
+    // Matrix multiplication: C = A × B
+    // This is O(n³) - very computationally intensive!
+    for i in 0..size {
+        if stop() {
+            break;
+        }
+        for j in 0..size {
+            let mut sum = 0.0;
+            for k in 0..size {
+                sum += matrix_a[i * size + k] * matrix_b[k * size + j];
+            }
+            result[i * size + j] = sum;
+        }
+    }
+
+    result
+}
+
+// --- Fibonacci ---
+pub fn fibonacci_sequence(count: u32, mut stop: impl FnMut() -> bool) -> Vec<u64> {
+    let mut sequence = Vec::with_capacity(count as usize);
+
+    if count >= 1 {
+        sequence.push(0);
+    }
+    if count >= 2 {
+        sequence.push(1);
+    }
+
+    for i in 2..count as usize {
+        if (i as u32).is_multiple_of(STOP_CHECK_INTERVAL) && stop() {
+            break;
+        }
+        let next = sequence[i - 1] + sequence[i - 2];
+        sequence.push(next);
+    }
+
+    sequence
+}
+
+// --- Hash-like Computation (Bitwise Operations) ---
+pub fn compute_hashes(iterations: u32, mut stop: impl FnMut() -> bool) -> u32 {
+    let mut hash: u32 = 0x12345678;
+
+    for i in 0..iterations {
+        if i.is_multiple_of(STOP_CHECK_INTERVAL) && stop() {
+            break;
+        }
+        // Simulate complex hash operations with bitwise math
+        hash = hash.wrapping_mul(1103515245).wrapping_add(12345);
+        hash ^= hash >> 16;
+        hash = hash.wrapping_mul(0x85ebca6b);
+        hash ^= hash >> 13;
+        hash = hash.wrapping_mul(0xc2b2ae35);
+        hash ^= hash >> 16;
+        hash = hash.wrapping_add(i);
+    }
+
+    hash
+}
+
+// --- Monte Carlo Pi Estimation (Random + Math) ---
+// A stopped run estimates from the samples it drew
+pub fn estimate_pi(samples: u32, seed: u32, mut stop: impl FnMut() -> bool) -> f64 {
+    let mut inside_circle = 0u32;
+    let mut rng = Lcg::new(seed);
+    let mut drawn = 0u32;
+
+    for sample in 0..samples {
+        if sample.is_multiple_of(STOP_CHECK_INTERVAL) && stop() {
+            break;
+        }
+        drawn += 1;
+        let x = rng.next_f64() * 2.0 - 1.0;
+        let y = rng.next_f64() * 2.0 - 1.0;
+
+        if x * x + y * y <= 1.0 {
+            inside_circle += 1;
+        }
+    }
+
+    4.0 * inside_circle as f64 / drawn as f64
+}
+
+// --- Sorting (Algorithm Performance) ---
+// The sort itself can't be interrupted, so `stop` is only asked while
+// generating the input, and stopping returns an empty array
+pub fn sort_array(size: u32, seed: u32, mut stop: impl FnMut() -> bool) -> Vec<i32> {
+    let mut arr = Vec::with_capacity(size as usize);
+    let mut rng = Lcg::new(seed);
+
+    for i in 0..size {
+        if i.is_multiple_of(STOP_CHECK_INTERVAL) && stop() {
+            return Vec::new();
+        }
+        arr.push((rng.next_u32() % 10000) as i32);
+    }
+
+    arr.sort_unstable();
+    arr
+}
+
+// --- String Processing ---
+// Swaps the case of a sentence `iterations` times and returns the first
+// 100 characters
+pub fn process_text(iterations: u32, mut stop: impl FnMut() -> bool) -> String {
+    let base_text = "The quick brown fox jumps over the lazy dog";
+    let mut result = String::new();
+
+    for i in 0..iterations {
+        if i.is_multiple_of(STOP_CHECK_INTERVAL) && stop() {
+            break;
+        }
+        for c in base_text.chars() {
+            if c.is_uppercase() {
+                result.push(c.to_lowercase().next().unwrap());
+            } else if c.is_lowercase() {
+                result.push(c.to_uppercase().next().unwrap());
+            } else {
+                result.push(c);
+            }
+        }
+    }
+
+    result.chars().take(100).collect()
+}
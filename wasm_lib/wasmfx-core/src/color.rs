@@ -0,0 +1,223 @@
+// ========================================================================
+// COLOR SPACES
+// Conversions shared by filters that need to work in linear light or in
+// another colour model. RGB arguments are 0..1: gamma-encoded sRGB for
+// HSL, HSV and YCbCr, linear for XYZ and OKLab.
+// ========================================================================
+
+use alloc::vec::Vec;
+
+use crate::math;
+
+// sRGB channel value -> linear intensity in 0..1, for all 256 inputs
+pub fn srgb_to_linear_table() -> [f32; 256] {
+    let mut table = [0.0f32; 256];
+    for (value, linear) in table.iter_mut().enumerate() {
+        let v = value as f32 / 255.0;
+        *linear = if v <= 0.04045 { v / 12.92 } else { math::powf((v + 0.055) / 1.055, 2.4) };
+    }
+    table
+}
+
+pub fn linear_to_srgb(value: f32) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    let encoded = if v <= 0.003_130_8 { v * 12.92 } else { 1.055 * math::powf(v, 1.0 / 2.4) - 0.055 };
+    (encoded * 255.0 + 0.5) as u8
+}
+
+// --- OKLab ---
+// Perceptual space (Björn Ottosson, 2020) where equal distances look like
+// equal differences; L is lightness in 0..1, a / b are the green-red and
+// blue-yellow axes. Chroma adjustments done here keep hue and lightness
+// steady, unlike scaling RGB.
+pub fn linear_to_oklab([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let l = math::cbrt(0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b);
+    let m = math::cbrt(0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b);
+    let s = math::cbrt(0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b);
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    ]
+}
+
+pub fn oklab_to_linear([l, a, b]: [f32; 3]) -> [f32; 3] {
+    let l_ = l + 0.396_337_78 * a + 0.215_803_76 * b;
+    let m_ = l - 0.105_561_346 * a - 0.063_854_17 * b;
+    let s_ = l - 0.089_484_18 * a - 1.291_485_5 * b;
+    let (l, m, s) = (l_ * l_ * l_, m_ * m_ * m_, s_ * s_ * s_);
+    [
+        4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+        -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+        -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+    ]
+}
+
+// --- HSL / HSV ---
+// Hue in degrees (0..360, 0 for grays), saturation and lightness / value
+// in 0..1
+pub fn rgb_to_hsl([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+    let lightness = (max + min) / 2.0;
+    let delta = max - min;
+    let saturation = if delta == 0.0 { 0.0 } else { delta / (1.0 - (2.0 * lightness - 1.0).abs()) };
+    [hue(r, g, b, max, delta), saturation.min(1.0), lightness]
+}
+
+pub fn hsl_to_rgb([h, s, l]: [f32; 3]) -> [f32; 3] {
+    let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    from_hue(h, chroma, l - chroma / 2.0)
+}
+
+pub fn rgb_to_hsv([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+    let delta = max - min;
+    let saturation = if max == 0.0 { 0.0 } else { delta / max };
+    [hue(r, g, b, max, delta), saturation, max]
+}
+
+pub fn hsv_to_rgb([h, s, v]: [f32; 3]) -> [f32; 3] {
+    let chroma = v * s;
+    from_hue(h, chroma, v - chroma)
+}
+
+fn hue(r: f32, g: f32, b: f32, max: f32, delta: f32) -> f32 {
+    if delta == 0.0 {
+        return 0.0;
+    }
+    let sector = if max == r {
+        let sector = (g - b) / delta;
+        if sector < 0.0 { sector + 6.0 } else { sector }
+    } else if max == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+    sector * 60.0
+}
+
+// The colour with hue `h` and `chroma`, lifted by `offset` on all channels
+fn from_hue(h: f32, chroma: f32, offset: f32) -> [f32; 3] {
+    let sector = h / 60.0 - 6.0 * math::floor(h / 360.0);
+    let rising = chroma * (1.0 - ((sector - 2.0 * math::floor(sector / 2.0)) - 1.0).abs());
+    let [r, g, b] = match sector as u32 {
+        0 => [chroma, rising, 0.0],
+        1 => [rising, chroma, 0.0],
+        2 => [0.0, chroma, rising],
+        3 => [0.0, rising, chroma],
+        4 => [rising, 0.0, chroma],
+        _ => [chroma, 0.0, rising],
+    };
+    [r + offset, g + offset, b + offset]
+}
+
+// --- XYZ / CIELAB ---
+// CIE 1931 XYZ with the sRGB (D65) white at Y = 1, and CIELAB relative to
+// it: L in 0..100, a / b roughly -128..128
+const D65_WHITE: [f32; 3] = [0.950_47, 1.0, 1.088_83];
+
+pub fn linear_to_xyz([r, g, b]: [f32; 3]) -> [f32; 3] {
+    [
+        0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b,
+        0.212_672_9 * r + 0.715_152_2 * g + 0.072_175 * b,
+        0.019_333_9 * r + 0.119_192 * g + 0.950_304_1 * b,
+    ]
+}
+
+pub fn xyz_to_linear([x, y, z]: [f32; 3]) -> [f32; 3] {
+    [
+        3.240_454_2 * x - 1.537_138_5 * y - 0.498_531_4 * z,
+        -0.969_266 * x + 1.876_010_8 * y + 0.041_556 * z,
+        0.055_643_4 * x - 0.204_025_9 * y + 1.057_225_2 * z,
+    ]
+}
+
+// Below (6/29)³ the cube root is replaced by a line, so dark colours
+// don't get an infinite slope
+const LAB_DELTA: f32 = 6.0 / 29.0;
+
+pub fn xyz_to_lab(xyz: [f32; 3]) -> [f32; 3] {
+    let f = |t: f32| {
+        if t > LAB_DELTA * LAB_DELTA * LAB_DELTA {
+            math::cbrt(t)
+        } else {
+            t / (3.0 * LAB_DELTA * LAB_DELTA) + 4.0 / 29.0
+        }
+    };
+    let [fx, fy, fz] = [f(xyz[0] / D65_WHITE[0]), f(xyz[1] / D65_WHITE[1]), f(xyz[2] / D65_WHITE[2])];
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+pub fn lab_to_xyz([l, a, b]: [f32; 3]) -> [f32; 3] {
+    let f_inv = |t: f32| {
+        if t > LAB_DELTA {
+            t * t * t
+        } else {
+            3.0 * LAB_DELTA * LAB_DELTA * (t - 4.0 / 29.0)
+        }
+    };
+    let fy = (l + 16.0) / 116.0;
+    [
+        D65_WHITE[0] * f_inv(fy + a / 500.0),
+        D65_WHITE[1] * f_inv(fy),
+        D65_WHITE[2] * f_inv(fy - b / 200.0),
+    ]
+}
+
+// --- YCbCr ---
+// Full-range BT.601, as in JPEG: Y in 0..1, Cb and Cr in -0.5..0.5
+pub fn rgb_to_ycbcr([r, g, b]: [f32; 3]) -> [f32; 3] {
+    [
+        0.299 * r + 0.587 * g + 0.114 * b,
+        -0.168_736 * r - 0.331_264 * g + 0.5 * b,
+        0.5 * r - 0.418_688 * g - 0.081_312 * b,
+    ]
+}
+
+pub fn ycbcr_to_rgb([y, cb, cr]: [f32; 3]) -> [f32; 3] {
+    [y + 1.402 * cr, y - 0.344_136 * cb - 0.714_136 * cr, y + 1.772 * cb]
+}
+
+// --- Fast Variants ---
+// Approximations for callers that would rather be quick than bit-exact
+// (`MathMode::Fast` in wasm_lib). They stay within one 8-bit step of the
+// conversions above but can round the other way.
+
+// Entries in `linear_to_srgb_table`
+pub const LINEAR_TABLE_SIZE: usize = 4096;
+
+// `linear_to_srgb` sampled evenly across 0..1
+pub fn linear_to_srgb_table() -> Vec<u8> {
+    let last = (LINEAR_TABLE_SIZE - 1) as f32;
+    (0..LINEAR_TABLE_SIZE).map(|i| linear_to_srgb(i as f32 / last)).collect()
+}
+
+pub fn linear_to_srgb_lookup(table: &[u8], value: f32) -> u8 {
+    let last = table.len() - 1;
+    table[(value.clamp(0.0, 1.0) * last as f32 + 0.5) as usize]
+}
+
+// Cube root from an exponent-bit estimate refined by two Newton steps;
+// good to about 1e-6 relative
+pub fn cbrt_fast(x: f32) -> f32 {
+    if x == 0.0 || !x.is_finite() {
+        return x;
+    }
+    let a = x.abs();
+    let mut y = f32::from_bits(a.to_bits() / 3 + 0x2a51_4067);
+    y = (2.0 * y + a / (y * y)) / 3.0;
+    y = (2.0 * y + a / (y * y)) / 3.0;
+    if x < 0.0 { -y } else { y }
+}
+
+// `linear_to_oklab` using `cbrt_fast`
+pub fn linear_to_oklab_fast([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let l = cbrt_fast(0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b);
+    let m = cbrt_fast(0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b);
+    let s = cbrt_fast(0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b);
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    ]
+}
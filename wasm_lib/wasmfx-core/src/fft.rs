@@ -0,0 +1,125 @@
+use alloc::vec;
+use core::ops::{Add, Mul, Sub};
+
+use crate::math;
+
+// ========================================================================
+// FFT
+// Iterative radix-2 Cooley-Tukey transforms used by the correlation
+// tracker (and anything else that wants to work in the frequency domain).
+// All lengths must be powers of two.
+// ========================================================================
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Complex {
+    pub re: f32,
+    pub im: f32,
+}
+
+impl Complex {
+    pub const ZERO: Complex = Complex { re: 0.0, im: 0.0 };
+
+    pub fn new(re: f32, im: f32) -> Complex {
+        Complex { re, im }
+    }
+
+    pub fn conj(self) -> Complex {
+        Complex::new(self.re, -self.im)
+    }
+
+    pub fn norm_sqr(self) -> f32 {
+        self.re * self.re + self.im * self.im
+    }
+
+    pub fn scale(self, factor: f32) -> Complex {
+        Complex::new(self.re * factor, self.im * factor)
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+// In-place 1D FFT. The inverse transform is scaled by 1/n so that
+// `fft(inverse)` after `fft(forward)` gives back the original data.
+pub fn fft(buffer: &mut [Complex], inverse: bool) {
+    let n = buffer.len();
+    if n <= 1 {
+        return;
+    }
+    debug_assert!(n.is_power_of_two(), "FFT length must be a power of two");
+
+    // Bit-reversal permutation
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            buffer.swap(i, j);
+        }
+    }
+
+    // Butterflies, doubling the transform size each pass
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut size = 2;
+    while size <= n {
+        let angle = sign * 2.0 * core::f32::consts::PI / size as f32;
+        let step = Complex::new(math::cos(angle), math::sin(angle));
+        for start in (0..n).step_by(size) {
+            let mut twiddle = Complex::new(1.0, 0.0);
+            for k in 0..size / 2 {
+                let even = buffer[start + k];
+                let odd = buffer[start + k + size / 2] * twiddle;
+                buffer[start + k] = even + odd;
+                buffer[start + k + size / 2] = even - odd;
+                twiddle = twiddle * step;
+            }
+        }
+        size *= 2;
+    }
+
+    if inverse {
+        let scale = 1.0 / n as f32;
+        for value in buffer.iter_mut() {
+            *value = value.scale(scale);
+        }
+    }
+}
+
+// In-place 2D FFT over a row-major `width` x `height` grid: rows first,
+// then columns.
+pub fn fft2d(data: &mut [Complex], width: usize, height: usize, inverse: bool) {
+    for row in data.chunks_exact_mut(width) {
+        fft(row, inverse);
+    }
+
+    let mut column = vec![Complex::ZERO; height];
+    for x in 0..width {
+        for y in 0..height {
+            column[y] = data[y * width + x];
+        }
+        fft(&mut column, inverse);
+        for y in 0..height {
+            data[y * width + x] = column[y];
+        }
+    }
+}
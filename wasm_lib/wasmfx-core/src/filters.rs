@@ -0,0 +1,519 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::math;
+
+// ========================================================================
+// IMAGE FILTERS
+// The kernels behind the WasmFX filters. The separable blurs are split
+// into a per-row horizontal pass and a per-band vertical pass so callers
+// can hand bands of rows to their own threads; `gaussian_blur`,
+// `box_blur` and `stack_blur` run both passes over the whole image on the
+// current thread.
+// ========================================================================
+
+// --- Grayscale / Invert ---
+// Integer luminance (R * 0.299 + G * 0.587 + B * 0.114); alpha is kept
+pub fn grayscale(data: &mut [u8]) {
+    for pixel in data.chunks_exact_mut(4) {
+        let gray = ((pixel[0] as u32 * 299 + pixel[1] as u32 * 587 + pixel[2] as u32 * 114) / 1000) as u8;
+        pixel[0] = gray;
+        pixel[1] = gray;
+        pixel[2] = gray;
+    }
+}
+
+pub fn invert(data: &mut [u8]) {
+    for pixel in data.chunks_exact_mut(4) {
+        pixel[0] = 255 - pixel[0];
+        pixel[1] = 255 - pixel[1];
+        pixel[2] = 255 - pixel[2];
+    }
+}
+
+// --- Gaussian Blur ---
+// The 1D kernel as (offset, weight) pairs, weights summing to 1, with
+// sigma = radius / 3. A `tap_step` above 1 keeps only every n-th tap
+// (symmetrically), trading accuracy for speed on large radii.
+pub fn gaussian_kernel(radius: i32, tap_step: i32) -> Vec<(i32, f32)> {
+    let sigma = radius as f32 / 3.0;
+    let two_sigma_sq = 2.0 * sigma * sigma;
+    let tap_step = tap_step.max(1);
+
+    let mut kernel: Vec<(i32, f32)> = (-radius..=radius)
+        .filter(|d| d % tap_step == 0)
+        .map(|d| (d, math::exp(-((d * d) as f32) / two_sigma_sq)))
+        .collect();
+    let total: f32 = kernel.iter().map(|&(_, weight)| weight).sum();
+    for (_, weight) in kernel.iter_mut() {
+        *weight /= total;
+    }
+    kernel
+}
+
+// Horizontal pass over one `width`-pixel row
+pub fn gaussian_pass_row(src: &[u8], dst: &mut [u8], width: usize, kernel: &[(i32, f32)]) {
+    for x in 0..width {
+        let mut r_sum = 0.0;
+        let mut g_sum = 0.0;
+        let mut b_sum = 0.0;
+
+        for &(dx, weight) in kernel {
+            let nx = (x as i32 + dx).max(0).min(width as i32 - 1) as usize;
+            let idx = nx * 4;
+
+            r_sum += src[idx] as f32 * weight;
+            g_sum += src[idx + 1] as f32 * weight;
+            b_sum += src[idx + 2] as f32 * weight;
+        }
+
+        let idx = x * 4;
+        dst[idx] = (r_sum + 0.5) as u8;
+        dst[idx + 1] = (g_sum + 0.5) as u8;
+        dst[idx + 2] = (b_sum + 0.5) as u8;
+    }
+}
+
+// Vertical pass writing the rows of `band`, which starts at `first_row` of
+// the `src` image. Columns go in strips of `strip` pixels so the rows each
+// tap reads stay in cache on wide images.
+pub fn gaussian_pass_columns(
+    src: &[u8],
+    band: &mut [u8],
+    first_row: usize,
+    width: usize,
+    height: usize,
+    kernel: &[(i32, f32)],
+    strip: usize,
+) {
+    let band_rows = band.len() / (width * 4).max(1);
+    for x_start in (0..width).step_by(strip.max(1)) {
+        for row in 0..band_rows {
+            let y = first_row + row;
+            for x in x_start..(x_start + strip).min(width) {
+                let mut r_sum = 0.0;
+                let mut g_sum = 0.0;
+                let mut b_sum = 0.0;
+
+                for &(dy, weight) in kernel {
+                    let ny = (y as i32 + dy).max(0).min(height as i32 - 1) as usize;
+                    let idx = (ny * width + x) * 4;
+
+                    r_sum += src[idx] as f32 * weight;
+                    g_sum += src[idx + 1] as f32 * weight;
+                    b_sum += src[idx + 2] as f32 * weight;
+                }
+
+                let idx = (row * width + x) * 4;
+                band[idx] = (r_sum + 0.5) as u8;
+                band[idx + 1] = (g_sum + 0.5) as u8;
+                band[idx + 2] = (b_sum + 0.5) as u8;
+            }
+        }
+    }
+}
+
+pub fn gaussian_blur(data: &mut [u8], width: usize, height: usize, radius: u32) {
+    let kernel = gaussian_kernel(radius as i32, 1);
+    let row_bytes = width * 4;
+    let mut temp = data.to_vec();
+    for (src_row, dst_row) in data.chunks_exact(row_bytes).zip(temp.chunks_exact_mut(row_bytes)) {
+        gaussian_pass_row(src_row, dst_row, width, &kernel);
+    }
+    gaussian_pass_columns(&temp, data, 0, width, height, &kernel, width);
+}
+
+// --- Box Blur ---
+// Three box blurs in a row converge on a Gaussian. Each box pass keeps a
+// running sum, so the cost per pixel doesn't depend on the radius at all.
+// The box widths are chosen so the result matches the variance of the
+// Gaussian blur with the same radius.
+pub const BOX_PASSES: usize = 3;
+
+// Radii of the boxes whose combined variance is closest to sigma^2
+// (Kovesi, "Fast almost-Gaussian filtering")
+pub fn box_radii(sigma: f32) -> [usize; BOX_PASSES] {
+    let n = BOX_PASSES as f32;
+    let ideal_width = math::sqrt(12.0 * sigma * sigma / n + 1.0);
+    let mut lower = math::floor(ideal_width) as i32;
+    if lower % 2 == 0 {
+        lower -= 1;
+    }
+    let lower = lower.max(1);
+    let upper = lower + 2;
+    let lower_f = lower as f32;
+    let lower_count = math::round(
+        (12.0 * sigma * sigma - n * lower_f * lower_f - 4.0 * n * lower_f - 3.0 * n) / (-4.0 * lower_f - 4.0),
+    ) as usize;
+
+    let mut radii = [0; BOX_PASSES];
+    for (i, radius) in radii.iter_mut().enumerate() {
+        let width = if i < lower_count { lower } else { upper };
+        *radius = (width as usize - 1) / 2;
+    }
+    radii
+}
+
+pub fn box_pass_row(src: &[u8], dst: &mut [u8], width: usize, radius: usize) {
+    let window = (2 * radius + 1) as u32;
+    let clamp = |x: isize| x.clamp(0, width as isize - 1) as usize;
+    let mut sums = [0u32; 3];
+    for dx in -(radius as isize)..=radius as isize {
+        let idx = clamp(dx) * 4;
+        for c in 0..3 {
+            sums[c] += src[idx + c] as u32;
+        }
+    }
+    for x in 0..width {
+        for c in 0..3 {
+            dst[x * 4 + c] = ((sums[c] + window / 2) / window) as u8;
+        }
+        let leaving = clamp(x as isize - radius as isize) * 4;
+        let entering = clamp(x as isize + radius as isize + 1) * 4;
+        for c in 0..3 {
+            sums[c] = sums[c] + src[entering + c] as u32 - src[leaving + c] as u32;
+        }
+    }
+}
+
+// Primes the column sums from the rows around `first_row`, then slides down
+// the band
+pub fn box_pass_columns(src: &[u8], band: &mut [u8], first_row: usize, width: usize, height: usize, radius: usize) {
+    let row_bytes = width * 4;
+    let window = (2 * radius + 1) as u32;
+    let clamp = |y: isize| y.clamp(0, height as isize - 1) as usize;
+    let mut sums = vec![0u32; width * 3];
+    for dy in -(radius as isize)..=radius as isize {
+        let row = &src[clamp(first_row as isize + dy) * row_bytes..][..row_bytes];
+        for (sum, pixel) in sums.chunks_exact_mut(3).zip(row.chunks_exact(4)) {
+            for c in 0..3 {
+                sum[c] += pixel[c] as u32;
+            }
+        }
+    }
+    for (row, out_row) in band.chunks_exact_mut(row_bytes).enumerate() {
+        let y = (first_row + row) as isize;
+        for (sum, pixel) in sums.chunks_exact(3).zip(out_row.chunks_exact_mut(4)) {
+            for c in 0..3 {
+                pixel[c] = ((sum[c] + window / 2) / window) as u8;
+            }
+        }
+        let leaving = &src[clamp(y - radius as isize) * row_bytes..][..row_bytes];
+        let entering = &src[clamp(y + radius as isize + 1) * row_bytes..][..row_bytes];
+        for (i, sum) in sums.chunks_exact_mut(3).enumerate() {
+            for c in 0..3 {
+                sum[c] = sum[c] + entering[i * 4 + c] as u32 - leaving[i * 4 + c] as u32;
+            }
+        }
+    }
+}
+
+pub fn box_blur(data: &mut [u8], width: usize, height: usize, radius: u32) {
+    let row_bytes = width * 4;
+    let mut temp = data.to_vec();
+    for box_radius in box_radii(radius as f32 / 3.0) {
+        if box_radius == 0 {
+            continue;
+        }
+        for (src_row, dst_row) in data.chunks_exact(row_bytes).zip(temp.chunks_exact_mut(row_bytes)) {
+            box_pass_row(src_row, dst_row, width, box_radius);
+        }
+        box_pass_columns(&temp, data, 0, width, height, box_radius);
+    }
+}
+
+// --- Stack Blur ---
+// One horizontal and one vertical pass of a triangle-shaped kernel, kept as
+// three running sums (Klingemann's stack blur), so like the box blur the
+// cost per pixel doesn't depend on the radius.
+
+// The triangle radius matching a Gaussian of `sigma`: a triangle kernel of
+// radius r has variance r(r + 2) / 6
+pub fn stack_radius(sigma: f32) -> usize {
+    math::round(math::sqrt(1.0 + 6.0 * sigma * sigma) - 1.0).max(0.0) as usize
+}
+
+// The weighted sum moves one pixel along by dropping the left half of the
+// stack (`sum_out`, pixels at or before x) and adding the right half
+// (`sum_in`, pixels after x); then the pixel at x + 1 crosses over
+pub fn stack_pass_row(src: &[u8], dst: &mut [u8], width: usize, radius: usize) {
+    let divisor = ((radius + 1) * (radius + 1)) as u32;
+    let r = radius as isize;
+    let clamp = |x: isize| x.clamp(0, width as isize - 1) as usize * 4;
+    let mut sums = [0u32; 3];
+    let mut sums_out = [0u32; 3];
+    let mut sums_in = [0u32; 3];
+    for dx in -r..=r {
+        let idx = clamp(dx);
+        let weight = (r + 1 - dx.abs()) as u32;
+        for c in 0..3 {
+            sums[c] += src[idx + c] as u32 * weight;
+            if dx <= 0 {
+                sums_out[c] += src[idx + c] as u32;
+            } else {
+                sums_in[c] += src[idx + c] as u32;
+            }
+        }
+    }
+    for x in 0..width as isize {
+        for c in 0..3 {
+            dst[x as usize * 4 + c] = ((sums[c] + divisor / 2) / divisor) as u8;
+        }
+        let leaving = clamp(x - r);
+        let entering = clamp(x + r + 1);
+        let crossing = clamp(x + 1);
+        for c in 0..3 {
+            sums[c] -= sums_out[c];
+            sums_out[c] -= src[leaving + c] as u32;
+            sums_in[c] += src[entering + c] as u32;
+            sums[c] += sums_in[c];
+            sums_in[c] -= src[crossing + c] as u32;
+            sums_out[c] += src[crossing + c] as u32;
+        }
+    }
+}
+
+pub fn stack_pass_columns(src: &[u8], band: &mut [u8], first_row: usize, width: usize, height: usize, radius: usize) {
+    let row_bytes = width * 4;
+    let divisor = ((radius + 1) * (radius + 1)) as u32;
+    let r = radius as isize;
+    let row_at = |y: isize| &src[y.clamp(0, height as isize - 1) as usize * row_bytes..][..row_bytes];
+    let mut sums = vec![0u32; width * 3];
+    let mut sums_out = vec![0u32; width * 3];
+    let mut sums_in = vec![0u32; width * 3];
+    for dy in -r..=r {
+        let row = row_at(first_row as isize + dy);
+        let weight = (r + 1 - dy.abs()) as u32;
+        let half = if dy <= 0 { &mut sums_out } else { &mut sums_in };
+        for ((sum, half), pixel) in sums.chunks_exact_mut(3).zip(half.chunks_exact_mut(3)).zip(row.chunks_exact(4)) {
+            for c in 0..3 {
+                sum[c] += pixel[c] as u32 * weight;
+                half[c] += pixel[c] as u32;
+            }
+        }
+    }
+    for (row, out_row) in band.chunks_exact_mut(row_bytes).enumerate() {
+        let y = (first_row + row) as isize;
+        for (sum, pixel) in sums.chunks_exact(3).zip(out_row.chunks_exact_mut(4)) {
+            for c in 0..3 {
+                pixel[c] = ((sum[c] + divisor / 2) / divisor) as u8;
+            }
+        }
+        let leaving = row_at(y - r);
+        let entering = row_at(y + r + 1);
+        let crossing = row_at(y + 1);
+        for i in 0..width {
+            for c in 0..3 {
+                let k = i * 3 + c;
+                let p = i * 4 + c;
+                sums[k] -= sums_out[k];
+                sums_out[k] -= leaving[p] as u32;
+                sums_in[k] += entering[p] as u32;
+                sums[k] += sums_in[k];
+                sums_in[k] -= crossing[p] as u32;
+                sums_out[k] += crossing[p] as u32;
+            }
+        }
+    }
+}
+
+pub fn stack_blur(data: &mut [u8], width: usize, height: usize, radius: u32) {
+    let stack_radius = stack_radius(radius as f32 / 3.0);
+    if stack_radius == 0 {
+        return;
+    }
+    let row_bytes = width * 4;
+    let mut temp = data.to_vec();
+    for (src_row, dst_row) in data.chunks_exact(row_bytes).zip(temp.chunks_exact_mut(row_bytes)) {
+        stack_pass_row(src_row, dst_row, width, stack_radius);
+    }
+    stack_pass_columns(&temp, data, 0, width, height, stack_radius);
+}
+
+// --- Sobel Edge Detection ---
+// Gradient magnitude of the luminance as a gray image; the one-pixel
+// border is left transparent black
+pub fn edge_detection(data: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut result = vec![0u8; data.len()];
+    let gradients = sobel_gradients(data, width, height);
+
+    // Process each pixel (except borders)
+    for y in 1..height.saturating_sub(1) {
+        for x in 1..width.saturating_sub(1) {
+            let (gx, gy) = gradients[y * width + x];
+            let magnitude = math::sqrt(gx * gx + gy * gy).min(255.0) as u8;
+
+            let idx = (y * width + x) * 4;
+            result[idx] = magnitude;
+            result[idx + 1] = magnitude;
+            result[idx + 2] = magnitude;
+            result[idx + 3] = data[idx + 3];
+        }
+    }
+
+    result
+}
+
+// Horizontal and vertical Sobel responses of the luminance at every pixel,
+// row by row; the border repeats the nearest edge pixel
+pub fn sobel_gradients(data: &[u8], width: usize, height: usize) -> Vec<(f32, f32)> {
+    // Sobel operators for edge detection
+    let sobel_x = [[-1, 0, 1], [-2, 0, 2], [-1, 0, 1]];
+    let sobel_y = [[-1, -2, -1], [0, 0, 0], [1, 2, 1]];
+
+    let mut gradients = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let mut gx = 0.0;
+            let mut gy = 0.0;
+
+            // Apply 3x3 Sobel kernel - 9 operations per pixel
+            for ky in 0..3 {
+                for kx in 0..3 {
+                    let ny = (y + ky).saturating_sub(1).min(height - 1);
+                    let nx = (x + kx).saturating_sub(1).min(width - 1);
+                    let idx = (ny * width + nx) * 4;
+
+                    // Convert to grayscale first
+                    let gray = data[idx] as f32 * 0.299 + data[idx + 1] as f32 * 0.587 + data[idx + 2] as f32 * 0.114;
+
+                    gx += gray * sobel_x[ky][kx] as f32;
+                    gy += gray * sobel_y[ky][kx] as f32;
+                }
+            }
+            gradients.push((gx, gy));
+        }
+    }
+    gradients
+}
+
+// --- Canny Edge Detection ---
+// Blur radius applied before the gradients (sigma = 4 / 3, close to the
+// usual 1.4)
+const CANNY_BLUR_RADIUS: u32 = 4;
+
+// One-pixel-wide edges as white on black, keeping alpha: the image is
+// smoothed, its Sobel gradient magnitude thinned to the local maxima
+// across each edge, and the remaining pixels kept where the magnitude is
+// at least `high`, or at least `low` and connected (8-way) to such a
+// pixel. Magnitudes are on `sobel_gradients`' scale, measured after the
+// smoothing, where a hard black to white step peaks at about 540.
+pub fn canny(data: &[u8], width: usize, height: usize, low: f32, high: f32) -> Vec<u8> {
+    let mut smoothed = data.to_vec();
+    gaussian_blur(&mut smoothed, width, height, CANNY_BLUR_RADIUS);
+    let gradients = sobel_gradients(&smoothed, width, height);
+    let magnitudes: Vec<f32> = gradients.iter().map(|&(gx, gy)| math::sqrt(gx * gx + gy * gy)).collect();
+    let magnitude_at = |x: isize, y: isize| {
+        if x < 0 || y < 0 || x >= width as isize || y >= height as isize {
+            0.0
+        } else {
+            magnitudes[y as usize * width + x as usize]
+        }
+    };
+
+    // Non-maximum suppression: keep a pixel only if it's at least as strong
+    // as both neighbours along its gradient, rounded to 0, 45, 90 or 135
+    // degrees. 1 marks weak candidates, 2 strong ones.
+    let mut labels = vec![0u8; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            let i = y * width + x;
+            let magnitude = magnitudes[i];
+            if magnitude < low {
+                continue;
+            }
+            let (gx, gy) = gradients[i];
+            // tan(22.5) and tan(67.5), to avoid atan2
+            let (ax, ay) = (gx.abs(), gy.abs());
+            let (dx, dy): (isize, isize) = if ay <= ax * 0.414_213_56 {
+                (1, 0)
+            } else if ay >= ax * 2.414_213_6 {
+                (0, 1)
+            } else if (gx > 0.0) == (gy > 0.0) {
+                (1, 1)
+            } else {
+                (1, -1)
+            };
+            let (xi, yi) = (x as isize, y as isize);
+            if magnitude >= magnitude_at(xi + dx, yi + dy) && magnitude >= magnitude_at(xi - dx, yi - dy) {
+                labels[i] = if magnitude >= high { 2 } else { 1 };
+            }
+        }
+    }
+
+    // Hysteresis: flood out from every strong pixel through weak ones
+    let mut stack: Vec<usize> = (0..labels.len()).filter(|&i| labels[i] == 2).collect();
+    while let Some(i) = stack.pop() {
+        let (x, y) = (i % width, i / width);
+        for ny in y.saturating_sub(1)..(y + 2).min(height) {
+            for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                let n = ny * width + nx;
+                if labels[n] == 1 {
+                    labels[n] = 2;
+                    stack.push(n);
+                }
+            }
+        }
+    }
+
+    let mut result = vec![0u8; data.len()];
+    for (i, (out, pixel)) in result.chunks_exact_mut(4).zip(data.chunks_exact(4)).enumerate() {
+        let level = if labels[i] == 2 { 255 } else { 0 };
+        out[..3].fill(level);
+        out[3] = pixel[3];
+    }
+    result
+}
+
+// --- Sharpen ---
+// 5x5 unsharp-mask style kernel added back at `strength` percent; the
+// two-pixel border is left transparent black
+pub fn sharpen(data: &[u8], width: usize, height: usize, strength: u32) -> Vec<u8> {
+    let strength = strength as i32;
+    let mut result = vec![0u8; data.len()];
+
+    #[rustfmt::skip]
+    let kernel: [[i32; 5]; 5] = [
+        [-1, -1, -1, -1, -1],
+        [-1,  2,  2,  2, -1],
+        [-1,  2,  8,  2, -1],
+        [-1,  2,  2,  2, -1],
+        [-1, -1, -1, -1, -1],
+    ];
+
+    let kernel_sum: i32 = 8;
+
+    for y in 2..height.saturating_sub(2) {
+        for x in 2..width.saturating_sub(2) {
+            let mut r_sum: i32 = 0;
+            let mut g_sum: i32 = 0;
+            let mut b_sum: i32 = 0;
+
+            // Apply 5x5 kernel - 25 operations per pixel!
+            for (ky, row) in kernel.iter().enumerate() {
+                for (kx, &k_val) in row.iter().enumerate() {
+                    let ny = y + ky - 2;
+                    let nx = x + kx - 2;
+                    let idx = (ny * width + nx) * 4;
+
+                    r_sum += data[idx] as i32 * k_val;
+                    g_sum += data[idx + 1] as i32 * k_val;
+                    b_sum += data[idx + 2] as i32 * k_val;
+                }
+            }
+
+            // Apply strength and clamp
+            let orig_idx = (y * width + x) * 4;
+            let orig_r = data[orig_idx] as i32;
+            let orig_g = data[orig_idx + 1] as i32;
+            let orig_b = data[orig_idx + 2] as i32;
+
+            result[orig_idx] = (orig_r + (r_sum * strength) / (kernel_sum * 100)).clamp(0, 255) as u8;
+            result[orig_idx + 1] = (orig_g + (g_sum * strength) / (kernel_sum * 100)).clamp(0, 255) as u8;
+            result[orig_idx + 2] = (orig_b + (b_sum * strength) / (kernel_sum * 100)).clamp(0, 255) as u8;
+            result[orig_idx + 3] = data[orig_idx + 3];
+        }
+    }
+
+    result
+}
@@ -0,0 +1,118 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+// ========================================================================
+// FRACTALS
+// Images generated from scratch rather than filtered.
+// ========================================================================
+
+// --- Mandelbrot Set ---
+// The view covers -2.5..1 on the real axis and -1..1 on the imaginary one.
+// Points inside the set are black, the rest are coloured by escape time.
+pub fn mandelbrot(width: usize, height: usize, max_iterations: usize) -> Vec<u8> {
+    let mut result = vec![0u8; width * height * 4];
+    for (y, row) in result.chunks_exact_mut((width * 4).max(1)).enumerate() {
+        mandelbrot_row(row, y, width, height, max_iterations);
+    }
+    result
+}
+
+// Renders row `y` of a `width` x `height` image into `row`
+pub fn mandelbrot_row(row: &mut [u8], y: usize, width: usize, height: usize, max_iterations: usize) {
+    let x_min = -2.5;
+    let x_max = 1.0;
+    let y_min = -1.0;
+    let y_max = 1.0;
+
+    let x_scale = (x_max - x_min) / width as f64;
+    let y_scale = (y_max - y_min) / height as f64;
+    let y0 = y_min + y as f64 * y_scale;
+
+    for (px, pixel) in row.chunks_exact_mut(4).enumerate() {
+        // Map pixel to complex plane
+        let x0 = x_min + px as f64 * x_scale;
+        shade(pixel, escape_time((0.0, 0.0), (x0, y0), max_iterations), max_iterations);
+    }
+}
+
+// Iterations of z = z² + c, starting from `z`, before |z| passes 2 (or
+// `max_iterations` if it never does)
+fn escape_time((mut x, mut y): (f64, f64), (cx, cy): (f64, f64), max_iterations: usize) -> usize {
+    let mut iteration = 0;
+    while x * x + y * y <= 4.0 && iteration < max_iterations {
+        let xtemp = x * x - y * y + cx;
+        y = 2.0 * x * y + cy;
+        x = xtemp;
+        iteration += 1;
+    }
+    iteration
+}
+
+fn shade(pixel: &mut [u8], iteration: usize, max_iterations: usize) {
+    if iteration == max_iterations {
+        // Point is in the set - black
+        pixel[0] = 0;
+        pixel[1] = 0;
+        pixel[2] = 0;
+    } else {
+        // Color based on escape time
+        let ratio = iteration as f32 / max_iterations as f32;
+        pixel[0] = (255.0 * (1.0 - ratio)) as u8;
+        pixel[1] = (255.0 * crate::math::sqrt(ratio)) as u8;
+        pixel[2] = (255.0 * ratio) as u8;
+    }
+    pixel[3] = 255;
+}
+
+// --- Map Tiles ---
+// Slippy-map tiling (the scheme Leaflet and OpenLayers use): at zoom `z`
+// the world is 2^z x 2^z tiles, tile (0, 0) in the top-left corner, and
+// each tile splits into four at the next zoom level. The world is a
+// square of the complex plane around the fractal, imaginary axis pointing
+// up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fractal {
+    Mandelbrot,
+    // The Julia set for z = z² + c
+    Julia { c: (f64, f64) },
+}
+
+impl Fractal {
+    // Top-left corner and side length of the world square
+    pub fn world(self) -> ((f64, f64), f64) {
+        match self {
+            Fractal::Mandelbrot => ((-2.5, 2.0), 4.0),
+            Fractal::Julia { .. } => ((-2.0, 2.0), 4.0),
+        }
+    }
+}
+
+// `tile_size` x `tile_size` RGBA tile, coloured like `mandelbrot`. Pixels
+// are sampled at their centres, so neighbouring tiles line up exactly.
+pub fn render_tile(
+    fractal: Fractal,
+    tile_x: u32,
+    tile_y: u32,
+    zoom_level: u32,
+    tile_size: usize,
+    max_iterations: usize,
+) -> Vec<u8> {
+    let ((left, top), side) = fractal.world();
+    let span = side / (1u64 << zoom_level) as f64;
+    let pixel_size = span / tile_size as f64;
+    let (x_start, y_start) = (left + tile_x as f64 * span, top - tile_y as f64 * span);
+
+    let mut result = vec![0u8; tile_size * tile_size * 4];
+    for (py, row) in result.chunks_exact_mut((tile_size * 4).max(1)).enumerate() {
+        let im = y_start - (py as f64 + 0.5) * pixel_size;
+        for (px, pixel) in row.chunks_exact_mut(4).enumerate() {
+            let re = x_start + (px as f64 + 0.5) * pixel_size;
+            let iteration = match fractal {
+                Fractal::Mandelbrot => escape_time((0.0, 0.0), (re, im), max_iterations),
+                Fractal::Julia { c } => escape_time((re, im), c, max_iterations),
+            };
+            shade(pixel, iteration, max_iterations);
+        }
+    }
+    result
+}
@@ -0,0 +1,30 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// ========================================================================
+// WASMFX CORE
+// The algorithms behind the WasmFX filters with no wasm-bindgen, logging
+// or global settings, so native apps and benchmarks can use them as a
+// plain Rust library. The `wasm_lib` crate wraps them with input checks,
+// cancellation, quality tiers and the worker pool, and re-exports it as
+// `wasm_lib::wasmfx_core`.
+//
+// Images are row-major RGBA, four bytes per pixel, and the functions
+// assume `data.len() == width * height * 4`; they panic otherwise, so
+// validate dimensions before calling them.
+//
+// Builds without the `std` feature are `no_std` (they still need `alloc`)
+// and get their float math from `libm`.
+// ========================================================================
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("wasmfx-core needs either the `std` or the `libm` feature for float math");
+
+extern crate alloc;
+
+pub mod bench;
+pub mod color;
+pub mod fft;
+pub mod filters;
+pub mod fractal;
+mod math;
+pub mod random;
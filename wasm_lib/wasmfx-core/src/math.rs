@@ -0,0 +1,43 @@
+// ========================================================================
+// FLOAT MATH
+// `f32` methods like `sqrt` and `exp` live in std; `no_std` builds use the
+// same functions from `libm` instead.
+// ========================================================================
+
+#[cfg(feature = "std")]
+mod imp {
+    pub fn sqrt(x: f32) -> f32 {
+        x.sqrt()
+    }
+    pub fn sqrt_f64(x: f64) -> f64 {
+        x.sqrt()
+    }
+    pub fn exp(x: f32) -> f32 {
+        x.exp()
+    }
+    pub fn powf(x: f32, y: f32) -> f32 {
+        x.powf(y)
+    }
+    pub fn cbrt(x: f32) -> f32 {
+        x.cbrt()
+    }
+    pub fn sin(x: f32) -> f32 {
+        x.sin()
+    }
+    pub fn cos(x: f32) -> f32 {
+        x.cos()
+    }
+    pub fn floor(x: f32) -> f32 {
+        x.floor()
+    }
+    pub fn round(x: f32) -> f32 {
+        x.round()
+    }
+}
+
+#[cfg(not(feature = "std"))]
+mod imp {
+    pub use libm::{sqrt as sqrt_f64, cbrtf as cbrt, cosf as cos, expf as exp, floorf as floor, powf, roundf as round, sinf as sin, sqrtf as sqrt};
+}
+
+pub(crate) use imp::*;
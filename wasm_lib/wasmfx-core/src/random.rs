@@ -0,0 +1,26 @@
+// ========================================================================
+// RANDOM NUMBERS
+// The same 32-bit LCG the demo site's JavaScript benchmarks use, so both
+// sides do identical work for a given seed. Fast and reproducible, not
+// statistically strong.
+// ========================================================================
+
+pub struct Lcg {
+    state: u32,
+}
+
+impl Lcg {
+    pub fn new(seed: u32) -> Lcg {
+        Lcg { state: seed }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.state = self.state.wrapping_mul(1103515245).wrapping_add(12345);
+        self.state
+    }
+
+    // Uniform in [0, 1]
+    pub fn next_f64(&mut self) -> f64 {
+        self.next_u32() as f64 / u32::MAX as f64
+    }
+}
@@ -0,0 +1,191 @@
+// Property tests for invariants every filter has to keep, run natively
+// over random images:
+//
+//   cargo test -p wasmfx-core --target x86_64-unknown-linux-gnu
+//
+// When adding a filter to wasmfx-core, add it to the checks here that
+// apply to it.
+#![cfg(not(target_arch = "wasm32"))]
+
+use proptest::prelude::*;
+use wasmfx_core::color::{
+    hsl_to_rgb, hsv_to_rgb, lab_to_xyz, linear_to_oklab, linear_to_oklab_fast, linear_to_srgb, linear_to_srgb_lookup,
+    linear_to_srgb_table, linear_to_xyz, oklab_to_linear, rgb_to_hsl, rgb_to_hsv, rgb_to_ycbcr, srgb_to_linear_table,
+    xyz_to_lab, xyz_to_linear, ycbcr_to_rgb,
+};
+use wasmfx_core::fft::{fft, Complex};
+use wasmfx_core::{filters, fractal};
+
+// (width, height, RGBA bytes) for images up to 24x24
+fn image() -> impl Strategy<Value = (usize, usize, Vec<u8>)> {
+    (1usize..=24, 1usize..=24).prop_flat_map(|(w, h)| (Just(w), Just(h), prop::collection::vec(any::<u8>(), w * h * 4)))
+}
+
+fn alphas(data: &[u8]) -> Vec<u8> {
+    data.chunks_exact(4).map(|pixel| pixel[3]).collect()
+}
+
+type InPlaceFilter = fn(&mut [u8], usize, usize);
+type Blur = fn(&mut [u8], usize, usize, u32);
+type Conversion = fn([f32; 3]) -> [f32; 3];
+
+// Every in-place filter, by name, for the checks they all share
+fn in_place_filters() -> Vec<(&'static str, InPlaceFilter)> {
+    vec![
+        ("grayscale", |data, _, _| filters::grayscale(data)),
+        ("invert", |data, _, _| filters::invert(data)),
+        ("gaussian_blur", |data, w, h| filters::gaussian_blur(data, w, h, 4)),
+        ("box_blur", |data, w, h| filters::box_blur(data, w, h, 4)),
+        ("stack_blur", |data, w, h| filters::stack_blur(data, w, h, 4)),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn in_place_filters_keep_alpha((w, h, data) in image()) {
+        for (name, filter) in in_place_filters() {
+            let mut out = data.clone();
+            filter(&mut out, w, h);
+            prop_assert_eq!(out.len(), data.len(), "{} changed the length", name);
+            prop_assert_eq!(alphas(&out), alphas(&data), "{} changed alpha", name);
+        }
+    }
+
+    #[test]
+    fn convolutions_keep_length_and_interior_alpha((w, h, data) in image()) {
+        let outputs = [
+            ("edge_detection", filters::edge_detection(&data, w, h), 1),
+            ("sharpen", filters::sharpen(&data, w, h, 75), 2),
+            ("canny", filters::canny(&data, w, h, 40.0, 120.0), 0),
+        ];
+        for (name, out, border) in outputs {
+            prop_assert_eq!(out.len(), data.len(), "{} changed the length", name);
+            for y in border..h.saturating_sub(border) {
+                for x in border..w.saturating_sub(border) {
+                    let idx = (y * w + x) * 4 + 3;
+                    prop_assert_eq!(out[idx], data[idx], "{} changed alpha at ({}, {})", name, x, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn grayscale_is_idempotent((_, _, data) in image()) {
+        let mut once = data.clone();
+        filters::grayscale(&mut once);
+        let mut twice = once.clone();
+        filters::grayscale(&mut twice);
+        prop_assert_eq!(once, twice);
+    }
+
+    #[test]
+    fn invert_twice_is_identity((_, _, data) in image()) {
+        let mut out = data.clone();
+        filters::invert(&mut out);
+        filters::invert(&mut out);
+        prop_assert_eq!(out, data);
+    }
+
+    #[test]
+    fn blurs_keep_flat_images_flat(w in 1usize..=24, h in 1usize..=24, pixel in any::<[u8; 4]>(), radius in 1u32..=12) {
+        let data: Vec<u8> = pixel.iter().copied().cycle().take(w * h * 4).collect();
+        let blurs: [(&str, Blur); 3] = [
+            ("gaussian_blur", filters::gaussian_blur),
+            ("box_blur", filters::box_blur),
+            ("stack_blur", filters::stack_blur),
+        ];
+        for (name, blur) in blurs {
+            let mut out = data.clone();
+            blur(&mut out, w, h, radius);
+            prop_assert_eq!(&out, &data, "{} changed a flat image", name);
+        }
+    }
+
+    #[test]
+    fn mandelbrot_fills_every_pixel(w in 1usize..=32, h in 1usize..=32, iterations in 1usize..=64) {
+        let out = fractal::mandelbrot(w, h, iterations);
+        prop_assert_eq!(out.len(), w * h * 4);
+        prop_assert!(alphas(&out).iter().all(|&a| a == 255));
+    }
+
+    #[test]
+    fn fractal_tiles_split_into_their_children(zoom in 0u32..6, x in 0u32..64, y in 0u32..64, julia in any::<bool>()) {
+        // A 2-pixel tile's pixels sit at the centres of its four
+        // 1-pixel children at the next zoom level
+        let fractal = if julia { fractal::Fractal::Julia { c: (-0.8, 0.156) } } else { fractal::Fractal::Mandelbrot };
+        let (x, y) = (x % (1 << zoom), y % (1 << zoom));
+        let parent = fractal::render_tile(fractal, x, y, zoom, 2, 64);
+        for (i, pixel) in parent.chunks_exact(4).enumerate() {
+            let (cx, cy) = (2 * x + (i % 2) as u32, 2 * y + (i / 2) as u32);
+            prop_assert_eq!(pixel, &fractal::render_tile(fractal, cx, cy, zoom + 1, 1, 64)[..]);
+        }
+    }
+
+    #[test]
+    fn fft_round_trips(log_len in 0u32..=8, seed in any::<u64>()) {
+        let len = 1usize << log_len;
+        let original: Vec<Complex> = (0..len)
+            .map(|i| {
+                let v = seed.wrapping_mul(i as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+                Complex::new((v >> 40) as f32 / 65536.0 - 128.0, (v & 0xFFFF) as f32 / 256.0 - 128.0)
+            })
+            .collect();
+        let mut buffer = original.clone();
+        fft(&mut buffer, false);
+        fft(&mut buffer, true);
+        for (a, b) in original.iter().zip(&buffer) {
+            prop_assert!((*a - *b).norm_sqr().sqrt() < 1e-2, "{:?} came back as {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn oklab_round_trips(rgb in any::<[u8; 3]>()) {
+        let to_linear = srgb_to_linear_table();
+        let linear = rgb.map(|c| to_linear[c as usize]);
+        let back = oklab_to_linear(linear_to_oklab(linear)).map(linear_to_srgb);
+        prop_assert_eq!(back, rgb);
+    }
+
+    #[test]
+    fn lab_round_trips(rgb in any::<[u8; 3]>()) {
+        let to_linear = srgb_to_linear_table();
+        let linear = rgb.map(|c| to_linear[c as usize]);
+        let back = xyz_to_linear(lab_to_xyz(xyz_to_lab(linear_to_xyz(linear)))).map(linear_to_srgb);
+        prop_assert_eq!(back, rgb);
+    }
+
+    #[test]
+    fn gamma_space_models_round_trip(rgb in any::<[u8; 3]>()) {
+        let encoded = rgb.map(|c| c as f32 / 255.0);
+        let byte = |v: f32| (v * 255.0).round() as u8;
+        let conversions: [(&str, Conversion, Conversion); 3] = [
+            ("hsl", rgb_to_hsl, hsl_to_rgb),
+            ("hsv", rgb_to_hsv, hsv_to_rgb),
+            ("ycbcr", rgb_to_ycbcr, ycbcr_to_rgb),
+        ];
+        for (name, forward, back) in conversions {
+            let model = forward(encoded);
+            prop_assert_eq!(back(model).map(byte), rgb, "{} went through {:?}", name, model);
+        }
+    }
+
+    #[test]
+    fn fast_conversions_stay_within_one_step(rgb in any::<[u8; 3]>()) {
+        let to_linear = srgb_to_linear_table();
+        let encode = linear_to_srgb_table();
+        let linear = rgb.map(|c| to_linear[c as usize]);
+        let exact = oklab_to_linear(linear_to_oklab(linear)).map(linear_to_srgb);
+        let fast = oklab_to_linear(linear_to_oklab_fast(linear)).map(|v| linear_to_srgb_lookup(&encode, v));
+        for (e, f) in exact.iter().zip(&fast) {
+            prop_assert!(e.abs_diff(*f) <= 1, "{:?}: exact {:?}, fast {:?}", rgb, exact, fast);
+        }
+    }
+}
+
+#[test]
+fn srgb_round_trips_every_value() {
+    let to_linear = srgb_to_linear_table();
+    for value in 0..=255u8 {
+        assert_eq!(linear_to_srgb(to_linear[value as usize]), value);
+    }
+}